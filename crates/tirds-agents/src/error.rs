@@ -14,6 +14,9 @@ pub enum AgentError {
     #[error("Agent disabled: {0}")]
     Disabled(String),
 
+    #[error("Stale cache data: {0}")]
+    StaleData(String),
+
     #[error("Cache error: {0}")]
     Cache(#[from] tirds_cache::CacheError),
 
@@ -0,0 +1,148 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use tirds_models::agent_message::AgentResponse;
+use tirds_models::trade_decision::{DataSourceRef, InformationRelevance};
+
+/// Measure the age of the data behind each specialist's response.
+///
+/// For each response, the rows it reports in `data_sources_consulted` are looked up
+/// in the snapshot; if none match (e.g., the agent reported free-form names), every
+/// row in the snapshot is used, since that is what the agent received. Freshness is
+/// the age of the *oldest* matching row, so a domain is only as fresh as its stalest input.
+///
+/// Returns domain → age in seconds. Domains with no datable rows are omitted.
+pub fn measure_freshness(
+    sources: &[DataSourceRef],
+    responses: &[AgentResponse],
+    now: DateTime<Utc>,
+) -> BTreeMap<String, i64> {
+    let mut freshness = BTreeMap::new();
+    for response in responses {
+        let consulted: Vec<&DataSourceRef> = sources
+            .iter()
+            .filter(|s| response.data_sources_consulted.contains(&s.key))
+            .collect();
+        let rows: Vec<&DataSourceRef> = if consulted.is_empty() {
+            sources.iter().collect()
+        } else {
+            consulted
+        };
+
+        let oldest = rows
+            .iter()
+            .filter_map(|s| DateTime::parse_from_rfc3339(&s.updated_at).ok())
+            .map(|t| t.with_timezone(&Utc))
+            .min();
+        if let Some(oldest) = oldest {
+            let age = (now - oldest).num_seconds().max(0);
+            freshness.insert(response.domain.clone(), age);
+        }
+    }
+    freshness
+}
+
+/// Overwrite the synthesizer's `freshness_seconds` with measured values.
+///
+/// Contributions are matched to domains by `source_name`; unmatched contributions
+/// keep whatever the synthesizer reported.
+pub fn apply_measured_freshness(
+    relevance: &mut InformationRelevance,
+    measured: &BTreeMap<String, i64>,
+) {
+    for contribution in &mut relevance.source_contributions {
+        if let Some(&age) = measured.get(&contribution.source_name) {
+            contribution.freshness_seconds = age;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use rust_decimal_macros::dec;
+    use tirds_models::trade_decision::SourceContribution;
+    use uuid::Uuid;
+
+    fn source(key: &str, updated_at: DateTime<Utc>) -> DataSourceRef {
+        DataSourceRef {
+            key: key.to_string(),
+            category: "indicator".to_string(),
+            source: "test".to_string(),
+            updated_at: updated_at.to_rfc3339(),
+        }
+    }
+
+    fn response(domain: &str, consulted: &[&str]) -> AgentResponse {
+        AgentResponse {
+            request_id: Uuid::new_v4(),
+            agent_name: domain.to_string(),
+            domain: domain.to_string(),
+            confidence: dec!(0.50),
+            reasoning: String::new(),
+            analysis: serde_json::Value::Null,
+            data_sources_consulted: consulted.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn uses_oldest_consulted_row() {
+        let now = Utc::now();
+        let sources = vec![
+            source("indicator:rsi_14:AAPL", now - Duration::seconds(30)),
+            source("bars:AAPL:5m", now - Duration::seconds(300)),
+            source("ref:VIX", now - Duration::seconds(3600)),
+        ];
+        let responses = vec![response(
+            "technical",
+            &["indicator:rsi_14:AAPL", "bars:AAPL:5m"],
+        )];
+
+        let measured = measure_freshness(&sources, &responses, now);
+        assert_eq!(measured.get("technical"), Some(&300));
+    }
+
+    #[test]
+    fn falls_back_to_whole_snapshot_when_no_keys_match() {
+        let now = Utc::now();
+        let sources = vec![
+            source("indicator:rsi_14:AAPL", now - Duration::seconds(30)),
+            source("ref:VIX", now - Duration::seconds(3600)),
+        ];
+        let responses = vec![response("macro", &["vix_data"])];
+
+        let measured = measure_freshness(&sources, &responses, now);
+        assert_eq!(measured.get("macro"), Some(&3600));
+    }
+
+    #[test]
+    fn empty_snapshot_yields_no_measurement() {
+        let measured = measure_freshness(&[], &[response("sentiment", &[])], Utc::now());
+        assert!(measured.is_empty());
+    }
+
+    #[test]
+    fn overwrites_matching_contributions_only() {
+        let mut relevance = InformationRelevance {
+            score: dec!(0.80),
+            source_contributions: vec![
+                SourceContribution {
+                    source_name: "technical".to_string(),
+                    relevance: dec!(0.90),
+                    freshness_seconds: 5,
+                },
+                SourceContribution {
+                    source_name: "news_flow".to_string(),
+                    relevance: dec!(0.50),
+                    freshness_seconds: 60,
+                },
+            ],
+        };
+        let measured = BTreeMap::from([("technical".to_string(), 900)]);
+
+        apply_measured_freshness(&mut relevance, &measured);
+        assert_eq!(relevance.source_contributions[0].freshness_seconds, 900);
+        assert_eq!(relevance.source_contributions[1].freshness_seconds, 60);
+    }
+}
@@ -1,9 +1,11 @@
 pub mod claude_cli;
 pub mod error;
+pub mod freshness;
 pub mod orchestrator;
 pub mod parser;
 pub mod prompts;
 pub mod specialist;
+pub mod staleness;
 
 pub mod test_support;
 
@@ -1,9 +1,13 @@
+use std::collections::BTreeMap;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
-use tirds_cache::CacheReader;
+use tirds_cache::{CacheSource, DomainSnapshot, SymbolDemand};
 use tirds_models::agent_message::{AgentRequest, AgentResponse};
+use tirds_models::cache_schema::key_patterns;
 use tirds_models::config::AgentsConfig;
+use tirds_models::regime::MARKET_SCOPE;
+use tirds_models::symbol_request::SymbolRequestStatus;
 use tirds_models::trade_decision::*;
 use tirds_models::trade_input::TradeProposal;
 use tracing::{error, info, warn};
@@ -11,37 +15,93 @@ use uuid::Uuid;
 
 use crate::claude_cli::{invoke_claude, ClaudeCliConfig};
 use crate::error::AgentError;
+use crate::freshness::{apply_measured_freshness, measure_freshness};
 use crate::parser::extract_json;
-use crate::prompts::synthesizer_system_prompt;
+use crate::prompts::{prompt_version, synthesizer_system_prompt, SYNTHESIZER_PROMPT_VERSION};
 use crate::specialist::SpecialistAgent;
+use crate::staleness::{apply_staleness_policy, describe_stale, enforce_staleness};
 
 /// The orchestrator coordinates specialist agents and produces a TradeDecision.
 pub struct Orchestrator {
     specialists: Vec<Arc<dyn SpecialistAgent>>,
-    cache: Arc<CacheReader>,
+    cache: Arc<dyn CacheSource>,
     config: AgentsConfig,
+    demand: Option<Arc<SymbolDemand>>,
 }
 
 impl Orchestrator {
     pub fn new(
         specialists: Vec<Arc<dyn SpecialistAgent>>,
-        cache: Arc<CacheReader>,
+        cache: Arc<dyn CacheSource>,
         config: AgentsConfig,
     ) -> Self {
         Self {
             specialists,
             cache,
             config,
+            demand: None,
         }
     }
 
+    /// Enable on-demand loading: symbols with no market data in the cache are
+    /// enqueued for the loader (see `AgentsConfig::on_demand`).
+    pub fn with_symbol_demand(mut self, demand: Arc<SymbolDemand>) -> Self {
+        self.demand = Some(demand);
+        self
+    }
+
+    /// Build the symbol's snapshot, asking the loader for it first if the cache
+    /// has no market data and on-demand loading is enabled.
+    async fn load_snapshot(&self, symbol: &str) -> Result<DomainSnapshot, AgentError> {
+        let snapshot = self.cache.snapshot(symbol).await?;
+        let demand = match &self.demand {
+            Some(demand) if self.config.on_demand.enabled => demand,
+            _ => return Ok(snapshot),
+        };
+        if has_market_data(&snapshot) {
+            return Ok(snapshot);
+        }
+
+        demand.request(symbol).await?;
+        info!(symbol, "No market data cached; requested on-demand load");
+
+        let deadline = Instant::now() + Duration::from_secs(self.config.on_demand.wait_seconds);
+        let poll = Duration::from_millis(self.config.on_demand.poll_interval_millis);
+        while Instant::now() < deadline {
+            tokio::time::sleep(poll).await;
+            match demand.get(symbol).await? {
+                Some(req) if req.status == SymbolRequestStatus::Loaded => {
+                    return Ok(self.cache.snapshot(symbol).await?);
+                }
+                Some(req) if req.status == SymbolRequestStatus::Failed => {
+                    warn!(symbol, error = ?req.error, "Loader could not load requested symbol");
+                    return Ok(snapshot);
+                }
+                _ => {}
+            }
+        }
+
+        if self.config.on_demand.wait_seconds > 0 {
+            warn!(symbol, "Timed out waiting for on-demand load");
+        }
+        Ok(snapshot)
+    }
+
     /// Evaluate a trade proposal by fanning out to specialists and synthesizing.
     pub async fn evaluate(&self, proposal: &TradeProposal) -> Result<TradeDecision, AgentError> {
         let start = Instant::now();
         info!(symbol = %proposal.symbol, id = %proposal.id, "Starting evaluation");
 
         // 1. Pre-fetch domain data from cache
-        let domain_snapshot = self.cache.build_domain_snapshot(&proposal.symbol)?;
+        let snapshot = self.load_snapshot(&proposal.symbol).await?;
+        let stale =
+            snapshot.stale_entries(&self.config.staleness.max_age_seconds, chrono::Utc::now());
+        if !stale.is_empty() {
+            warn!(symbol = %proposal.symbol, stale = %describe_stale(&stale), "Snapshot contains stale data");
+            enforce_staleness(&stale, &self.config.staleness)?;
+        }
+        let regime = shared_regime(&snapshot.data, &proposal.symbol);
+        let domain_snapshot = snapshot.data;
 
         // 2. Fan-out to specialists in parallel
         let mut handles = Vec::new();
@@ -102,10 +162,35 @@ impl Orchestrator {
             }
         }
 
-        // 4. Synthesize final decision
-        let decision = self
-            .synthesize(proposal, &agent_responses, &agent_reports, start.elapsed())
+        // 4. Measure how old each specialist's input data actually was
+        let freshness = measure_freshness(&snapshot.sources, &agent_responses, chrono::Utc::now());
+
+        // 5. Synthesize final decision
+        let mut decision = self
+            .synthesize(
+                proposal,
+                &agent_responses,
+                &agent_reports,
+                &freshness,
+                &regime,
+                start.elapsed(),
+            )
             .await?;
+        apply_measured_freshness(&mut decision.information_relevance, &freshness);
+        apply_staleness_policy(&mut decision, &stale, &self.config.staleness);
+
+        let mut agents = specialist_provenance(&self.specialists);
+        agents.push(AgentProvenance {
+            agent_name: "synthesizer".to_string(),
+            domain: "synthesizer".to_string(),
+            model: Some(self.config.synthesizer_model.clone()),
+            prompt_version: Some(SYNTHESIZER_PROMPT_VERSION.to_string()),
+        });
+        decision.provenance = DecisionProvenance {
+            snapshot_hash: snapshot.fingerprint,
+            data_sources: snapshot.sources,
+            agents,
+        };
 
         info!(
             symbol = %proposal.symbol,
@@ -122,11 +207,15 @@ impl Orchestrator {
         proposal: &TradeProposal,
         responses: &[AgentResponse],
         reports: &[AgentReport],
+        freshness: &BTreeMap<String, i64>,
+        regime: &serde_json::Value,
         total_elapsed: Duration,
     ) -> Result<TradeDecision, AgentError> {
         let synthesis_input = serde_json::json!({
             "proposal": proposal,
             "agent_reports": responses,
+            "data_freshness_seconds": freshness,
+            "regime": regime,
         });
 
         let system_prompt = synthesizer_system_prompt();
@@ -147,7 +236,37 @@ impl Orchestrator {
     }
 }
 
+/// Record the model and prompt template version used by each specialist.
+fn specialist_provenance(specialists: &[Arc<dyn SpecialistAgent>]) -> Vec<AgentProvenance> {
+    specialists
+        .iter()
+        .map(|spec| AgentProvenance {
+            agent_name: spec.name().to_string(),
+            domain: spec.domain().to_string(),
+            model: spec.model().map(|m| m.to_string()),
+            prompt_version: prompt_version(spec.domain()).map(|v| v.to_string()),
+        })
+        .collect()
+}
+
+/// Whether the snapshot contains any of the symbol's own market data rows.
+fn has_market_data(snapshot: &DomainSnapshot) -> bool {
+    snapshot.sources.iter().any(|s| s.category == "market_data")
+}
+
+/// The market and symbol regime entries from the snapshot, passed to the
+/// synthesizer so it shares the specialists' regime view. Missing entries are null.
+fn shared_regime(data: &serde_json::Value, symbol: &str) -> serde_json::Value {
+    serde_json::json!({
+        "market": data.get(key_patterns::regime(MARKET_SCOPE)),
+        "symbol": data.get(key_patterns::regime(symbol)),
+    })
+}
+
 /// Build a TradeDecision from the synthesizer's JSON output.
+///
+/// The returned decision carries an empty `provenance`; `Orchestrator::evaluate`
+/// fills it in from the snapshot and agents it used.
 pub fn build_trade_decision(
     proposal: &TradeProposal,
     synthesized: &serde_json::Value,
@@ -205,6 +324,7 @@ pub fn build_trade_decision(
         timeline,
         agent_reports: reports.to_vec(),
         processing_time_ms: total_elapsed.as_millis() as u64,
+        provenance: DecisionProvenance::default(),
     })
 }
 
@@ -213,7 +333,7 @@ mod tests {
     use super::*;
     use crate::specialist::tests::MockSpecialist;
     use rust_decimal_macros::dec;
-    use tirds_cache::SqliteReader;
+    use tirds_cache::InMemorySource;
     use tirds_models::trade_input::{LegSide, TradeLeg, INPUT_SCHEMA_VERSION};
 
     fn test_proposal() -> TradeProposal {
@@ -240,9 +360,8 @@ mod tests {
         }
     }
 
-    fn test_cache() -> Arc<CacheReader> {
-        let sqlite = SqliteReader::open_in_memory().unwrap();
-        Arc::new(CacheReader::new(sqlite, 100, Duration::from_secs(60)))
+    fn test_cache() -> Arc<dyn CacheSource> {
+        Arc::new(InMemorySource::new())
     }
 
     #[test]
@@ -353,4 +472,69 @@ mod tests {
         // Verify construction - failure handling is tested in the evaluate flow
         assert_eq!(orchestrator.specialists.len(), 2);
     }
+
+    #[tokio::test]
+    async fn load_snapshot_enqueues_missing_symbol() {
+        let demand = Arc::new(SymbolDemand::open_in_memory().unwrap());
+        let mut config = AgentsConfig::default();
+        config.on_demand.enabled = true;
+        config.on_demand.wait_seconds = 0;
+
+        let orchestrator =
+            Orchestrator::new(vec![], test_cache(), config).with_symbol_demand(demand.clone());
+        let snapshot = orchestrator.load_snapshot("NVDA").await.unwrap();
+
+        assert!(snapshot.sources.is_empty());
+        let req = demand.get("NVDA").await.unwrap().unwrap();
+        assert_eq!(req.status, SymbolRequestStatus::Pending);
+    }
+
+    #[tokio::test]
+    async fn load_snapshot_skips_demand_when_disabled() {
+        let demand = Arc::new(SymbolDemand::open_in_memory().unwrap());
+        let orchestrator = Orchestrator::new(vec![], test_cache(), AgentsConfig::default())
+            .with_symbol_demand(demand.clone());
+
+        orchestrator.load_snapshot("NVDA").await.unwrap();
+        assert!(demand.get("NVDA").await.unwrap().is_none());
+    }
+
+    #[test]
+    fn specialist_provenance_records_models_and_prompt_versions() {
+        let specialists: Vec<Arc<dyn SpecialistAgent>> = vec![
+            Arc::new(crate::specialist::ClaudeSpecialist::new(
+                "technical".to_string(),
+                "technical".to_string(),
+                "claude-3-5-haiku-latest".to_string(),
+                Duration::from_secs(45),
+            )),
+            Arc::new(MockSpecialist::new("custom", "custom", dec!(0.50))),
+        ];
+
+        let provenance = specialist_provenance(&specialists);
+        assert_eq!(provenance.len(), 2);
+        assert_eq!(
+            provenance[0].model.as_deref(),
+            Some("claude-3-5-haiku-latest")
+        );
+        assert_eq!(
+            provenance[0].prompt_version.as_deref(),
+            Some(crate::prompts::TECHNICAL_PROMPT_VERSION)
+        );
+        assert!(provenance[1].model.is_none());
+        assert!(provenance[1].prompt_version.is_none());
+    }
+
+    #[test]
+    fn shared_regime_picks_market_and_symbol_entries() {
+        let data = serde_json::json!({
+            "regime:market": {"risk": "risk_off"},
+            "regime:AAPL": {"trend": "uptrend"},
+            "regime:TSLA": {"trend": "range"},
+        });
+        let regime = shared_regime(&data, "AAPL");
+        assert_eq!(regime["market"]["risk"], "risk_off");
+        assert_eq!(regime["symbol"]["trend"], "uptrend");
+        assert!(shared_regime(&data, "NVDA")["symbol"].is_null());
+    }
 }
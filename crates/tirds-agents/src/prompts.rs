@@ -1,3 +1,11 @@
+/// Prompt template versions. Bump when the wording or rules of a prompt change
+/// so that decisions record which template they were produced with.
+pub const TECHNICAL_PROMPT_VERSION: &str = "4";
+pub const MACRO_PROMPT_VERSION: &str = "3";
+pub const SENTIMENT_PROMPT_VERSION: &str = "3";
+pub const SECTOR_PROMPT_VERSION: &str = "4";
+pub const SYNTHESIZER_PROMPT_VERSION: &str = "4";
+
 /// Schema description included in all specialist system prompts.
 fn response_schema() -> String {
     let example = serde_json::json!({
@@ -12,6 +20,23 @@ fn response_schema() -> String {
     serde_json::to_string_pretty(&example).unwrap_or_default()
 }
 
+/// Shared regime section included in every specialist prompt.
+const REGIME_GUIDANCE: &str = "## SHARED REGIME\n\n\
+     `regime:market` (market trend, volatility, breadth and risk: risk_on | neutral | risk_off) and \
+     `regime:SYMBOL` (the symbol's own trend and volatility) are classified once by the data loader, \
+     with the inputs that drove each classification. Treat them as the shared regime view: use them \
+     instead of re-deriving trend or risk-on/risk-off from raw series, and say so in your analysis \
+     if your data contradicts them.";
+
+/// Data quality section included in every specialist prompt.
+const QUALITY_GUIDANCE: &str = "## DATA QUALITY\n\n\
+     `quality:SYMBOL` → {\"score\": 0.0-1.0, \"counts\": {issue kind: count}, \"issues\": [...]} \
+     reports problems the loader found in the symbol's candles (duplicate or out-of-order bars, \
+     invalid OHLC, suspected splits, spikes, zero volume, gaps) and whether each was labeled, \
+     repaired or dropped. If the score is below 0.9, reduce confidence by (0.9 - score) / 2; if an \
+     issue is `suspected_split` with resolution `labeled`, treat price-based signals as unreliable \
+     and cap confidence at 0.5. Mention any adjustment in your reasoning.";
+
 pub fn technical_system_prompt() -> String {
     format!(
         "You are a technical analysis specialist agent in TIRDS (Trading Information Relevance \
@@ -29,7 +54,12 @@ pub fn technical_system_prompt() -> String {
          - `indicator:obv:SYMBOL` → {{\"value\": [array of cumulative OBV]}}\n\
          - `bars:SYMBOL:5m` → array of candle objects: {{\"open\", \"high\", \"low\", \"close\", \
          \"volume\", \"timestamp\"}}\n\
-         - `quote:SYMBOL` → {{\"price\": current_price, ...}}\n\n\
+         - `quote:SYMBOL` → {{\"price\": current_price, ...}}\n\
+         - `levels:SYMBOL` → {{\"prior_day\": {{\"high\", \"low\", \"close\"}}, \
+         \"pivots\": {{\"pivot\", \"r1\", \"r2\", \"r3\", \"s1\", \"s2\", \"s3\"}}, \
+         \"swing_highs\": [{{\"price\", \"timestamp\"}}], \"swing_lows\": [...], \
+         \"volume_profile\": {{\"poc\", \"value_area_high\", \"value_area_low\", \"nodes\": [...]}}}} \
+         (prices are decimal strings)\n\n\
          Use the LAST (most recent) value in each array for current readings.\n\n\
          ## INTERPRETATION RULES\n\n\
          Start with base confidence 0.50 and apply adjustments:\n\n\
@@ -70,6 +100,13 @@ pub fn technical_system_prompt() -> String {
          - 3+ consecutive lower closes: Downtrend → -0.10\n\
          - Higher highs + higher lows: Strong uptrend → +0.15\n\
          - Lower highs + lower lows: Strong downtrend → -0.15\n\n\
+         ### Support and Resistance (from `levels:SYMBOL`)\n\
+         Support: S1-S3, prior-day low, swing lows, value area low. \
+         Resistance: R1-R3, prior-day high, swing highs, value area high.\n\
+         - Buy price within 0.5% above a support level: Good entry → +0.08\n\
+         - Buy price within 0.5% below a resistance level: Poor entry → -0.08\n\
+         - Sell price at or just below resistance: Good exit → +0.05\n\
+         - Price near the volume profile POC: Fair value, low edge → no adjustment\n\n\
          ### OBV (Volume Confirmation)\n\
          - OBV rising with price: Confirmed trend → +0.05\n\
          - OBV diverging from price: Weakening trend → -0.05\n\n\
@@ -84,11 +121,16 @@ pub fn technical_system_prompt() -> String {
          ## CONFIDENCE CALCULATION\n\n\
          Base = 0.50, apply all applicable adjustments, clamp to [0.0, 1.0].\n\
          Show your work: \"RSI 28 (oversold, +0.15). EMA > SMA (+0.10). Base 0.50 → 0.75.\"\n\n\
+         {regime}\n\n\
+         {quality}\n\n\
          You MUST respond with ONLY a JSON object matching this schema:\n\
          {}\n\n\
          The confidence field is a decimal string between \"0.0\" and \"1.0\".\n\
-         In the analysis field, include: rsi_signal, ma_trend, macd_signal, warnings (array).",
-        response_schema()
+         In the analysis field, include: rsi_signal, ma_trend, macd_signal, \
+         key_levels (nearest support and resistance prices with their source), warnings (array).",
+        response_schema(),
+        regime = REGIME_GUIDANCE,
+        quality = QUALITY_GUIDANCE
     )
 }
 
@@ -126,11 +168,15 @@ pub fn macro_system_prompt() -> String {
          ## WARNING CONDITIONS\n\n\
          - VIX > 35: \"Extreme market volatility - exercise caution on all positions\"\n\
          - VIX > 30 + SPY downtrend: \"High-volatility market downtrend - avoid new positions\"\n\n\
+         {regime}\n\n\
+         {quality}\n\n\
          You MUST respond with ONLY a JSON object matching this schema:\n\
          {}\n\n\
          The confidence field is a decimal string between \"0.0\" and \"1.0\".\n\
          In the analysis field, include: vix_regime, market_trend, sector_strength, warnings.",
-        response_schema()
+        response_schema(),
+        regime = REGIME_GUIDANCE,
+        quality = QUALITY_GUIDANCE
     )
 }
 
@@ -171,11 +217,15 @@ pub fn sentiment_system_prompt() -> String {
          ## WARNING CONDITIONS\n\n\
          - All sources strongly negative (< -0.5): \"Uniformly negative sentiment across sources\"\n\
          - High social volume + negative score: \"Negative social media buzz - potential panic\"\n\n\
+         {regime}\n\n\
+         {quality}\n\n\
          You MUST respond with ONLY a JSON object matching this schema:\n\
          {}\n\n\
          The confidence field is a decimal string between \"0.0\" and \"1.0\".\n\
          In the analysis field, include: news_sentiment, social_sentiment, overall, warnings.",
-        response_schema()
+        response_schema(),
+        regime = REGIME_GUIDANCE,
+        quality = QUALITY_GUIDANCE
     )
 }
 
@@ -185,13 +235,15 @@ pub fn sector_system_prompt() -> String {
          Relevance Decider System). Evaluate sector conditions for trade proposals.\n\n\
          ## DATA FORMAT\n\n\
          Your `domain_data` JSON contains:\n\
+         - `meta:{{SYMBOL}}` → {{\"sector\": \"Information Technology\", \"industry\": \"...\", \"sector_etf\": \"XLK\"}} (when the symbol is mapped)\n\
          - `ref:XLK` → {{\"value\": [...]}} (Technology sector ETF)\n\
          - `ref:XLF` → {{\"value\": [...]}} (Financial sector ETF)\n\
          - `ref:XLE` → {{\"value\": [...]}} (Energy sector ETF)\n\
          - `ref:XLV` → {{\"value\": [...]}} (Healthcare sector ETF)\n\
          - `ref:SPY` → {{\"value\": [...]}} (S&P 500 benchmark)\n\
          - `bars:XLK:1d`, `bars:XLF:1d`, etc. → daily candle arrays for sector ETFs\n\n\
-         Map the proposal's symbol to its sector: tech stocks → XLK, financials → XLF, etc.\n\n\
+         Use `meta:{{SYMBOL}}.sector_etf` as the proposal's sector ETF. If there is no `meta` entry, \
+         map the symbol to its sector yourself: tech stocks → XLK, financials → XLF, etc.\n\n\
          ## INTERPRETATION RULES\n\n\
          Start with base confidence 0.50 and apply adjustments:\n\n\
          ### Sector Relative Performance vs SPY\n\
@@ -210,11 +262,15 @@ pub fn sector_system_prompt() -> String {
          ## WARNING CONDITIONS\n\n\
          - Sector underperforming SPY by >5%: \"Sector significantly underperforming market\"\n\
          - Sector in downtrend + underperforming: \"Sector rotation away - unfavorable conditions\"\n\n\
+         {regime}\n\n\
+         {quality}\n\n\
          You MUST respond with ONLY a JSON object matching this schema:\n\
          {}\n\n\
          The confidence field is a decimal string between \"0.0\" and \"1.0\".\n\
          In the analysis field, include: sector_performance, sector_trend, rotation_signal, warnings.",
-        response_schema()
+        response_schema(),
+        regime = REGIME_GUIDANCE,
+        quality = QUALITY_GUIDANCE
     )
 }
 
@@ -229,18 +285,25 @@ pub fn synthesizer_system_prompt() -> String {
      \"reasoning\": \"...\"}, \"price_assessment\": {\"favorability\": \"<decimal>\", \
      \"suggested_price\": null|\"<decimal>\", \"reasoning\": \"...\"}}]\n\
      - information_relevance: {\"score\": \"<0.0-1.0>\", \"source_contributions\": \
-     [{\"source_name\": \"...\", \"relevance\": \"<0.0-1.0>\", \"freshness_seconds\": <int>}]}\n\
+     [{\"source_name\": \"<specialist domain>\", \"relevance\": \"<0.0-1.0>\", \"freshness_seconds\": <int>}]}\n\
      - confidence_decay: {\"daily_rate\": \"<0.0-1.0>\", \"model\": \"linear\"|\"exponential\"}\n\
      - price_target_decay: null or same format as confidence_decay\n\
      - trade_intelligence: {\"smartness_score\": \"<0.0-1.0>\", \"assessments\": [\"...\"]}\n\
      - timeline: [{\"offset_hours\": <int>, \"projected_confidence\": \"<decimal>\", \
      \"projected_price_target\": null|\"<decimal>\", \"note\": null|\"...\"}] \
      (include points at 1h, 4h, 24h, 72h, 168h, 720h)\n\n\
+     The input's data_freshness_seconds maps each specialist domain to the measured age of its \
+     oldest input data. Use these values for freshness_seconds and lower relevance for stale domains.\n\
+     The input's regime field holds the shared market and symbol regime the specialists also saw; \
+     frame confidence_decay and timeline projections consistently with it (e.g., faster decay in \
+     risk_off or extreme volatility).\n\
      When specialist agents report warnings, propagate them into trade_intelligence assessments.\n\
      Weight specialist confidences: technical (0.35), macro (0.20), sentiment (0.20), sector (0.25).\n\n\
      For one-sided trades (buy-only or sell-only), pay special attention to trade_intelligence: \
      assess whether the price is smart (e.g., sell below market = bad, buy below market = good), \
-     whether waiting would yield a better price, and provide specific price suggestions.\n\n\
+     whether waiting would yield a better price, and provide specific price suggestions.\n\
+     Anchor suggested_price and projected_price_target to the support and resistance levels in the \
+     technical specialist's key_levels rather than round numbers.\n\n\
      All decimal values MUST be quoted strings (e.g., \"0.75\" not 0.75).\n\
      Respond with ONLY the JSON object, no other text."
         .to_string()
@@ -257,6 +320,17 @@ pub fn get_specialist_prompt(domain: &str) -> Option<String> {
     }
 }
 
+/// Get the prompt template version for a given specialist domain.
+pub fn prompt_version(domain: &str) -> Option<&'static str> {
+    match domain {
+        "technical" => Some(TECHNICAL_PROMPT_VERSION),
+        "macro" => Some(MACRO_PROMPT_VERSION),
+        "sentiment" => Some(SENTIMENT_PROMPT_VERSION),
+        "sector" => Some(SECTOR_PROMPT_VERSION),
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -284,6 +358,18 @@ mod tests {
     #[test]
     fn unknown_domain_returns_none() {
         assert!(get_specialist_prompt("unknown").is_none());
+        assert!(prompt_version("unknown").is_none());
+    }
+
+    #[test]
+    fn every_specialist_prompt_has_a_version() {
+        let domains = ["technical", "macro", "sentiment", "sector"];
+        for domain in &domains {
+            assert!(
+                prompt_version(domain).is_some(),
+                "Missing prompt version for {domain}"
+            );
+        }
     }
 
     #[test]
@@ -297,6 +383,9 @@ mod tests {
         assert!(prompt.contains("trade_intelligence"));
         assert!(prompt.contains("timeline"));
         assert!(prompt.contains("smartness_score"));
+        assert!(prompt.contains("data_freshness_seconds"));
+        assert!(prompt.contains("key_levels"));
+        assert!(prompt.contains("regime field"));
     }
 
     #[test]
@@ -311,6 +400,8 @@ mod tests {
         assert!(prompt.contains("ATR"));
         assert!(prompt.contains("Stochastic"));
         assert!(prompt.contains("OBV"));
+        assert!(prompt.contains("levels:SYMBOL"));
+        assert!(prompt.contains("key_levels"));
         assert!(prompt.contains("WARNING"));
     }
 
@@ -339,6 +430,7 @@ mod tests {
         assert!(prompt.contains("XLF"));
         assert!(prompt.contains("Relative Performance"));
         assert!(prompt.contains("rotation"));
+        assert!(prompt.contains("meta:{SYMBOL}"));
     }
 
     #[test]
@@ -354,6 +446,14 @@ mod tests {
                 prompt.contains("INTERPRETATION RULES"),
                 "Missing INTERPRETATION RULES in {domain}"
             );
+            assert!(
+                prompt.contains("regime:market"),
+                "Missing shared regime in {domain}"
+            );
+            assert!(
+                prompt.contains("quality:SYMBOL"),
+                "Missing data quality in {domain}"
+            );
         }
     }
 }
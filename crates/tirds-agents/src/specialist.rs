@@ -12,6 +12,11 @@ pub trait SpecialistAgent: Send + Sync {
     fn name(&self) -> &str;
     fn domain(&self) -> &str;
 
+    /// The model ID backing this agent, if any. Recorded in decision provenance.
+    fn model(&self) -> Option<&str> {
+        None
+    }
+
     async fn evaluate(&self, request: &AgentRequest) -> Result<AgentResponse, AgentError>;
 }
 
@@ -42,6 +47,10 @@ impl SpecialistAgent for ClaudeSpecialist {
         &self.domain
     }
 
+    fn model(&self) -> Option<&str> {
+        Some(&self.cli_config.model)
+    }
+
     async fn evaluate(&self, request: &AgentRequest) -> Result<AgentResponse, AgentError> {
         let system_prompt = get_specialist_prompt(&self.domain).ok_or_else(|| {
             AgentError::Cli(format!("No system prompt for domain: {}", self.domain))
@@ -0,0 +1,140 @@
+use tirds_cache::StaleEntry;
+use tirds_models::config::{StalenessConfig, StalenessPolicy};
+use tirds_models::trade_decision::TradeDecision;
+
+use crate::error::AgentError;
+
+/// Human-readable summary of stale entries, e.g. `quote:AAPL (95s old, max 60s)`.
+pub fn describe_stale(stale: &[StaleEntry]) -> String {
+    stale
+        .iter()
+        .map(|s| {
+            format!(
+                "{} ({}s old, max {}s)",
+                s.key, s.age_seconds, s.max_age_seconds
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Refuse the evaluation up front when the policy is `Fail` and any entry is stale.
+pub fn enforce_staleness(stale: &[StaleEntry], config: &StalenessConfig) -> Result<(), AgentError> {
+    if !stale.is_empty() && config.policy == StalenessPolicy::Fail {
+        return Err(AgentError::StaleData(describe_stale(stale)));
+    }
+    Ok(())
+}
+
+/// Annotate (and, under `Downgrade`, penalize) a decision made on stale data.
+pub fn apply_staleness_policy(
+    decision: &mut TradeDecision,
+    stale: &[StaleEntry],
+    config: &StalenessConfig,
+) {
+    if stale.is_empty() {
+        return;
+    }
+
+    decision
+        .trade_intelligence
+        .assessments
+        .push(format!("Stale data: {}", describe_stale(stale)));
+
+    if config.policy == StalenessPolicy::Downgrade {
+        let factor = config.downgrade_factor;
+        decision.overall_confidence.score *= factor;
+        for leg in &mut decision.leg_assessments {
+            leg.confidence.score *= factor;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orchestrator::build_trade_decision;
+    use rust_decimal_macros::dec;
+    use std::time::Duration;
+    use tirds_models::trade_input::{LegSide, TradeLeg, TradeProposal, INPUT_SCHEMA_VERSION};
+    use uuid::Uuid;
+
+    fn stale_quote() -> Vec<StaleEntry> {
+        vec![StaleEntry {
+            key: "quote:AAPL".to_string(),
+            age_seconds: 95,
+            max_age_seconds: 60,
+        }]
+    }
+
+    fn sample_decision() -> TradeDecision {
+        let proposal = TradeProposal {
+            id: Uuid::new_v4(),
+            schema_version: INPUT_SCHEMA_VERSION,
+            symbol: "AAPL".to_string(),
+            legs: vec![TradeLeg {
+                side: LegSide::Buy,
+                price: Some(dec!(150.00)),
+                quantity: None,
+                time_in_force: None,
+            }],
+            proposed_at: chrono::Utc::now(),
+            context: None,
+        };
+        let synthesized = serde_json::json!({
+            "overall_confidence": {"score": "0.80", "reasoning": "Strong setup"},
+            "leg_assessments": [{
+                "side": "buy",
+                "confidence": {"score": "0.60", "reasoning": "Good entry"},
+                "price_assessment": {"favorability": "0.02", "suggested_price": null, "reasoning": "ok"}
+            }],
+            "information_relevance": {"score": "0.90", "source_contributions": []},
+            "confidence_decay": {"daily_rate": "0.30", "model": "exponential"},
+            "trade_intelligence": {"smartness_score": "0.82", "assessments": []},
+            "timeline": []
+        });
+        build_trade_decision(&proposal, &synthesized, &[], Duration::from_secs(1)).unwrap()
+    }
+
+    #[test]
+    fn describe_lists_key_age_and_limit() {
+        assert_eq!(
+            describe_stale(&stale_quote()),
+            "quote:AAPL (95s old, max 60s)"
+        );
+    }
+
+    #[test]
+    fn fail_policy_refuses_stale_snapshot() {
+        let config = StalenessConfig {
+            policy: StalenessPolicy::Fail,
+            ..StalenessConfig::default()
+        };
+        let err = enforce_staleness(&stale_quote(), &config).unwrap_err();
+        assert!(matches!(err, AgentError::StaleData(_)));
+        assert!(enforce_staleness(&[], &config).is_ok());
+    }
+
+    #[test]
+    fn warn_policy_annotates_without_changing_confidence() {
+        let mut decision = sample_decision();
+        apply_staleness_policy(&mut decision, &stale_quote(), &StalenessConfig::default());
+
+        assert_eq!(decision.overall_confidence.score, dec!(0.80));
+        assert!(decision.trade_intelligence.assessments[0].starts_with("Stale data:"));
+    }
+
+    #[test]
+    fn downgrade_policy_scales_confidence() {
+        let mut decision = sample_decision();
+        let config = StalenessConfig {
+            policy: StalenessPolicy::Downgrade,
+            downgrade_factor: dec!(0.5),
+            ..StalenessConfig::default()
+        };
+        apply_staleness_policy(&mut decision, &stale_quote(), &config);
+
+        assert_eq!(decision.overall_confidence.score, dec!(0.40));
+        assert_eq!(decision.leg_assessments[0].confidence.score, dec!(0.30));
+    }
+}
@@ -415,8 +415,12 @@ fn evaluate_sector(request: &AgentRequest) -> AgentResponse {
     let mut warnings: Vec<String> = Vec::new();
     let mut sources: Vec<String> = Vec::new();
 
-    // Default to XLK (tech) since most test scenarios are tech stocks
-    let sector_etf = "XLK";
+    // Use the mapped sector ETF; default to XLK (tech) since most test scenarios are tech stocks
+    let sector_etf = data
+        .get(format!("meta:{}", request.proposal.symbol))
+        .and_then(|v| v.get("sector_etf"))
+        .and_then(|v| v.as_str())
+        .unwrap_or("XLK");
     let sector_key = format!("ref:{sector_etf}");
     let spy_key = "ref:SPY";
 
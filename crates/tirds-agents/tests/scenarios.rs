@@ -117,7 +117,7 @@ async fn run_scenario(
     proposal: &TradeProposal,
     cache: &Arc<CacheReader>,
 ) -> (Vec<AgentResponse>, Vec<AgentReport>) {
-    let domain_snapshot = cache.build_domain_snapshot(&proposal.symbol).unwrap();
+    let domain_snapshot = cache.build_domain_snapshot(&proposal.symbol).await.unwrap();
 
     let specialists: Vec<Box<dyn SpecialistAgent>> = vec![
         Box::new(ScenarioMockSpecialist::technical()),
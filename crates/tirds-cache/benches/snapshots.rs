@@ -0,0 +1,101 @@
+//! Throughput of concurrent `build_domain_snapshot` calls against a file
+//! database, with one pooled connection versus several.
+//!
+//! Run with:
+//! ```bash
+//! cargo bench -p tirds-cache --bench snapshots
+//! ```
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{Duration as ChronoDuration, Utc};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use tirds_cache::{CacheReader, ReaderPool};
+use tirds_models::cache_schema::CACHE_TABLE_DDL;
+
+const SYMBOLS: &[&str] = &[
+    "AAPL", "MSFT", "NVDA", "TSLA", "AMZN", "META", "GOOG", "SPY",
+];
+const INDICATORS_PER_SYMBOL: usize = 40;
+
+/// Seed a cache file with a few dozen indicator rows per symbol.
+fn seed(path: &str) {
+    let conn = rusqlite::Connection::open(path).unwrap();
+    conn.execute_batch("PRAGMA journal_mode=WAL;").unwrap();
+    conn.execute_batch(CACHE_TABLE_DDL).unwrap();
+    let now = Utc::now();
+    let expires = (now + ChronoDuration::hours(1)).to_rfc3339();
+    let mut insert = conn
+        .prepare(
+            "INSERT INTO cache_entries \
+             (key, category, value_json, source, symbol, created_at, expires_at, updated_at) \
+             VALUES (?1, 'indicator', ?2, 'bench', ?3, ?4, ?5, ?4)",
+        )
+        .unwrap();
+    for symbol in SYMBOLS {
+        for i in 0..INDICATORS_PER_SYMBOL {
+            let value = format!(r#"{{"value": {i}.5, "history": [1.0, 2.0, 3.0, 4.0]}}"#);
+            insert
+                .execute(rusqlite::params![
+                    format!("indicator:bench_{i}:{symbol}"),
+                    value,
+                    symbol,
+                    now.to_rfc3339(),
+                    expires,
+                ])
+                .unwrap();
+        }
+    }
+}
+
+fn concurrent_snapshots(c: &mut Criterion) {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("bench.db");
+    let path = path.to_str().unwrap();
+    seed(path);
+
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(4)
+        .enable_all()
+        .build()
+        .unwrap();
+
+    let mut group = c.benchmark_group("build_domain_snapshot");
+    for connections in [1, 4] {
+        let reader = Arc::new(CacheReader::with_pool(
+            ReaderPool::open(path, connections).unwrap(),
+            1_000,
+            Duration::from_secs(60),
+        ));
+        for concurrent in [1, 4, 16] {
+            group.throughput(Throughput::Elements(concurrent as u64));
+            group.bench_with_input(
+                BenchmarkId::new(format!("{connections}_connections"), concurrent),
+                &concurrent,
+                |b, &concurrent| {
+                    b.iter(|| {
+                        runtime.block_on(async {
+                            let tasks: Vec<_> = (0..concurrent)
+                                .map(|i| {
+                                    let reader = reader.clone();
+                                    let symbol = SYMBOLS[i % SYMBOLS.len()];
+                                    tokio::spawn(async move {
+                                        reader.build_domain_snapshot(symbol).await.unwrap()
+                                    })
+                                })
+                                .collect();
+                            for task in tasks {
+                                task.await.unwrap();
+                            }
+                        })
+                    })
+                },
+            );
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, concurrent_snapshots);
+criterion_main!(benches);
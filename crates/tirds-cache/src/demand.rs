@@ -0,0 +1,183 @@
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Utc};
+use rusqlite::Connection;
+use tirds_models::symbol_request::{SymbolRequest, SymbolRequestStatus};
+
+use crate::error::CacheError;
+use crate::sqlite::{check_schema, has_table};
+
+/// Writer for the `symbol_requests` table.
+///
+/// The evaluator otherwise never writes to the shared cache; this is the one
+/// exception, so it gets its own connection rather than widening `SqliteReader`.
+pub struct SymbolDemand {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl SymbolDemand {
+    /// Open a read-write connection to an existing cache database.
+    ///
+    /// The loader's migrations own the `symbol_requests` table, so this fails
+    /// when the schema is one this build can't read or predates the table.
+    pub fn open(path: &str) -> Result<Self, CacheError> {
+        let conn = Connection::open_with_flags(
+            path,
+            rusqlite::OpenFlags::SQLITE_OPEN_READ_WRITE | rusqlite::OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        )?;
+        conn.busy_timeout(std::time::Duration::from_secs(5))?;
+        check_schema(&conn)?;
+        if !has_table(&conn, "symbol_requests")? {
+            return Err(CacheError::Unavailable(
+                "cache has no symbol_requests table; run the loader to migrate it".to_string(),
+            ));
+        }
+        Ok(Self {
+            conn: Arc::new(Mutex::new(conn)),
+        })
+    }
+
+    /// Open an in-memory database for testing.
+    pub fn open_in_memory() -> Result<Self, CacheError> {
+        let conn = Connection::open_in_memory()?;
+        conn.execute_batch(tirds_models::cache_schema::SYMBOL_REQUESTS_DDL)?;
+        Ok(Self {
+            conn: Arc::new(Mutex::new(conn)),
+        })
+    }
+
+    /// Enqueue a symbol for loading. A request that is already pending is left
+    /// alone; a completed or failed one is reset to pending.
+    pub async fn request(&self, symbol: &str) -> Result<(), CacheError> {
+        let symbol = symbol.to_string();
+        self.run(move |conn| {
+            conn.execute(
+                "INSERT INTO symbol_requests (symbol, status, requested_at) VALUES (?1, ?2, ?3) \
+             ON CONFLICT(symbol) DO UPDATE SET \
+                 status = excluded.status, requested_at = excluded.requested_at, \
+                 completed_at = NULL, error = NULL \
+             WHERE symbol_requests.status != ?2",
+                rusqlite::params![
+                    symbol,
+                    SymbolRequestStatus::Pending.as_str(),
+                    Utc::now().to_rfc3339()
+                ],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// Current state of a symbol's request, if any.
+    pub async fn get(&self, symbol: &str) -> Result<Option<SymbolRequest>, CacheError> {
+        let symbol = symbol.to_string();
+        self.run(move |conn| read_request(conn, &symbol)).await
+    }
+
+    /// Run `f` against the connection on the blocking pool, since a busy
+    /// database can hold the caller for the full busy timeout.
+    async fn run<T, F>(&self, f: F) -> Result<T, CacheError>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Connection) -> Result<T, CacheError> + Send + 'static,
+    {
+        let conn = Arc::clone(&self.conn);
+        tokio::task::spawn_blocking(move || {
+            let conn = conn
+                .lock()
+                .map_err(|e| CacheError::Unavailable(format!("Demand mutex poisoned: {e}")))?;
+            f(&conn)
+        })
+        .await
+        .map_err(|e| CacheError::Unavailable(format!("Demand query failed: {e}")))?
+    }
+}
+
+fn read_request(conn: &Connection, symbol: &str) -> Result<Option<SymbolRequest>, CacheError> {
+    let result = conn.query_row(
+        "SELECT symbol, status, requested_at, completed_at, error \
+         FROM symbol_requests WHERE symbol = ?1",
+        rusqlite::params![symbol],
+        |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, Option<String>>(3)?,
+                row.get::<_, Option<String>>(4)?,
+            ))
+        },
+    );
+
+    let (symbol, status, requested_at, completed_at, error) = match result {
+        Ok(row) => row,
+        Err(rusqlite::Error::QueryReturnedNoRows) => return Ok(None),
+        Err(e) => return Err(CacheError::Sqlite(e)),
+    };
+
+    let status = SymbolRequestStatus::parse(&status)
+        .ok_or_else(|| CacheError::Unavailable(format!("Unknown request status: {status}")))?;
+    Ok(Some(SymbolRequest {
+        symbol,
+        status,
+        requested_at: parse_timestamp(&requested_at)?,
+        completed_at: completed_at.as_deref().map(parse_timestamp).transpose()?,
+        error,
+    }))
+}
+
+fn parse_timestamp(s: &str) -> Result<DateTime<Utc>, CacheError> {
+    DateTime::parse_from_rfc3339(s)
+        .map(|t| t.with_timezone(&Utc))
+        .map_err(|e| CacheError::Unavailable(format!("Invalid timestamp {s}: {e}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn request_creates_pending_row() {
+        let demand = SymbolDemand::open_in_memory().unwrap();
+        assert!(demand.get("NVDA").await.unwrap().is_none());
+
+        demand.request("NVDA").await.unwrap();
+        let req = demand.get("NVDA").await.unwrap().unwrap();
+        assert_eq!(req.status, SymbolRequestStatus::Pending);
+        assert!(req.completed_at.is_none());
+    }
+
+    #[tokio::test]
+    async fn request_resets_completed_row() {
+        let demand = SymbolDemand::open_in_memory().unwrap();
+        demand.request("NVDA").await.unwrap();
+        demand
+            .run(|conn| {
+                conn.execute(
+                    "UPDATE symbol_requests SET status = 'failed', completed_at = ?1, error = 'boom'",
+                    rusqlite::params![Utc::now().to_rfc3339()],
+                )?;
+                Ok(())
+            })
+            .await
+            .unwrap();
+
+        demand.request("NVDA").await.unwrap();
+        let req = demand.get("NVDA").await.unwrap().unwrap();
+        assert_eq!(req.status, SymbolRequestStatus::Pending);
+        assert!(req.error.is_none());
+        assert!(req.completed_at.is_none());
+    }
+
+    #[tokio::test]
+    async fn repeated_pending_request_keeps_original_time() {
+        let demand = SymbolDemand::open_in_memory().unwrap();
+        demand.request("NVDA").await.unwrap();
+        let first = demand.get("NVDA").await.unwrap().unwrap().requested_at;
+        demand.request("NVDA").await.unwrap();
+        assert_eq!(
+            demand.get("NVDA").await.unwrap().unwrap().requested_at,
+            first
+        );
+    }
+}
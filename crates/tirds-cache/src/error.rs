@@ -13,4 +13,17 @@ pub enum CacheError {
 
     #[error("Cache not available: {0}")]
     Unavailable(String),
+
+    #[error("Invalid cache fixture {path}: {reason}")]
+    Fixture { path: String, reason: String },
+
+    #[error(
+        "Cache schema version {version} requires a reader for version {min_reader_version} \
+         or later; this reader supports {supported}"
+    )]
+    UnsupportedSchema {
+        version: u32,
+        min_reader_version: u32,
+        supported: u32,
+    },
 }
@@ -0,0 +1,134 @@
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use tirds_models::cache_schema::CacheRow;
+
+use crate::error::CacheError;
+use crate::in_memory::InMemorySource;
+use crate::source::{CacheSource, SourceMetadata};
+
+/// A [`CacheSource`] loaded from a directory of JSON fixture files.
+///
+/// Every `*.json` file holds one cache row or an array of them, with the
+/// `cache_entries` columns as fields (`symbol` may be omitted). Files are
+/// read in name order and a later row replaces an earlier one with the same
+/// key. Fixtures are fixed snapshots, so `expires_at` is not enforced.
+pub struct FixtureSource {
+    dir: PathBuf,
+    rows: InMemorySource,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum FixtureFile {
+    Many(Vec<CacheRow>),
+    One(CacheRow),
+}
+
+impl FixtureSource {
+    /// Load every fixture file in `dir`.
+    pub fn open(dir: impl AsRef<Path>) -> Result<Self, CacheError> {
+        let dir = dir.as_ref().to_path_buf();
+        let fixture_error = |path: &Path, reason: String| CacheError::Fixture {
+            path: path.display().to_string(),
+            reason,
+        };
+
+        let mut files = std::fs::read_dir(&dir)
+            .map_err(|e| fixture_error(&dir, e.to_string()))?
+            .map(|entry| entry.map(|e| e.path()))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| fixture_error(&dir, e.to_string()))?;
+        files.retain(|path| path.extension().is_some_and(|ext| ext == "json"));
+        files.sort();
+
+        let rows = InMemorySource::new().without_expiry();
+        for path in &files {
+            let text =
+                std::fs::read_to_string(path).map_err(|e| fixture_error(path, e.to_string()))?;
+            match serde_json::from_str(&text).map_err(|e| fixture_error(path, e.to_string()))? {
+                FixtureFile::Many(many) => many.into_iter().for_each(|row| rows.insert(row)),
+                FixtureFile::One(row) => rows.insert(row),
+            }
+        }
+        tracing::debug!(dir = %dir.display(), files = files.len(), rows = rows.len(), "Loaded cache fixtures");
+
+        Ok(Self { dir, rows })
+    }
+}
+
+#[async_trait]
+impl CacheSource for FixtureSource {
+    async fn get(&self, key: &str) -> Result<Option<CacheRow>, CacheError> {
+        self.rows.get(key).await
+    }
+
+    async fn get_by_symbol(&self, symbol: &str) -> Result<Vec<CacheRow>, CacheError> {
+        self.rows.get_by_symbol(symbol).await
+    }
+
+    async fn get_by_prefix(&self, prefix: &str) -> Result<Vec<CacheRow>, CacheError> {
+        self.rows.get_by_prefix(prefix).await
+    }
+
+    async fn metadata(&self) -> Result<SourceMetadata, CacheError> {
+        Ok(self
+            .rows
+            .entry_metadata("fixture", Some(self.dir.display().to_string())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AAPL: &str = r#"[
+        {
+            "key": "quote:AAPL",
+            "category": "market_data",
+            "value_json": "{\"price\": 150.25}",
+            "source": "fixture",
+            "symbol": "AAPL",
+            "created_at": "2024-01-16T14:30:00Z",
+            "expires_at": "2024-01-16T14:31:00Z",
+            "updated_at": "2024-01-16T14:30:00Z"
+        }
+    ]"#;
+
+    const MARKET: &str = r#"{
+        "key": "regime:market",
+        "category": "indicator",
+        "value_json": "{\"risk\": \"risk_on\"}",
+        "source": "fixture",
+        "created_at": "2024-01-16T14:30:00Z",
+        "expires_at": "2024-01-16T14:31:00Z",
+        "updated_at": "2024-01-16T14:30:00Z"
+    }"#;
+
+    #[tokio::test]
+    async fn loads_rows_from_json_files() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("aapl.json"), AAPL).unwrap();
+        std::fs::write(dir.path().join("market.json"), MARKET).unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "not a fixture").unwrap();
+
+        let source = FixtureSource::open(dir.path()).unwrap();
+        let snapshot = source.snapshot("AAPL").await.unwrap();
+        assert_eq!(snapshot.data["quote:AAPL"]["price"], 150.25);
+        assert_eq!(snapshot.data["regime:market"]["risk"], "risk_on");
+
+        let metadata = source.metadata().await.unwrap();
+        assert_eq!(metadata.kind, "fixture");
+        assert_eq!(metadata.entries, Some(2));
+    }
+
+    #[test]
+    fn invalid_fixture_names_the_file() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("broken.json"), "{").unwrap();
+
+        let err = FixtureSource::open(dir.path()).err().unwrap();
+        assert!(matches!(&err, CacheError::Fixture { path, .. } if path.ends_with("broken.json")));
+    }
+}
@@ -0,0 +1,198 @@
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use tirds_models::cache_schema::CacheRow;
+
+use crate::error::CacheError;
+use crate::source::{CacheSource, SourceMetadata};
+
+/// A [`CacheSource`] over rows held in memory, for tests and embedded users.
+///
+/// Rows past their `expires_at` are hidden, as SQLite would, unless built
+/// with [`InMemorySource::without_expiry`] (e.g. for replaying old rows).
+pub struct InMemorySource {
+    rows: RwLock<BTreeMap<String, CacheRow>>,
+    honour_expiry: bool,
+}
+
+impl InMemorySource {
+    pub fn new() -> Self {
+        Self {
+            rows: RwLock::new(BTreeMap::new()),
+            honour_expiry: true,
+        }
+    }
+
+    /// Source holding `rows`; a later row replaces an earlier one with the same key.
+    pub fn from_rows(rows: impl IntoIterator<Item = CacheRow>) -> Self {
+        let source = Self::new();
+        for row in rows {
+            source.insert(row);
+        }
+        source
+    }
+
+    /// Serve rows regardless of their `expires_at`.
+    pub fn without_expiry(mut self) -> Self {
+        self.honour_expiry = false;
+        self
+    }
+
+    /// Insert or replace the row under its key.
+    pub fn insert(&self, row: CacheRow) {
+        self.write().insert(row.key.clone(), row);
+    }
+
+    /// Remove the row under `key`, returning it.
+    pub fn remove(&self, key: &str) -> Option<CacheRow> {
+        self.write().remove(key)
+    }
+
+    /// Number of rows held, expired or not.
+    pub fn len(&self) -> usize {
+        self.read().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, BTreeMap<String, CacheRow>> {
+        self.rows.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn write(&self) -> std::sync::RwLockWriteGuard<'_, BTreeMap<String, CacheRow>> {
+        self.rows.write().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Live rows matching `filter`, in key order.
+    fn select(&self, filter: impl Fn(&CacheRow) -> bool) -> Vec<CacheRow> {
+        let now = Utc::now();
+        self.read()
+            .values()
+            .filter(|row| self.is_live(row, now) && filter(row))
+            .cloned()
+            .collect()
+    }
+
+    /// Whether `row` has not expired; rows with an unparseable `expires_at`
+    /// count as expired.
+    fn is_live(&self, row: &CacheRow, now: DateTime<Utc>) -> bool {
+        !self.honour_expiry
+            || DateTime::parse_from_rfc3339(&row.expires_at)
+                .map(|at| at.with_timezone(&Utc) > now)
+                .unwrap_or(false)
+    }
+
+    pub(crate) fn entry_metadata(
+        &self,
+        kind: &'static str,
+        location: Option<String>,
+    ) -> SourceMetadata {
+        SourceMetadata {
+            kind,
+            location,
+            schema_version: None,
+            entries: Some(self.len()),
+        }
+    }
+}
+
+impl Default for InMemorySource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl CacheSource for InMemorySource {
+    async fn get(&self, key: &str) -> Result<Option<CacheRow>, CacheError> {
+        let now = Utc::now();
+        Ok(self
+            .read()
+            .get(key)
+            .filter(|row| self.is_live(row, now))
+            .cloned())
+    }
+
+    async fn get_by_symbol(&self, symbol: &str) -> Result<Vec<CacheRow>, CacheError> {
+        Ok(self.select(|row| row.symbol.as_deref() == Some(symbol)))
+    }
+
+    async fn get_by_prefix(&self, prefix: &str) -> Result<Vec<CacheRow>, CacheError> {
+        Ok(self.select(|row| row.key.starts_with(prefix)))
+    }
+
+    async fn metadata(&self) -> Result<SourceMetadata, CacheError> {
+        Ok(self.entry_metadata("memory", None))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn make_row(key: &str, symbol: &str, ttl_seconds: i64) -> CacheRow {
+        let now = Utc::now();
+        CacheRow {
+            key: key.to_string(),
+            category: "indicator".to_string(),
+            value_json: r#"{"value": 1.0}"#.to_string(),
+            source: "test".to_string(),
+            symbol: Some(symbol.to_string()),
+            created_at: now.to_rfc3339(),
+            expires_at: (now + Duration::seconds(ttl_seconds)).to_rfc3339(),
+            updated_at: now.to_rfc3339(),
+        }
+    }
+
+    #[tokio::test]
+    async fn filters_by_symbol_prefix_and_expiry() {
+        let source = InMemorySource::from_rows([
+            make_row("indicator:rsi_14:AAPL", "AAPL", 300),
+            make_row("indicator:sma_20:AAPL", "AAPL", -1),
+            make_row("quote:AAPL", "AAPL", 300),
+            make_row("quote:MSFT", "MSFT", 300),
+        ]);
+
+        let keys = |rows: Vec<CacheRow>| rows.into_iter().map(|r| r.key).collect::<Vec<_>>();
+        assert_eq!(
+            keys(source.get_by_symbol("AAPL").await.unwrap()),
+            vec!["indicator:rsi_14:AAPL", "quote:AAPL"]
+        );
+        assert_eq!(
+            keys(source.get_by_prefix("quote:").await.unwrap()),
+            vec!["quote:AAPL", "quote:MSFT"]
+        );
+        assert!(source.get("indicator:sma_20:AAPL").await.unwrap().is_none());
+        assert_eq!(source.metadata().await.unwrap().entries, Some(4));
+    }
+
+    #[tokio::test]
+    async fn without_expiry_serves_expired_rows() {
+        let source =
+            InMemorySource::from_rows([make_row("quote:AAPL", "AAPL", -60)]).without_expiry();
+        assert!(source.get("quote:AAPL").await.unwrap().is_some());
+        assert_eq!(source.snapshot("AAPL").await.unwrap().sources.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn insert_replaces_and_remove_drops() {
+        let source = InMemorySource::new();
+        source.insert(make_row("quote:AAPL", "AAPL", 300));
+        source.insert(CacheRow {
+            value_json: r#"{"value": 2.0}"#.to_string(),
+            ..make_row("quote:AAPL", "AAPL", 300)
+        });
+        assert_eq!(source.len(), 1);
+        assert_eq!(
+            source.get_json("quote:AAPL").await.unwrap().as_deref(),
+            Some(r#"{"value": 2.0}"#)
+        );
+        assert!(source.remove("quote:AAPL").is_some());
+        assert!(source.is_empty());
+    }
+}
@@ -1,8 +1,20 @@
+pub mod demand;
 pub mod error;
+pub mod fixture;
+pub mod in_memory;
 pub mod memory;
+pub mod pool;
 pub mod reader;
+pub mod snapshot;
+pub mod source;
 pub mod sqlite;
 
+pub use demand::SymbolDemand;
 pub use error::CacheError;
+pub use fixture::FixtureSource;
+pub use in_memory::InMemorySource;
+pub use pool::ReaderPool;
 pub use reader::CacheReader;
+pub use snapshot::{DomainSnapshot, StaleEntry};
+pub use source::{CacheSource, SourceMetadata};
 pub use sqlite::SqliteReader;
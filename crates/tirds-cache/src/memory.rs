@@ -1,12 +1,44 @@
 use moka::future::Cache;
-use std::time::Duration;
+use moka::Expiry;
+use std::time::{Duration, Instant};
 
 /// In-memory hot cache backed by moka.
 ///
-/// Provides fast access to recently-read cache entries.
-/// Entries are automatically evicted after TTL.
+/// Provides fast access to recently-read cache entries. Each entry expires
+/// after the cache TTL or its own expiry, whichever comes first.
 pub struct MemoryCache {
-    inner: Cache<String, String>,
+    inner: Cache<String, HotEntry>,
+    ttl: Duration,
+}
+
+#[derive(Clone)]
+struct HotEntry {
+    value: String,
+    ttl: Duration,
+}
+
+/// Expires each entry after its own `ttl`, counted from its last write.
+struct EntryExpiry;
+
+impl Expiry<String, HotEntry> for EntryExpiry {
+    fn expire_after_create(
+        &self,
+        _key: &String,
+        entry: &HotEntry,
+        _at: Instant,
+    ) -> Option<Duration> {
+        Some(entry.ttl)
+    }
+
+    fn expire_after_update(
+        &self,
+        _key: &String,
+        entry: &HotEntry,
+        _at: Instant,
+        _remaining: Option<Duration>,
+    ) -> Option<Duration> {
+        Some(entry.ttl)
+    }
 }
 
 impl MemoryCache {
@@ -14,23 +46,40 @@ impl MemoryCache {
         Self {
             inner: Cache::builder()
                 .max_capacity(max_capacity)
-                .time_to_live(ttl)
+                .expire_after(EntryExpiry)
                 .build(),
+            ttl,
         }
     }
 
     pub async fn get(&self, key: &str) -> Option<String> {
-        self.inner.get(key).await
+        self.inner.get(key).await.map(|entry| entry.value)
     }
 
+    /// Insert an entry that expires after the cache TTL.
     pub async fn insert(&self, key: String, value: String) {
-        self.inner.insert(key, value).await;
+        self.insert_expiring(key, value, self.ttl).await;
+    }
+
+    /// Insert an entry that expires after `expires_in` or the cache TTL,
+    /// whichever is sooner. Nothing is inserted when `expires_in` is zero.
+    pub async fn insert_expiring(&self, key: String, value: String, expires_in: Duration) {
+        let ttl = expires_in.min(self.ttl);
+        if ttl.is_zero() {
+            return;
+        }
+        self.inner.insert(key, HotEntry { value, ttl }).await;
     }
 
     pub async fn invalidate(&self, key: &str) {
         self.inner.invalidate(key).await;
     }
 
+    /// Drop every entry.
+    pub fn invalidate_all(&self) {
+        self.inner.invalidate_all();
+    }
+
     pub fn entry_count(&self) -> u64 {
         self.inner.entry_count()
     }
@@ -66,6 +115,36 @@ mod tests {
         assert_eq!(result, None);
     }
 
+    #[tokio::test]
+    async fn entry_expiry_shorter_than_ttl() {
+        let cache = MemoryCache::new(100, Duration::from_secs(60));
+        cache
+            .insert_expiring(
+                "short".to_string(),
+                "value".to_string(),
+                Duration::from_millis(50),
+            )
+            .await;
+        cache
+            .insert_expiring("gone".to_string(), "value".to_string(), Duration::ZERO)
+            .await;
+        cache.insert("long".to_string(), "value".to_string()).await;
+
+        assert!(cache.get("short").await.is_some());
+        assert!(cache.get("gone").await.is_none());
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(cache.get("short").await.is_none());
+        assert!(cache.get("long").await.is_some());
+    }
+
+    #[tokio::test]
+    async fn invalidate_all() {
+        let cache = MemoryCache::new(100, Duration::from_secs(60));
+        cache.insert("key1".to_string(), "value1".to_string()).await;
+        cache.invalidate_all();
+        assert!(cache.get("key1").await.is_none());
+    }
+
     #[tokio::test]
     async fn ttl_expiration() {
         let cache = MemoryCache::new(100, Duration::from_millis(50));
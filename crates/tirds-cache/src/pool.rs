@@ -0,0 +1,208 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use tokio::sync::Semaphore;
+
+use crate::error::CacheError;
+use crate::sqlite::SqliteReader;
+
+/// A fixed set of read-only SQLite connections shared by concurrent readers.
+///
+/// WAL mode lets every connection read at once; each query runs on the
+/// blocking thread pool so async callers never wait on SQLite directly.
+/// A connection lost to a panicking read is reopened from the database path;
+/// if that fails the pool shrinks, and once empty every read fails.
+pub struct ReaderPool {
+    idle: Mutex<Vec<SqliteReader>>,
+    permits: Semaphore,
+    size: AtomicUsize,
+    /// Database file, for replacing lost connections. None for in-memory readers.
+    path: Option<String>,
+}
+
+impl ReaderPool {
+    /// Open `size` (at least one) read-only connections to the cache database.
+    pub fn open(path: &str, size: usize) -> Result<Self, CacheError> {
+        let readers = (0..size.max(1))
+            .map(|_| SqliteReader::open(path))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self::from_readers(readers))
+    }
+
+    /// Pool already-open readers.
+    pub fn from_readers(readers: Vec<SqliteReader>) -> Self {
+        Self {
+            permits: Semaphore::new(readers.len()),
+            size: AtomicUsize::new(readers.len()),
+            path: readers.first().and_then(|r| r.path()).map(str::to_string),
+            idle: Mutex::new(readers),
+        }
+    }
+
+    /// Connections in the pool.
+    pub fn size(&self) -> usize {
+        self.size.load(Ordering::Relaxed)
+    }
+
+    /// Run `f` with a connection on the blocking thread pool, waiting for a
+    /// free connection first.
+    pub async fn run<T, F>(&self, f: F) -> Result<T, CacheError>
+    where
+        T: Send + 'static,
+        F: FnOnce(&SqliteReader) -> Result<T, CacheError> + Send + 'static,
+    {
+        let permit = self
+            .permits
+            .acquire()
+            .await
+            .map_err(|_| CacheError::Unavailable("reader pool closed".to_string()))?;
+        let reader = self
+            .idle
+            .lock()
+            .map_err(|e| CacheError::Unavailable(format!("reader pool poisoned: {e}")))?
+            .pop()
+            .ok_or_else(|| CacheError::Unavailable("no idle reader".to_string()))?;
+
+        match tokio::task::spawn_blocking(move || {
+            let result = f(&reader);
+            (reader, result)
+        })
+        .await
+        {
+            Ok((reader, result)) => {
+                if let Ok(mut idle) = self.idle.lock() {
+                    idle.push(reader);
+                }
+                drop(permit);
+                result
+            }
+            Err(e) => {
+                // The connection went down with the task.
+                match self.reopen().await {
+                    Ok(reader) => {
+                        if let Ok(mut idle) = self.idle.lock() {
+                            idle.push(reader);
+                        }
+                        drop(permit);
+                    }
+                    Err(reopen) => {
+                        tracing::error!(error = %reopen, "Failed to replace cache reader");
+                        permit.forget();
+                        if self.size.fetch_sub(1, Ordering::Relaxed) == 1 {
+                            self.permits.close();
+                        }
+                    }
+                }
+                Err(CacheError::Unavailable(format!("cache read failed: {e}")))
+            }
+        }
+    }
+
+    /// Open a connection to replace one that was lost.
+    async fn reopen(&self) -> Result<SqliteReader, CacheError> {
+        let Some(path) = self.path.clone() else {
+            return Err(CacheError::Unavailable(
+                "in-memory reader can't be reopened".to_string(),
+            ));
+        };
+        tokio::task::spawn_blocking(move || SqliteReader::open(&path))
+            .await
+            .map_err(|e| CacheError::Unavailable(format!("reopen failed: {e}")))?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn runs_reads_on_pooled_connections() {
+        let pool = ReaderPool::from_readers(vec![SqliteReader::open_in_memory().unwrap()]);
+        let found = pool.run(|r| r.get("quote:AAPL")).await.unwrap();
+        assert!(found.is_none());
+        assert_eq!(pool.size(), 1);
+    }
+
+    #[tokio::test]
+    async fn waits_for_a_free_connection() {
+        let pool = Arc::new(ReaderPool::from_readers(vec![
+            SqliteReader::open_in_memory().unwrap(),
+        ]));
+        let (started, release) = (
+            Arc::new(std::sync::Barrier::new(2)),
+            Arc::new(std::sync::Barrier::new(2)),
+        );
+        let holder = {
+            let (pool, started, release) = (pool.clone(), started.clone(), release.clone());
+            tokio::spawn(async move {
+                pool.run(move |_| {
+                    started.wait();
+                    release.wait();
+                    Ok(())
+                })
+                .await
+            })
+        };
+        tokio::task::spawn_blocking(move || started.wait())
+            .await
+            .unwrap();
+
+        let waiting = tokio::spawn({
+            let pool = pool.clone();
+            async move { pool.run(|r| r.data_version()).await }
+        });
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        assert!(!waiting.is_finished());
+
+        tokio::task::spawn_blocking(move || release.wait())
+            .await
+            .unwrap();
+        holder.await.unwrap().unwrap();
+        assert!(waiting.await.unwrap().is_ok());
+    }
+
+    #[tokio::test]
+    async fn panicking_read_shrinks_the_pool() {
+        let pool = ReaderPool::from_readers(vec![
+            SqliteReader::open_in_memory().unwrap(),
+            SqliteReader::open_in_memory().unwrap(),
+        ]);
+        let result: Result<(), _> = pool.run(|_| panic!("boom")).await;
+        assert!(matches!(result, Err(CacheError::Unavailable(_))));
+        assert_eq!(pool.size(), 1);
+        assert!(pool.run(|r| r.data_version()).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn losing_the_last_connection_fails_reads() {
+        let pool = ReaderPool::from_readers(vec![SqliteReader::open_in_memory().unwrap()]);
+        let _: Result<(), _> = pool.run(|_| panic!("boom")).await;
+        assert_eq!(pool.size(), 0);
+
+        let read = tokio::time::timeout(
+            std::time::Duration::from_secs(5),
+            pool.run(|r| r.data_version()),
+        )
+        .await
+        .expect("read should fail, not wait");
+        assert!(matches!(read, Err(CacheError::Unavailable(_))));
+    }
+
+    #[tokio::test]
+    async fn panicking_read_reopens_file_connection() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cache.db");
+        let path = path.to_str().unwrap();
+        rusqlite::Connection::open(path)
+            .unwrap()
+            .execute_batch(tirds_models::cache_schema::CACHE_TABLE_DDL)
+            .unwrap();
+
+        let pool = ReaderPool::open(path, 1).unwrap();
+        let result: Result<(), _> = pool.run(|_| panic!("boom")).await;
+        assert!(matches!(result, Err(CacheError::Unavailable(_))));
+        assert_eq!(pool.size(), 1);
+        assert!(pool.run(|r| r.get("quote:AAPL")).await.unwrap().is_none());
+    }
+}
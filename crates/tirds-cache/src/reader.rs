@@ -1,117 +1,243 @@
-use std::sync::Mutex;
-use std::time::Duration;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
 
+use chrono::{DateTime, Utc};
 use serde::de::DeserializeOwned;
-use tirds_models::cache_schema::CacheRow;
+use tirds_models::cache_schema::{key_patterns, CacheRow};
+use tirds_models::loader_status::LoaderStatus;
+use tirds_models::regime::MARKET_SCOPE;
 
 use crate::error::CacheError;
 use crate::memory::MemoryCache;
+use crate::pool::ReaderPool;
+use crate::snapshot::DomainSnapshot;
 use crate::sqlite::SqliteReader;
 
 /// Read-through cache: checks moka (hot) → SQLite (shared) → None.
 ///
-/// On SQLite hit, promotes the entry to the moka hot cache for subsequent fast access.
+/// On SQLite hit, promotes the entry to the moka hot cache for subsequent fast access,
+/// until the memory TTL or the row's `expires_at`, whichever is sooner. The hot cache is
+/// dropped when the SQLite file changes (its `data_version`), checked before every read
+/// by default. Callers that accept briefly stale values can throttle the check with
+/// [`CacheReader::with_version_check_interval`]; the hot cache then serves a value SQLite
+/// no longer returns for at most that long.
 /// This is a read-only consumer - the SQLite database is written by external data pipelines.
 ///
-/// SQLite access is synchronized via `Mutex` since `rusqlite::Connection` is not `Sync`.
+/// SQLite reads go through a [`ReaderPool`] and run on the blocking thread pool.
 pub struct CacheReader {
     memory: MemoryCache,
-    sqlite: Mutex<SqliteReader>,
+    pool: ReaderPool,
+    version_check_interval: Duration,
+    created: Instant,
+    /// Milliseconds after `created` when SQLite is next checked for changes.
+    next_version_check: AtomicU64,
+    /// Bumped whenever the hot cache is dropped; rows read before a bump are
+    /// not promoted.
+    generation: AtomicU64,
 }
 
+/// Default for [`CacheReader::with_version_check_interval`]: check on every read, so
+/// the hot cache never serves a value SQLite would reject.
+pub const DEFAULT_VERSION_CHECK_INTERVAL: Duration = Duration::ZERO;
+
 impl CacheReader {
+    /// Reader over a single SQLite connection.
     pub fn new(sqlite: SqliteReader, max_capacity: u64, memory_ttl: Duration) -> Self {
+        Self::with_pool(
+            ReaderPool::from_readers(vec![sqlite]),
+            max_capacity,
+            memory_ttl,
+        )
+    }
+
+    /// Reader over a pool of SQLite connections, for concurrent reads.
+    pub fn with_pool(pool: ReaderPool, max_capacity: u64, memory_ttl: Duration) -> Self {
         Self {
             memory: MemoryCache::new(max_capacity, memory_ttl),
-            sqlite: Mutex::new(sqlite),
+            pool,
+            version_check_interval: DEFAULT_VERSION_CHECK_INTERVAL,
+            created: Instant::now(),
+            next_version_check: AtomicU64::new(0),
+            generation: AtomicU64::new(0),
         }
     }
 
+    /// Check SQLite for changes at most this often before serving from the
+    /// hot cache. Zero checks on every read.
+    pub fn with_version_check_interval(mut self, interval: Duration) -> Self {
+        self.version_check_interval = interval;
+        self
+    }
+
     /// Get a typed value by cache key.
     /// Checks moka first, then SQLite. Promotes SQLite hits to moka.
     pub async fn get<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>, CacheError> {
-        // 1. Check moka hot cache
-        if let Some(json) = self.memory.get(key).await {
-            return Ok(Some(serde_json::from_str(&json)?));
-        }
-
-        // 2. Check SQLite (TTL filtering happens in the query)
-        let row = {
-            let sqlite = self
-                .sqlite
-                .lock()
-                .map_err(|e| CacheError::Unavailable(format!("SQLite mutex poisoned: {e}")))?;
-            sqlite.get(key)?
-        };
-
-        if let Some(row) = row {
-            // Promote to moka
-            self.memory
-                .insert(key.to_string(), row.value_json.clone())
-                .await;
-            return Ok(Some(serde_json::from_str(&row.value_json)?));
+        match self.get_json(key).await? {
+            Some(json) => Ok(Some(serde_json::from_str(&json)?)),
+            None => Ok(None),
         }
-
-        Ok(None)
     }
 
     /// Get the raw JSON string for a cache key.
     pub async fn get_json(&self, key: &str) -> Result<Option<String>, CacheError> {
+        // 1. Check moka hot cache, unless SQLite changed since it was filled
+        self.drop_hot_cache_if_changed().await?;
         if let Some(json) = self.memory.get(key).await {
             return Ok(Some(json));
         }
 
-        let row = {
-            let sqlite = self
-                .sqlite
-                .lock()
-                .map_err(|e| CacheError::Unavailable(format!("SQLite mutex poisoned: {e}")))?;
-            sqlite.get(key)?
-        };
+        // 2. Check SQLite (TTL filtering happens in the query)
+        let generation = self.generation.load(Ordering::SeqCst);
+        let owned = key.to_string();
+        let row = self.pool.run(move |sqlite| sqlite.get(&owned)).await?;
+
+        match row {
+            Some(row) => {
+                self.promote(&row, generation).await;
+                Ok(Some(row.value_json))
+            }
+            None => Ok(None),
+        }
+    }
 
-        if let Some(row) = row {
-            self.memory
-                .insert(key.to_string(), row.value_json.clone())
-                .await;
-            return Ok(Some(row.value_json));
+    /// Drop the hot cache if SQLite changed, checking at most once per
+    /// version check interval across all callers.
+    async fn drop_hot_cache_if_changed(&self) -> Result<(), CacheError> {
+        let now = self.created.elapsed().as_millis() as u64;
+        let due = self.next_version_check.load(Ordering::Relaxed);
+        let next = now + self.version_check_interval.as_millis() as u64;
+        if now < due
+            || self
+                .next_version_check
+                .compare_exchange(due, next, Ordering::Relaxed, Ordering::Relaxed)
+                .is_err()
+        {
+            return Ok(());
+        }
+        if self.pool.run(|sqlite| sqlite.data_changed()).await? {
+            self.generation.fetch_add(1, Ordering::SeqCst);
+            self.memory.invalidate_all();
         }
+        Ok(())
+    }
 
-        Ok(None)
+    /// Promote a row read at `generation` to moka until the row itself
+    /// expires, unless the hot cache was dropped since.
+    async fn promote(&self, row: &CacheRow, generation: u64) {
+        if self.generation.load(Ordering::SeqCst) != generation {
+            return;
+        }
+        let expires_in = DateTime::parse_from_rfc3339(&row.expires_at)
+            .ok()
+            .and_then(|at| (at.with_timezone(&Utc) - Utc::now()).to_std().ok())
+            .unwrap_or_default();
+        self.memory
+            .insert_expiring(row.key.clone(), row.value_json.clone(), expires_in)
+            .await;
+        // Dropped while inserting: the insert may have landed after the drop
+        if self.generation.load(Ordering::SeqCst) != generation {
+            self.memory.invalidate(&row.key).await;
+        }
+    }
+
+    /// Get the raw CacheRow for a key from SQLite, bypassing the hot cache.
+    pub async fn get_row(&self, key: &str) -> Result<Option<CacheRow>, CacheError> {
+        let key = key.to_string();
+        self.pool.run(move |sqlite| sqlite.get(&key)).await
     }
 
     /// Get all cache entries for a symbol as raw CacheRows.
-    pub fn get_by_symbol(&self, symbol: &str) -> Result<Vec<CacheRow>, CacheError> {
-        let sqlite = self
-            .sqlite
-            .lock()
-            .map_err(|e| CacheError::Unavailable(format!("SQLite mutex poisoned: {e}")))?;
-        sqlite.get_by_symbol(symbol)
+    pub async fn get_by_symbol(&self, symbol: &str) -> Result<Vec<CacheRow>, CacheError> {
+        let symbol = symbol.to_string();
+        self.pool
+            .run(move |sqlite| sqlite.get_by_symbol(&symbol))
+            .await
     }
 
     /// Get all cache entries matching a key prefix as raw CacheRows.
-    pub fn get_by_prefix(&self, prefix: &str) -> Result<Vec<CacheRow>, CacheError> {
-        let sqlite = self
-            .sqlite
-            .lock()
-            .map_err(|e| CacheError::Unavailable(format!("SQLite mutex poisoned: {e}")))?;
-        sqlite.get_by_prefix(prefix)
+    pub async fn get_by_prefix(&self, prefix: &str) -> Result<Vec<CacheRow>, CacheError> {
+        let prefix = prefix.to_string();
+        self.pool
+            .run(move |sqlite| sqlite.get_by_prefix(&prefix))
+            .await
     }
 
     /// Build a domain data snapshot for a symbol.
     /// Collects all cache entries for the symbol and merges them into a single JSON object.
-    pub fn build_domain_snapshot(&self, symbol: &str) -> Result<serde_json::Value, CacheError> {
-        let sqlite = self
-            .sqlite
-            .lock()
-            .map_err(|e| CacheError::Unavailable(format!("SQLite mutex poisoned: {e}")))?;
-        let rows = sqlite.get_by_symbol(symbol)?;
-        let mut map = serde_json::Map::new();
-        for row in rows {
-            if let Ok(value) = serde_json::from_str::<serde_json::Value>(&row.value_json) {
-                map.insert(row.key, value);
-            }
-        }
-        Ok(serde_json::Value::Object(map))
+    pub async fn build_domain_snapshot(
+        &self,
+        symbol: &str,
+    ) -> Result<serde_json::Value, CacheError> {
+        Ok(self.build_snapshot(symbol).await?.data)
+    }
+
+    /// Build a domain snapshot for a symbol along with the rows it was built
+    /// from and a content fingerprint. Market-wide entries (`regime:market`)
+    /// are included in every symbol's snapshot.
+    pub async fn build_snapshot(&self, symbol: &str) -> Result<DomainSnapshot, CacheError> {
+        let symbol = symbol.to_string();
+        self.pool
+            .run(move |sqlite| {
+                let mut rows = sqlite.get_by_symbol(&symbol)?;
+                if let Some(market) = sqlite.get(&key_patterns::regime(MARKET_SCOPE))? {
+                    rows.push(market);
+                }
+                Ok(DomainSnapshot::from_rows(rows))
+            })
+            .await
+    }
+
+    /// Get the value a key had at `at` from the cache history, bypassing the
+    /// hot cache.
+    pub async fn get_as_of(
+        &self,
+        key: &str,
+        at: DateTime<Utc>,
+    ) -> Result<Option<CacheRow>, CacheError> {
+        let key = key.to_string();
+        self.pool
+            .run(move |sqlite| sqlite.get_as_of(&key, at))
+            .await
+    }
+
+    /// Build a domain data snapshot for a symbol as the cache held it at `at`.
+    pub async fn build_domain_snapshot_as_of(
+        &self,
+        symbol: &str,
+        at: DateTime<Utc>,
+    ) -> Result<serde_json::Value, CacheError> {
+        Ok(self.build_snapshot_as_of(symbol, at).await?.data)
+    }
+
+    /// [`CacheReader::build_snapshot`] from the cache history at `at`, for
+    /// replay and backtesting.
+    pub async fn build_snapshot_as_of(
+        &self,
+        symbol: &str,
+        at: DateTime<Utc>,
+    ) -> Result<DomainSnapshot, CacheError> {
+        let symbol = symbol.to_string();
+        self.pool
+            .run(move |sqlite| {
+                let mut rows = sqlite.get_by_symbol_as_of(&symbol, at)?;
+                if let Some(market) = sqlite.get_as_of(&key_patterns::regime(MARKET_SCOPE), at)? {
+                    rows.push(market);
+                }
+                Ok(DomainSnapshot::from_rows(rows))
+            })
+            .await
+    }
+
+    /// Read the loader's heartbeat record, bypassing the hot cache.
+    pub async fn loader_status(&self) -> Result<Option<LoaderStatus>, CacheError> {
+        self.pool.run(|sqlite| sqlite.loader_status()).await
+    }
+
+    /// Database path and schema version, for [`crate::CacheSource::metadata`].
+    pub(crate) async fn database_info(&self) -> Result<(Option<String>, u32), CacheError> {
+        self.pool
+            .run(|sqlite| Ok((sqlite.path().map(str::to_string), sqlite.schema_version())))
+            .await
     }
 
     /// Get the number of entries in the hot moka cache.
@@ -123,7 +249,7 @@ impl CacheReader {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use chrono::{Duration as ChronoDuration, Utc};
+    use chrono::Duration as ChronoDuration;
 
     fn make_row(key: &str, symbol: &str, value_json: &str, ttl_seconds: i64) -> CacheRow {
         let now = Utc::now();
@@ -205,19 +331,110 @@ mod tests {
         assert!(result.is_none());
     }
 
-    #[test]
-    fn get_by_symbol() {
+    #[tokio::test]
+    async fn hot_entry_expires_with_row() {
+        let sqlite = SqliteReader::open_in_memory().unwrap();
+        let now = Utc::now();
+        sqlite
+            .insert(&CacheRow {
+                expires_at: (now + ChronoDuration::milliseconds(200)).to_rfc3339(),
+                ..make_row("quote:AAPL", "AAPL", r#"{"price": 150.25}"#, 0)
+            })
+            .unwrap();
+        let reader = CacheReader::new(sqlite, 100, Duration::from_secs(60));
+
+        assert!(reader.get_json("quote:AAPL").await.unwrap().is_some());
+        assert!(reader.memory.get("quote:AAPL").await.is_some());
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        assert!(reader.memory.get("quote:AAPL").await.is_none());
+        assert!(reader.get_json("quote:AAPL").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn hot_cache_dropped_when_database_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cache.db");
+        let path = path.to_str().unwrap();
+        let writer = rusqlite::Connection::open(path).unwrap();
+        writer
+            .execute_batch(tirds_models::cache_schema::CACHE_TABLE_DDL)
+            .unwrap();
+        let write = |value: &str| {
+            let row = make_row("quote:AAPL", "AAPL", value, 300);
+            writer
+                .execute(
+                    "INSERT OR REPLACE INTO cache_entries \
+                     (key, category, value_json, source, symbol, created_at, expires_at, updated_at) \
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                    rusqlite::params![
+                        row.key,
+                        row.category,
+                        row.value_json,
+                        row.source,
+                        row.symbol,
+                        row.created_at,
+                        row.expires_at,
+                        row.updated_at,
+                    ],
+                )
+                .unwrap();
+        };
+        write(r#"{"price": 1}"#);
+        let reader = CacheReader::new(
+            SqliteReader::open(path).unwrap(),
+            100,
+            Duration::from_secs(60),
+        )
+        .with_version_check_interval(Duration::from_millis(200));
+        // The default checks on every read
+        let unthrottled = CacheReader::new(
+            SqliteReader::open(path).unwrap(),
+            100,
+            Duration::from_secs(60),
+        );
+
+        let first: serde_json::Value = reader.get("quote:AAPL").await.unwrap().unwrap();
+        assert_eq!(first["price"], 1);
+        let first: serde_json::Value = unthrottled.get("quote:AAPL").await.unwrap().unwrap();
+        assert_eq!(first["price"], 1);
+        write(r#"{"price": 2}"#);
+        let fresh: serde_json::Value = unthrottled.get("quote:AAPL").await.unwrap().unwrap();
+        assert_eq!(fresh["price"], 2);
+        // Not checked again until the interval has passed
+        let cached: serde_json::Value = reader.get("quote:AAPL").await.unwrap().unwrap();
+        assert_eq!(cached["price"], 1);
+        tokio::time::sleep(Duration::from_millis(250)).await;
+        let second: serde_json::Value = reader.get("quote:AAPL").await.unwrap().unwrap();
+        assert_eq!(second["price"], 2);
+    }
+
+    #[tokio::test]
+    async fn rows_read_before_a_drop_are_not_promoted() {
         let reader = setup_reader();
+        let row = reader.get_row("quote:AAPL").await.unwrap().unwrap();
+        let generation = reader.generation.load(Ordering::SeqCst);
+
+        reader.generation.fetch_add(1, Ordering::SeqCst);
+        reader.promote(&row, generation).await;
+        assert!(reader.memory.get("quote:AAPL").await.is_none());
+
+        reader.promote(&row, generation + 1).await;
+        assert!(reader.memory.get("quote:AAPL").await.is_some());
+    }
 
-        let rows = reader.get_by_symbol("AAPL").unwrap();
+    #[tokio::test]
+    async fn get_by_symbol() {
+        let reader = setup_reader();
+
+        let rows = reader.get_by_symbol("AAPL").await.unwrap();
         assert_eq!(rows.len(), 3); // rsi, sma, quote
     }
 
-    #[test]
-    fn build_domain_snapshot() {
+    #[tokio::test]
+    async fn build_domain_snapshot() {
         let reader = setup_reader();
 
-        let snapshot = reader.build_domain_snapshot("AAPL").unwrap();
+        let snapshot = reader.build_domain_snapshot("AAPL").await.unwrap();
         assert!(snapshot.is_object());
         let obj = snapshot.as_object().unwrap();
         assert_eq!(obj.len(), 3);
@@ -225,4 +442,45 @@ mod tests {
         assert!(obj.contains_key("indicator:sma_20:AAPL"));
         assert!(obj.contains_key("quote:AAPL"));
     }
+
+    #[tokio::test]
+    async fn build_snapshot_includes_market_regime() {
+        let sqlite = SqliteReader::open_in_memory().unwrap();
+        sqlite
+            .insert(&make_row("quote:AAPL", "AAPL", r#"{"price": 150.25}"#, 300))
+            .unwrap();
+        sqlite
+            .insert(&CacheRow {
+                symbol: None,
+                ..make_row("regime:market", "", r#"{"risk": "risk_on"}"#, 300)
+            })
+            .unwrap();
+        sqlite
+            .insert(&make_row(
+                "regime:TSLA",
+                "TSLA",
+                r#"{"trend": "range"}"#,
+                300,
+            ))
+            .unwrap();
+        let reader = CacheReader::new(sqlite, 100, Duration::from_secs(60));
+
+        let snapshot = reader.build_snapshot("AAPL").await.unwrap();
+        let keys: Vec<&str> = snapshot.sources.iter().map(|s| s.key.as_str()).collect();
+        assert_eq!(keys, vec!["quote:AAPL", "regime:market"]);
+        assert_eq!(snapshot.data["regime:market"]["risk"], "risk_on");
+    }
+
+    #[tokio::test]
+    async fn build_snapshot_records_sources() {
+        let reader = setup_reader();
+
+        let snapshot = reader.build_snapshot("AAPL").await.unwrap();
+        assert_eq!(snapshot.sources.len(), 3);
+        assert!(snapshot.sources.iter().all(|s| s.source == "test"));
+        assert_eq!(
+            snapshot.fingerprint,
+            reader.build_snapshot("AAPL").await.unwrap().fingerprint
+        );
+    }
 }
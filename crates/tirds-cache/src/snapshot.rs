@@ -0,0 +1,204 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+use tirds_models::cache_schema::CacheRow;
+use tirds_models::trade_decision::DataSourceRef;
+
+/// A point-in-time view of all cache entries for a symbol.
+///
+/// `data` is what specialists receive as `domain_data`; `sources` and
+/// `fingerprint` record exactly which rows went into it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DomainSnapshot {
+    /// Cache key → parsed value JSON.
+    pub data: serde_json::Value,
+    /// One entry per row included in `data`, sorted by key.
+    pub sources: Vec<DataSourceRef>,
+    /// Hex-encoded SHA-256 of `data` in canonical (key-sorted) JSON form.
+    pub fingerprint: String,
+}
+
+impl DomainSnapshot {
+    /// Build a snapshot from raw cache rows. Rows whose `value_json` does not parse are skipped.
+    pub fn from_rows(rows: Vec<CacheRow>) -> Self {
+        let mut map = serde_json::Map::new();
+        let mut sources = Vec::new();
+        for row in rows {
+            if let Ok(value) = serde_json::from_str::<serde_json::Value>(&row.value_json) {
+                sources.push(DataSourceRef {
+                    key: row.key.clone(),
+                    category: row.category,
+                    source: row.source,
+                    updated_at: row.updated_at,
+                });
+                map.insert(row.key, value);
+            }
+        }
+        sources.sort_by(|a, b| a.key.cmp(&b.key));
+
+        let data = serde_json::Value::Object(map);
+        let fingerprint = fingerprint(&data);
+        Self {
+            data,
+            sources,
+            fingerprint,
+        }
+    }
+}
+
+/// A snapshot entry older than its configured maximum age.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StaleEntry {
+    pub key: String,
+    pub age_seconds: i64,
+    pub max_age_seconds: u64,
+}
+
+impl DomainSnapshot {
+    /// Find entries whose `updated_at` is older than the limit for their key family.
+    ///
+    /// `max_age_seconds` maps a key family (e.g., `quote`, `sentiment:news`) to a limit;
+    /// a family matches keys starting with `{family}:` and the longest match wins.
+    /// Entries with no matching family or an unparseable timestamp are not checked.
+    pub fn stale_entries(
+        &self,
+        max_age_seconds: &BTreeMap<String, u64>,
+        now: DateTime<Utc>,
+    ) -> Vec<StaleEntry> {
+        if max_age_seconds.is_empty() {
+            return Vec::new();
+        }
+
+        self.sources
+            .iter()
+            .filter_map(|source| {
+                let max_age = max_age_for_key(&source.key, max_age_seconds)?;
+                let updated = DateTime::parse_from_rfc3339(&source.updated_at).ok()?;
+                let age = (now - updated.with_timezone(&Utc)).num_seconds();
+                (age > max_age as i64).then(|| StaleEntry {
+                    key: source.key.clone(),
+                    age_seconds: age,
+                    max_age_seconds: max_age,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Longest-prefix lookup of a key's family limit.
+fn max_age_for_key(key: &str, max_age_seconds: &BTreeMap<String, u64>) -> Option<u64> {
+    max_age_seconds
+        .iter()
+        .filter(|(family, _)| {
+            key.strip_prefix(family.as_str())
+                .is_some_and(|rest| rest.starts_with(':'))
+        })
+        .max_by_key(|(family, _)| family.len())
+        .map(|(_, &max_age)| max_age)
+}
+
+/// Hex-encoded SHA-256 of a JSON value.
+///
+/// `serde_json::Map` keeps keys sorted, so equal values always hash the same
+/// regardless of the order rows were read in.
+pub fn fingerprint(value: &serde_json::Value) -> String {
+    let canonical = serde_json::to_string(value).unwrap_or_default();
+    Sha256::digest(canonical.as_bytes())
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_row(key: &str, value_json: &str, updated_at: &str) -> CacheRow {
+        CacheRow {
+            key: key.to_string(),
+            category: "indicator".to_string(),
+            value_json: value_json.to_string(),
+            source: "test".to_string(),
+            symbol: Some("AAPL".to_string()),
+            created_at: updated_at.to_string(),
+            expires_at: "2099-01-01T00:00:00+00:00".to_string(),
+            updated_at: updated_at.to_string(),
+        }
+    }
+
+    #[test]
+    fn fingerprint_is_order_independent() {
+        let a = make_row(
+            "indicator:rsi_14:AAPL",
+            r#"{"value": 35.5}"#,
+            "2024-01-01T00:00:00Z",
+        );
+        let b = make_row("quote:AAPL", r#"{"price": 150.25}"#, "2024-01-01T00:00:00Z");
+
+        let first = DomainSnapshot::from_rows(vec![a.clone(), b.clone()]);
+        let second = DomainSnapshot::from_rows(vec![b, a]);
+
+        assert_eq!(first.fingerprint, second.fingerprint);
+        assert_eq!(first.fingerprint.len(), 64);
+        assert_eq!(first.sources, second.sources);
+        assert_eq!(first.sources[0].key, "indicator:rsi_14:AAPL");
+    }
+
+    #[test]
+    fn fingerprint_changes_with_data() {
+        let before = DomainSnapshot::from_rows(vec![make_row(
+            "quote:AAPL",
+            r#"{"price": 150.25}"#,
+            "2024-01-01T00:00:00Z",
+        )]);
+        let after = DomainSnapshot::from_rows(vec![make_row(
+            "quote:AAPL",
+            r#"{"price": 150.30}"#,
+            "2024-01-01T00:00:00Z",
+        )]);
+        assert_ne!(before.fingerprint, after.fingerprint);
+    }
+
+    #[test]
+    fn stale_entries_use_longest_family_match() {
+        let now = Utc::now();
+        let age = |secs: i64| (now - chrono::Duration::seconds(secs)).to_rfc3339();
+        let snapshot = DomainSnapshot::from_rows(vec![
+            make_row("quote:AAPL", r#"{"price": 150.25}"#, &age(90)),
+            make_row("indicator:rsi_14:AAPL", r#"{"value": 35.5}"#, &age(90)),
+            make_row("sentiment:news:AAPL", r#"{"score": 0.4}"#, &age(900)),
+            make_row("sentiment:social:AAPL", r#"{"score": 0.4}"#, &age(900)),
+            make_row("ref:VIX", r#"{"value": 14.0}"#, &age(99_999)),
+        ]);
+        let limits = BTreeMap::from([
+            ("quote".to_string(), 60),
+            ("indicator".to_string(), 600),
+            ("sentiment".to_string(), 300),
+            ("sentiment:news".to_string(), 1800),
+        ]);
+
+        let stale = snapshot.stale_entries(&limits, now);
+        let keys: Vec<&str> = stale.iter().map(|s| s.key.as_str()).collect();
+        assert_eq!(keys, vec!["quote:AAPL", "sentiment:social:AAPL"]);
+        assert_eq!(stale[0].max_age_seconds, 60);
+        assert!(stale[0].age_seconds >= 90);
+    }
+
+    #[test]
+    fn family_must_match_whole_segment() {
+        let limits = BTreeMap::from([("ref".to_string(), 60)]);
+        assert_eq!(max_age_for_key("ref:SPY", &limits), Some(60));
+        assert_eq!(max_age_for_key("refresh:SPY", &limits), None);
+    }
+
+    #[test]
+    fn unparseable_rows_are_excluded_from_sources() {
+        let snapshot = DomainSnapshot::from_rows(vec![
+            make_row("quote:AAPL", r#"{"price": 150.25}"#, "2024-01-01T00:00:00Z"),
+            make_row("bars:AAPL:5m", "not json", "2024-01-01T00:00:00Z"),
+        ]);
+        assert_eq!(snapshot.sources.len(), 1);
+        assert_eq!(snapshot.data.as_object().unwrap().len(), 1);
+    }
+}
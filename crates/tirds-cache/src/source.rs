@@ -0,0 +1,150 @@
+use async_trait::async_trait;
+use tirds_models::cache_schema::{key_patterns, CacheRow};
+use tirds_models::regime::MARKET_SCOPE;
+
+use crate::error::CacheError;
+use crate::reader::CacheReader;
+use crate::snapshot::DomainSnapshot;
+
+/// Where the evaluator reads cached market data from.
+///
+/// [`CacheReader`] serves the loader's SQLite cache; [`crate::InMemorySource`]
+/// and [`crate::FixtureSource`] supply rows directly, for tests, replays and
+/// embedded users without a SQLite file. Implementations return only live
+/// (unexpired) rows unless documented otherwise.
+#[async_trait]
+pub trait CacheSource: Send + Sync {
+    /// Get the row stored under `key`.
+    async fn get(&self, key: &str) -> Result<Option<CacheRow>, CacheError>;
+
+    /// Get the raw JSON value stored under `key`.
+    async fn get_json(&self, key: &str) -> Result<Option<String>, CacheError> {
+        Ok(self.get(key).await?.map(|row| row.value_json))
+    }
+
+    /// Get all rows for a symbol.
+    async fn get_by_symbol(&self, symbol: &str) -> Result<Vec<CacheRow>, CacheError>;
+
+    /// Get all rows whose key starts with `prefix`.
+    async fn get_by_prefix(&self, prefix: &str) -> Result<Vec<CacheRow>, CacheError>;
+
+    /// Build the symbol's domain snapshot: its rows plus the market-wide
+    /// regime (`regime:market`).
+    async fn snapshot(&self, symbol: &str) -> Result<DomainSnapshot, CacheError> {
+        let mut rows = self.get_by_symbol(symbol).await?;
+        if let Some(market) = self.get(&key_patterns::regime(MARKET_SCOPE)).await? {
+            rows.push(market);
+        }
+        Ok(DomainSnapshot::from_rows(rows))
+    }
+
+    /// Describe the source, for logs and health output.
+    async fn metadata(&self) -> Result<SourceMetadata, CacheError>;
+}
+
+/// What a [`CacheSource`] reads from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceMetadata {
+    /// Kind of source: `sqlite`, `memory` or `fixture`.
+    pub kind: &'static str,
+    /// File or directory the data comes from, if any.
+    pub location: Option<String>,
+    /// Cache schema version, for SQLite sources.
+    pub schema_version: Option<u32>,
+    /// Number of rows held, when cheap to know.
+    pub entries: Option<usize>,
+}
+
+#[async_trait]
+impl CacheSource for CacheReader {
+    async fn get(&self, key: &str) -> Result<Option<CacheRow>, CacheError> {
+        self.get_row(key).await
+    }
+
+    /// Served from the moka hot cache when possible.
+    async fn get_json(&self, key: &str) -> Result<Option<String>, CacheError> {
+        CacheReader::get_json(self, key).await
+    }
+
+    async fn get_by_symbol(&self, symbol: &str) -> Result<Vec<CacheRow>, CacheError> {
+        CacheReader::get_by_symbol(self, symbol).await
+    }
+
+    async fn get_by_prefix(&self, prefix: &str) -> Result<Vec<CacheRow>, CacheError> {
+        CacheReader::get_by_prefix(self, prefix).await
+    }
+
+    async fn snapshot(&self, symbol: &str) -> Result<DomainSnapshot, CacheError> {
+        self.build_snapshot(symbol).await
+    }
+
+    async fn metadata(&self) -> Result<SourceMetadata, CacheError> {
+        let (location, schema_version) = self.database_info().await?;
+        Ok(SourceMetadata {
+            kind: "sqlite",
+            location,
+            schema_version: Some(schema_version),
+            entries: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sqlite::SqliteReader;
+    use chrono::{Duration, Utc};
+    use std::sync::Arc;
+
+    fn make_row(key: &str, symbol: Option<&str>) -> CacheRow {
+        let now = Utc::now();
+        CacheRow {
+            key: key.to_string(),
+            category: "indicator".to_string(),
+            value_json: r#"{"value": 1.0}"#.to_string(),
+            source: "test".to_string(),
+            symbol: symbol.map(str::to_string),
+            created_at: now.to_rfc3339(),
+            expires_at: (now + Duration::minutes(5)).to_rfc3339(),
+            updated_at: now.to_rfc3339(),
+        }
+    }
+
+    #[tokio::test]
+    async fn sqlite_and_memory_sources_build_the_same_snapshot() {
+        let rows = vec![
+            make_row("quote:AAPL", Some("AAPL")),
+            make_row("indicator:rsi_14:AAPL", Some("AAPL")),
+            make_row("regime:market", None),
+        ];
+        let sqlite = SqliteReader::open_in_memory().unwrap();
+        for row in &rows {
+            sqlite.insert(row).unwrap();
+        }
+        let sources: Vec<Arc<dyn CacheSource>> = vec![
+            Arc::new(CacheReader::new(
+                sqlite,
+                100,
+                std::time::Duration::from_secs(60),
+            )),
+            Arc::new(crate::InMemorySource::from_rows(rows)),
+        ];
+
+        let snapshots = snapshots_of(&sources, "AAPL").await;
+        assert_eq!(snapshots[0].fingerprint, snapshots[1].fingerprint);
+        assert_eq!(snapshots[0].sources.len(), 3);
+        assert_eq!(sources[0].metadata().await.unwrap().kind, "sqlite");
+        assert_eq!(
+            sources[1].get_json("quote:AAPL").await.unwrap().as_deref(),
+            Some(r#"{"value": 1.0}"#)
+        );
+    }
+
+    async fn snapshots_of(sources: &[Arc<dyn CacheSource>], symbol: &str) -> Vec<DomainSnapshot> {
+        let mut snapshots = Vec::new();
+        for source in sources {
+            snapshots.push(source.snapshot(symbol).await.unwrap());
+        }
+        snapshots
+    }
+}
@@ -1,6 +1,10 @@
-use chrono::Utc;
-use rusqlite::Connection;
-use tirds_models::cache_schema::CacheRow;
+use std::cell::Cell;
+
+use chrono::{DateTime, Utc};
+use rusqlite::{Connection, OptionalExtension};
+use tirds_models::cache_schema::{history_timestamp, CacheRow};
+use tirds_models::loader_status::LoaderStatus;
+use tirds_models::migrations::{self, Compatibility, CURRENT_SCHEMA_VERSION};
 
 use crate::error::CacheError;
 
@@ -10,24 +14,81 @@ use crate::error::CacheError;
 /// and read by TIRDS. This struct provides read-only access.
 pub struct SqliteReader {
     conn: Connection,
+    schema_version: u32,
+    /// `data_version` seen by the last [`SqliteReader::data_changed`] call.
+    seen_version: Cell<Option<i64>>,
 }
 
 impl SqliteReader {
     /// Open a read-only connection to the shared cache database.
+    ///
+    /// Fails with [`CacheError::UnsupportedSchema`] when the loader has
+    /// migrated the database past what this reader understands. Older
+    /// databases open; reads of features they lack return nothing.
     pub fn open(path: &str) -> Result<Self, CacheError> {
         let conn = Connection::open_with_flags(
             path,
             rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY | rusqlite::OpenFlags::SQLITE_OPEN_NO_MUTEX,
         )?;
-        Ok(Self { conn })
+        Self::from_connection(conn)
     }
 
     /// Open an in-memory database. Useful for testing - creates the schema automatically.
     /// The in-memory DB is writable so tests can seed data.
     pub fn open_in_memory() -> Result<Self, CacheError> {
         let conn = Connection::open_in_memory()?;
-        conn.execute_batch(tirds_models::cache_schema::CACHE_TABLE_DDL)?;
-        Ok(Self { conn })
+        conn.execute_batch(migrations::SCHEMA_META_DDL)?;
+        for migration in migrations::MIGRATIONS {
+            for statement in migration.statements {
+                conn.execute_batch(statement)?;
+            }
+        }
+        conn.execute(
+            "INSERT INTO schema_meta (key, value) VALUES (?1, ?2)",
+            rusqlite::params![
+                migrations::SCHEMA_VERSION_KEY,
+                CURRENT_SCHEMA_VERSION.to_string()
+            ],
+        )?;
+        Self::from_connection(conn)
+    }
+
+    fn from_connection(conn: Connection) -> Result<Self, CacheError> {
+        let schema_version = check_schema(&conn)?;
+        Ok(Self {
+            conn,
+            schema_version,
+            seen_version: Cell::new(None),
+        })
+    }
+
+    /// SQLite's `data_version`: changes whenever another connection commits
+    /// to the database.
+    pub fn data_version(&self) -> Result<i64, CacheError> {
+        Ok(self
+            .conn
+            .query_row("PRAGMA data_version", [], |row| row.get(0))?)
+    }
+
+    /// Whether the database may have changed since the previous call: true on
+    /// the first call and whenever [`SqliteReader::data_version`] moved.
+    ///
+    /// `data_version` values are only comparable on the same connection, so
+    /// pooled readers track their own.
+    pub fn data_changed(&self) -> Result<bool, CacheError> {
+        let version = self.data_version()?;
+        Ok(self.seen_version.replace(Some(version)) != Some(version))
+    }
+
+    /// Path of the database file; None for in-memory databases.
+    pub fn path(&self) -> Option<&str> {
+        self.conn.path().filter(|p| !p.is_empty())
+    }
+
+    /// Schema version recorded by the loader; 0 for databases created
+    /// before versioning.
+    pub fn schema_version(&self) -> u32 {
+        self.schema_version
     }
 
     /// Get a single cache entry by key. Returns None if not found or expired.
@@ -111,6 +172,71 @@ impl SqliteReader {
         Ok(rows)
     }
 
+    /// Get the value a key had at `at` from the cache history. Returns None
+    /// if the key had no value then, or the loader doesn't record history.
+    ///
+    /// The returned row's `updated_at` is when that value was written and its
+    /// `expires_at` is when it stopped being current (or the live entry's
+    /// expiry, if it still is).
+    pub fn get_as_of(&self, key: &str, at: DateTime<Utc>) -> Result<Option<CacheRow>, CacheError> {
+        if !self.has_table("cache_history")? {
+            return Ok(None);
+        }
+        let mut stmt = self.conn.prepare_cached(HISTORY_SELECT_BY_KEY)?;
+        let result = stmt.query_row(rusqlite::params![key, history_timestamp(at)], history_row);
+
+        match result {
+            Ok(row) => Ok(Some(row)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(CacheError::Sqlite(e)),
+        }
+    }
+
+    /// Get all of a symbol's entries as they were at `at` from the cache
+    /// history. Empty if the loader doesn't record history.
+    pub fn get_by_symbol_as_of(
+        &self,
+        symbol: &str,
+        at: DateTime<Utc>,
+    ) -> Result<Vec<CacheRow>, CacheError> {
+        if !self.has_table("cache_history")? {
+            return Ok(Vec::new());
+        }
+        let mut stmt = self.conn.prepare_cached(HISTORY_SELECT_BY_SYMBOL)?;
+        let rows = stmt
+            .query_map(
+                rusqlite::params![symbol, history_timestamp(at)],
+                history_row,
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(rows)
+    }
+
+    fn has_table(&self, name: &str) -> Result<bool, CacheError> {
+        has_table(&self.conn, name)
+    }
+
+    /// Read the loader's heartbeat record.
+    /// Returns None if no loader has written one yet (or the table predates it).
+    pub fn loader_status(&self) -> Result<Option<LoaderStatus>, CacheError> {
+        if !self.has_table("loader_status")? {
+            return Ok(None);
+        }
+
+        let result = self.conn.query_row(
+            "SELECT status_json FROM loader_status WHERE id = 1",
+            [],
+            |row| row.get::<_, String>(0),
+        );
+
+        match result {
+            Ok(json) => Ok(Some(serde_json::from_str(&json)?)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(CacheError::Sqlite(e)),
+        }
+    }
+
     /// Insert a cache entry. In production, the data pipeline writes directly to SQLite.
     /// This method is available for testing and for the data pipeline crate to use.
     pub fn insert(&self, row: &CacheRow) -> Result<(), CacheError> {
@@ -133,6 +259,84 @@ impl SqliteReader {
     }
 }
 
+/// The history value of key `?1` current at `?2`, as a [`CacheRow`].
+const HISTORY_SELECT_BY_KEY: &str = "\
+SELECT h.key, h.category, h.value_json, h.source, h.symbol, h.valid_from, \
+COALESCE(h.valid_to, e.expires_at, h.valid_from), h.valid_from \
+FROM cache_history h LEFT JOIN cache_entries e ON e.key = h.key \
+WHERE h.key = ?1 AND h.valid_from <= ?2 AND (h.valid_to IS NULL OR h.valid_to > ?2)";
+
+/// History values of symbol `?1` current at `?2`, as [`CacheRow`]s.
+const HISTORY_SELECT_BY_SYMBOL: &str = "\
+SELECT h.key, h.category, h.value_json, h.source, h.symbol, h.valid_from, \
+COALESCE(h.valid_to, e.expires_at, h.valid_from), h.valid_from \
+FROM cache_history h LEFT JOIN cache_entries e ON e.key = h.key \
+WHERE h.symbol = ?1 AND h.valid_from <= ?2 AND (h.valid_to IS NULL OR h.valid_to > ?2)";
+
+fn history_row(row: &rusqlite::Row) -> rusqlite::Result<CacheRow> {
+    Ok(CacheRow {
+        key: row.get(0)?,
+        category: row.get(1)?,
+        value_json: row.get(2)?,
+        source: row.get(3)?,
+        symbol: row.get(4)?,
+        created_at: row.get(5)?,
+        expires_at: row.get(6)?,
+        updated_at: row.get(7)?,
+    })
+}
+
+/// Check that this build can read the database `conn` is open on and return
+/// its schema version (0 when it predates versioning).
+pub(crate) fn check_schema(conn: &Connection) -> Result<u32, CacheError> {
+    if !has_table(conn, "schema_meta")? {
+        // Created before schema versioning
+        return Ok(0);
+    }
+    let version = read_meta(conn, migrations::SCHEMA_VERSION_KEY)?.unwrap_or(0);
+    let min_reader_version = read_meta(conn, migrations::MIN_READER_VERSION_KEY)?.unwrap_or(1);
+    match migrations::reader_compatibility(version, min_reader_version) {
+        Compatibility::Current => {}
+        Compatibility::Older => {
+            tracing::debug!(version, "Cache schema predates this reader");
+        }
+        Compatibility::NewerReadable => {
+            tracing::warn!(
+                version,
+                supported = CURRENT_SCHEMA_VERSION,
+                "Cache schema is newer than this reader; newer features are ignored"
+            );
+        }
+        Compatibility::Unsupported => {
+            return Err(CacheError::UnsupportedSchema {
+                version,
+                min_reader_version,
+                supported: CURRENT_SCHEMA_VERSION,
+            });
+        }
+    }
+    Ok(version)
+}
+
+fn read_meta(conn: &Connection, key: &str) -> Result<Option<u32>, CacheError> {
+    let value: Option<String> = conn
+        .query_row(
+            "SELECT value FROM schema_meta WHERE key = ?1",
+            [key],
+            |row| row.get(0),
+        )
+        .optional()?;
+    Ok(value.and_then(|v| v.parse().ok()))
+}
+
+pub(crate) fn has_table(conn: &Connection, name: &str) -> Result<bool, CacheError> {
+    Ok(conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?1)",
+        [name],
+        |row| row.get(0),
+    )?)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -197,6 +401,145 @@ mod tests {
         assert_eq!(results.len(), 2);
     }
 
+    #[test]
+    fn loader_status_missing_returns_none() {
+        let reader = SqliteReader::open_in_memory().unwrap();
+        assert!(reader.loader_status().unwrap().is_none());
+    }
+
+    #[test]
+    fn loader_status_without_table_returns_none() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(tirds_models::cache_schema::CACHE_TABLE_DDL)
+            .unwrap();
+        let reader = SqliteReader::from_connection(conn).unwrap();
+        assert!(reader.loader_status().unwrap().is_none());
+    }
+
+    #[test]
+    fn loader_status_roundtrip() {
+        let reader = SqliteReader::open_in_memory().unwrap();
+        let status = LoaderStatus::new("0.1.0", 300);
+        reader
+            .conn
+            .execute(
+                "INSERT INTO loader_status (id, status_json, updated_at) VALUES (1, ?1, ?2)",
+                rusqlite::params![
+                    serde_json::to_string(&status).unwrap(),
+                    status.heartbeat_at.to_rfc3339()
+                ],
+            )
+            .unwrap();
+
+        assert_eq!(reader.loader_status().unwrap(), Some(status));
+    }
+
+    fn insert_history(reader: &SqliteReader, key: &str, value: &str, from: &str, to: Option<&str>) {
+        reader
+            .conn
+            .execute(
+                "INSERT INTO cache_history \
+                 (key, value_hash, value_json, category, source, symbol, valid_from, valid_to) \
+                 VALUES (?1, 0, ?2, 'indicator', 'test', ?3, ?4, ?5)",
+                rusqlite::params![key, value, key.rsplit(':').next(), from, to],
+            )
+            .unwrap();
+    }
+
+    fn at(ts: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(ts)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn get_as_of_returns_value_current_at_time() {
+        let reader = SqliteReader::open_in_memory().unwrap();
+        let key = "indicator:rsi_14:AAPL";
+        insert_history(
+            &reader,
+            key,
+            "30",
+            "2026-03-02T10:00:00.000000Z",
+            Some("2026-03-02T10:30:00.000000Z"),
+        );
+        insert_history(&reader, key, "35", "2026-03-02T10:30:00.000000Z", None);
+        insert_history(
+            &reader,
+            "indicator:rsi_14:TSLA",
+            "50",
+            "2026-03-02T10:00:00.000000Z",
+            None,
+        );
+
+        assert!(reader
+            .get_as_of(key, at("2026-03-02T09:59:59Z"))
+            .unwrap()
+            .is_none());
+        let before = reader.get_as_of(key, at("2026-03-02T10:29:59Z")).unwrap();
+        assert_eq!(before.unwrap().value_json, "30");
+        let after = reader.get_as_of(key, at("2026-03-02T10:32:00Z")).unwrap();
+        assert_eq!(after.unwrap().value_json, "35");
+
+        let rows = reader
+            .get_by_symbol_as_of("AAPL", at("2026-03-02T10:32:00Z"))
+            .unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].updated_at, "2026-03-02T10:30:00.000000Z");
+    }
+
+    #[test]
+    fn get_as_of_without_history_table_returns_none() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(tirds_models::cache_schema::CACHE_TABLE_DDL)
+            .unwrap();
+        let reader = SqliteReader::from_connection(conn).unwrap();
+        assert!(reader
+            .get_as_of("quote:AAPL", Utc::now())
+            .unwrap()
+            .is_none());
+        assert!(reader
+            .get_by_symbol_as_of("AAPL", Utc::now())
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn schema_version_checks() {
+        assert_eq!(
+            SqliteReader::open_in_memory().unwrap().schema_version(),
+            CURRENT_SCHEMA_VERSION
+        );
+
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(tirds_models::cache_schema::CACHE_TABLE_DDL)
+            .unwrap();
+        assert_eq!(
+            SqliteReader::from_connection(conn)
+                .unwrap()
+                .schema_version(),
+            0
+        );
+
+        let newer = |min_reader: u32| {
+            let reader = SqliteReader::open_in_memory().unwrap();
+            reader
+                .conn
+                .execute_batch(&format!(
+                    "UPDATE schema_meta SET value = '{}' WHERE key = 'schema_version'; \
+                     INSERT INTO schema_meta (key, value) VALUES ('min_reader_version', '{min_reader}');",
+                    CURRENT_SCHEMA_VERSION + 1
+                ))
+                .unwrap();
+            SqliteReader::from_connection(reader.conn)
+        };
+        assert!(newer(CURRENT_SCHEMA_VERSION).is_ok());
+        assert!(matches!(
+            newer(CURRENT_SCHEMA_VERSION + 1),
+            Err(CacheError::UnsupportedSchema { .. })
+        ));
+    }
+
     #[test]
     fn get_by_prefix() {
         let reader = SqliteReader::open_in_memory().unwrap();
@@ -0,0 +1,78 @@
+//! Compares full indicator recompute against incremental folding of one new
+//! 5-minute candle, for a single symbol over the default lookback.
+//!
+//! Run with:
+//! ```bash
+//! cargo bench -p tirds-loader --bench indicators
+//! ```
+
+use chrono::{Duration, TimeZone, Utc};
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use market_data_core::candle::Candle;
+use rust_decimal::Decimal;
+use tirds_loader::config::CalculationsConfig;
+use tirds_loader::sources::calculations::compute_indicators;
+use tirds_loader::sources::incremental::IncrementalIndicators;
+
+/// Five sessions of 5-minute candles (78 per session), laid out back to back.
+fn candles(count: usize) -> Vec<Candle> {
+    let base = Utc.with_ymd_and_hms(2024, 1, 16, 14, 30, 0).unwrap();
+    (0..count)
+        .map(|i| {
+            let price = Decimal::from(100 + (i * 7 % 31) as i64);
+            Candle {
+                timestamp: base + Duration::minutes(i as i64 * 5),
+                open: price,
+                high: price + Decimal::ONE,
+                low: price - Decimal::ONE,
+                close: price,
+                volume: 10_000,
+            }
+        })
+        .collect()
+}
+
+fn config() -> CalculationsConfig {
+    CalculationsConfig {
+        indicators: [
+            "sma_20",
+            "sma_50",
+            "ema_20",
+            "rsi_14",
+            "atr_14",
+            "macd",
+            "sma_20@15m",
+            "rsi_14@1h",
+        ]
+        .iter()
+        .map(|s| (*s).into())
+        .collect(),
+        warmup_bars: 60,
+        ..CalculationsConfig::default()
+    }
+}
+
+fn bench_indicators(c: &mut Criterion) {
+    let config = config();
+    let all = candles(78 * 5);
+    let previous = &all[..all.len() - 1];
+
+    let mut primed = IncrementalIndicators::new();
+    primed.compute("BENCH", previous, &config, true);
+
+    let mut group = c.benchmark_group("indicators");
+    group.bench_function("full", |b| {
+        b.iter(|| compute_indicators("BENCH", black_box(&all), &config))
+    });
+    group.bench_function("incremental", |b| {
+        b.iter_batched(
+            || primed.clone(),
+            |mut engine| engine.compute("BENCH", black_box(&all), &config, false),
+            BatchSize::SmallInput,
+        )
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_indicators);
+criterion_main!(benches);
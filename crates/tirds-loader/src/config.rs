@@ -1,4 +1,10 @@
-use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Deserializer, Serialize};
+use tirds_models::sector::SECTOR_ETFS;
+
+use crate::sector::SectorMap;
 
 /// Which market data provider to use for fetching missing data.
 #[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
@@ -7,6 +13,168 @@ pub enum ProviderKind {
     #[default]
     Yahoo,
     Alpaca,
+    /// Candle files in a local directory (see [`FilesProviderConfig`]).
+    Files,
+}
+
+/// Local candle files imported by `ProviderKind::Files`: `{path}/{SYMBOL}.{ext}`
+/// or any `{path}/{SYMBOL}/*.{ext}`, where ext is `csv`, `ndjson`/`jsonl` or `parquet`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct FilesProviderConfig {
+    pub path: String,
+    #[serde(default)]
+    pub columns: ColumnMapping,
+    /// `rfc3339`, `unix` (seconds), `unix_ms`, or a chrono format string such
+    /// as `%Y-%m-%d %H:%M:%S` read in `timezone`. Parquet timestamp columns are
+    /// read natively.
+    #[serde(default = "default_timestamp_format")]
+    pub timestamp_format: String,
+    /// IANA timezone for timestamps without an offset.
+    #[serde(default = "default_files_timezone")]
+    pub timezone: String,
+}
+
+impl FilesProviderConfig {
+    pub fn new(path: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            columns: ColumnMapping::default(),
+            timestamp_format: default_timestamp_format(),
+            timezone: default_files_timezone(),
+        }
+    }
+}
+
+/// Source column (CSV header, JSON field or Parquet column) for each candle field.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct ColumnMapping {
+    pub timestamp: String,
+    pub open: String,
+    pub high: String,
+    pub low: String,
+    pub close: String,
+    pub volume: String,
+}
+
+impl Default for ColumnMapping {
+    fn default() -> Self {
+        Self {
+            timestamp: "timestamp".to_string(),
+            open: "open".to_string(),
+            high: "high".to_string(),
+            low: "low".to_string(),
+            close: "close".to_string(),
+            volume: "volume".to_string(),
+        }
+    }
+}
+
+/// One provider in the fallback chain, with its request limits and circuit breaker.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ProviderSettings {
+    pub kind: ProviderKind,
+    /// Maximum fetches per minute; unlimited when omitted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requests_per_minute: Option<u32>,
+    /// Retries after a failed fetch before falling through to the next provider.
+    #[serde(default = "default_max_retries")]
+    pub max_retries: u32,
+    /// Delay before the first retry; doubles on each further retry.
+    #[serde(default = "default_backoff_ms")]
+    pub backoff_ms: u64,
+    /// Consecutive failed fetches (after retries) that open the circuit.
+    #[serde(default = "default_failure_threshold")]
+    pub failure_threshold: u32,
+    /// How long an open circuit skips the provider before trying it again.
+    #[serde(default = "default_cooldown_seconds")]
+    pub cooldown_seconds: u64,
+}
+
+impl ProviderSettings {
+    /// Settings for `kind` with default limits.
+    pub fn new(kind: ProviderKind) -> Self {
+        Self {
+            kind,
+            requests_per_minute: None,
+            max_retries: default_max_retries(),
+            backoff_ms: default_backoff_ms(),
+            failure_threshold: default_failure_threshold(),
+            cooldown_seconds: default_cooldown_seconds(),
+        }
+    }
+}
+
+/// Accepts `provider = "yahoo"`, `provider = ["alpaca", "yahoo"]`, or a list of
+/// `[[market_data.provider]]` tables (entries may mix names and tables).
+fn deserialize_providers<'de, D>(deserializer: D) -> Result<Vec<ProviderSettings>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Entry {
+        Kind(ProviderKind),
+        Settings(ProviderSettings),
+    }
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(Entry),
+        Many(Vec<Entry>),
+    }
+
+    let entries = match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(entry) => vec![entry],
+        OneOrMany::Many(entries) => entries,
+    };
+    Ok(entries
+        .into_iter()
+        .map(|entry| match entry {
+            Entry::Kind(kind) => ProviderSettings::new(kind),
+            Entry::Settings(settings) => settings,
+        })
+        .collect())
+}
+
+/// Bar timeframes the loader can produce by resampling 5-minute candles.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Timeframe {
+    #[serde(rename = "5m")]
+    FiveMinutes,
+    #[serde(rename = "15m")]
+    FifteenMinutes,
+    #[serde(rename = "1h")]
+    OneHour,
+    #[serde(rename = "1d")]
+    OneDay,
+    #[serde(rename = "1w")]
+    OneWeek,
+}
+
+impl Timeframe {
+    /// Suffix used in cache keys (e.g., `bars:AAPL:1d`, `indicator:rsi_14@1d:AAPL`).
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::FiveMinutes => "5m",
+            Self::FifteenMinutes => "15m",
+            Self::OneHour => "1h",
+            Self::OneDay => "1d",
+            Self::OneWeek => "1w",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "5m" => Some(Self::FiveMinutes),
+            "15m" => Some(Self::FifteenMinutes),
+            "1h" => Some(Self::OneHour),
+            "1d" => Some(Self::OneDay),
+            "1w" => Some(Self::OneWeek),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -15,6 +183,58 @@ pub struct LoaderConfig {
     pub market_data: MarketDataConfig,
     pub calculations: CalculationsConfig,
     pub stream: StreamConfig,
+    #[serde(default)]
+    pub derived: DerivedConfig,
+    #[serde(default)]
+    pub levels: LevelsConfig,
+    #[serde(default)]
+    pub regime: RegimeConfig,
+    #[serde(default)]
+    pub quality: QualityConfig,
+    /// Refresh schedules. When empty, every symbol and task is refreshed
+    /// every `market_data.refresh_interval_seconds`.
+    #[serde(default, rename = "schedule")]
+    pub schedules: Vec<ScheduleConfig>,
+}
+
+impl LoaderConfig {
+    /// Track the sector ETF of every configured symbol as a reference symbol,
+    /// and use the mapping for sector comparisons wherever `derived.sector_etfs`
+    /// doesn't already name an ETF.
+    pub fn apply_sector_map(&mut self, map: &SectorMap) {
+        for symbol in &self.market_data.symbols {
+            if let Some(etf) = map.sector_etf(symbol) {
+                if !self.market_data.reference_symbols.iter().any(|s| s == etf) {
+                    self.market_data.reference_symbols.push(etf.to_string());
+                }
+            }
+        }
+        for meta in map.iter() {
+            if let Some(etf) = &meta.sector_etf {
+                self.derived
+                    .sector_etfs
+                    .entry(meta.symbol.clone())
+                    .or_insert_with(|| etf.clone());
+            }
+        }
+    }
+
+    /// Longest expected gap between heartbeats: the shortest interval
+    /// schedule (plus its jitter) or the cleanup loop, whichever runs more
+    /// often. Cron schedules don't count, as their gaps can span days.
+    pub fn heartbeat_interval_seconds(&self) -> u64 {
+        let refresh = if self.schedules.is_empty() {
+            Some(self.market_data.refresh_interval_seconds)
+        } else {
+            self.schedules
+                .iter()
+                .filter_map(|s| s.every_seconds.map(|every| every + s.jitter_seconds))
+                .min()
+        };
+        refresh.map_or(self.cache.cleanup_interval_seconds, |r| {
+            r.min(self.cache.cleanup_interval_seconds)
+        })
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -24,6 +244,59 @@ pub struct LoaderCacheConfig {
     /// Interval in seconds between stale entry cleanup runs.
     #[serde(default = "default_cleanup_interval")]
     pub cleanup_interval_seconds: u64,
+    /// Interval in seconds between `VACUUM` runs, which return free pages to
+    /// the filesystem. 0 disables vacuuming.
+    #[serde(default = "default_vacuum_interval")]
+    pub vacuum_interval_seconds: u64,
+    #[serde(default)]
+    pub writer: WriterConfig,
+    #[serde(default)]
+    pub history: HistoryConfig,
+}
+
+/// Point-in-time history of cache values (`cache_history`).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct HistoryConfig {
+    /// Record every new cache value with the interval it was current for.
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Days to keep values after they stop being current. 0 keeps them forever.
+    #[serde(default = "default_history_retention_days")]
+    pub retention_days: u64,
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            retention_days: default_history_retention_days(),
+        }
+    }
+}
+
+/// The dedicated writer thread that owns the SQLite connection.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WriterConfig {
+    /// Write requests that can wait in the queue before senders block.
+    #[serde(default = "default_queue_capacity")]
+    pub queue_capacity: usize,
+    /// How long queued rows are collected (and coalesced by key) before
+    /// being committed in one transaction.
+    #[serde(default = "default_batch_window_ms")]
+    pub batch_window_ms: u64,
+    /// Commit early once this many distinct rows are pending.
+    #[serde(default = "default_max_batch_rows")]
+    pub max_batch_rows: usize,
+}
+
+impl Default for WriterConfig {
+    fn default() -> Self {
+        Self {
+            queue_capacity: default_queue_capacity(),
+            batch_window_ms: default_batch_window_ms(),
+            max_batch_rows: default_max_batch_rows(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -38,24 +311,388 @@ pub struct MarketDataConfig {
     /// Refresh interval in seconds.
     #[serde(default = "default_refresh_interval")]
     pub refresh_interval_seconds: u64,
-    /// Number of recent trading days of candles to load per symbol.
+    /// Number of recent calendar days of candles to load per symbol. Daily
+    /// indicators and the regime's slow period need several weeks.
     #[serde(default = "default_lookback_days")]
     pub lookback_days: u32,
     /// TTL in seconds for market data cache entries.
     #[serde(default = "default_market_ttl")]
     pub ttl_seconds: u64,
-    /// Which provider to use for fetching missing market data.
+    /// Providers tried in order when filling missing market data; a provider
+    /// that fails or has no data for a day falls through to the next.
+    #[serde(
+        default = "default_providers",
+        deserialize_with = "deserialize_providers"
+    )]
+    pub provider: Vec<ProviderSettings>,
+    /// Bar timeframes to write as `bars:{symbol}:{tf}`. Each is resampled from 5-minute candles.
+    #[serde(default = "default_timeframes")]
+    pub timeframes: Vec<Timeframe>,
+    /// How often to check the cache for on-demand symbol requests from the evaluator.
+    #[serde(default = "default_request_poll_interval")]
+    pub request_poll_interval_seconds: u64,
+    /// Optional symbol → sector mapping file (TOML or `.csv`). Mapped symbols
+    /// get a `meta:{symbol}` entry and their sector ETF is tracked automatically.
+    #[serde(default)]
+    pub sector_map_path: Option<String>,
+    /// Most trading days requested from a provider in one fetch when backfilling.
+    #[serde(default = "default_backfill_chunk_days")]
+    pub backfill_chunk_days: usize,
+    /// Market closures beyond the built-in NYSE holiday calendar (`"YYYY-MM-DD"`).
+    /// Days that aren't trading days are never requested from providers.
     #[serde(default)]
-    pub provider: ProviderKind,
+    pub holidays: Vec<NaiveDate>,
+    /// Required when a `files` provider is configured.
+    #[serde(default)]
+    pub files: Option<FilesProviderConfig>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CalculationsConfig {
-    /// Which indicators to compute. Format: "name" uses defaults, or "name_period" (e.g., "sma_20", "rsi_14").
-    pub indicators: Vec<String>,
+    /// Which indicators to compute. Each entry is either a shorthand string or a
+    /// structured [`IndicatorSpec`] table.
+    pub indicators: Vec<IndicatorEntry>,
     /// TTL in seconds for indicator cache entries.
     #[serde(default = "default_indicator_ttl")]
     pub ttl_seconds: u64,
+    /// Fold new candles into each symbol's cached indicator series instead of
+    /// recomputing the whole lookback window every cycle.
+    #[serde(default = "default_true")]
+    pub incremental: bool,
+    /// Bars of history (in the indicator's timeframe) recomputed ahead of the
+    /// new bars in incremental mode. Should cover the longest indicator period.
+    #[serde(default = "default_warmup_bars")]
+    pub warmup_bars: usize,
+    /// Every Nth refresh cycle recomputes from scratch, correcting drift in
+    /// recursive indicators (EMA, RSI, MACD) seeded from the warm-up window.
+    #[serde(default = "default_full_recompute_every")]
+    pub full_recompute_every: u32,
+}
+
+/// No indicators, with the serde defaults for everything else.
+impl Default for CalculationsConfig {
+    fn default() -> Self {
+        Self {
+            indicators: Vec::new(),
+            ttl_seconds: default_indicator_ttl(),
+            incremental: default_true(),
+            warmup_bars: default_warmup_bars(),
+            full_recompute_every: default_full_recompute_every(),
+        }
+    }
+}
+
+/// One configured indicator.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum IndicatorEntry {
+    /// "name" uses defaults, or "name_period" (e.g., "sma_20", "rsi_14").
+    /// Append "@{timeframe}" to compute on resampled bars (e.g., "rsi_14@1d").
+    /// The string itself is the output key name.
+    Shorthand(String),
+    Spec(IndicatorSpec),
+}
+
+impl From<&str> for IndicatorEntry {
+    fn from(s: &str) -> Self {
+        Self::Shorthand(s.to_string())
+    }
+}
+
+/// Structured indicator configuration, for calculations whose parameters
+/// don't fit the `name_period` shorthand (MACD, Bollinger Bands, stochastic).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct IndicatorSpec {
+    /// Calculation id in the market-calculations registry (e.g., "macd").
+    pub id: String,
+    /// Parameters passed to the calculation (e.g., `{ fast = 12, slow = 26, signal = 9 }`).
+    #[serde(default)]
+    pub params: BTreeMap<String, IndicatorParam>,
+    /// Timeframe of the bars to compute on.
+    #[serde(default = "default_indicator_timeframe")]
+    pub timeframe: Timeframe,
+    /// Output key name (`indicator:{alias}:{symbol}`). Defaults to the id,
+    /// suffixed with `@{timeframe}` when not 5m.
+    pub alias: Option<String>,
+}
+
+/// A calculation parameter value, mapped onto `market_calculations::ParamValue`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum IndicatorParam {
+    Bool(bool),
+    Integer(i64),
+    Float(f64),
+    String(String),
+}
+
+/// Cross-symbol metrics computed from candles already loaded each cycle.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DerivedConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Market benchmark symbol. Must be loaded (usually via `reference_symbols`).
+    #[serde(default = "default_benchmark")]
+    pub benchmark: String,
+    /// Timeframe of the bars whose returns are compared.
+    #[serde(default = "default_derived_timeframe")]
+    pub timeframe: Timeframe,
+    /// Number of returns in each rolling beta/correlation window.
+    #[serde(default = "default_derived_window")]
+    pub window: usize,
+    /// Symbol → sector ETF (e.g., `AAPL = "XLK"`). The ETF must be loaded.
+    #[serde(default)]
+    pub sector_etfs: BTreeMap<String, String>,
+}
+
+impl Default for DerivedConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            benchmark: default_benchmark(),
+            timeframe: default_derived_timeframe(),
+            window: default_derived_window(),
+            sector_etfs: BTreeMap::new(),
+        }
+    }
+}
+
+/// Support/resistance levels written as `levels:{symbol}`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LevelsConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Timeframe of the bars scanned for swing highs/lows.
+    #[serde(default = "default_swing_timeframe")]
+    pub swing_timeframe: Timeframe,
+    /// A swing high (low) must be above (below) this many bars on each side.
+    #[serde(default = "default_swing_strength")]
+    pub swing_strength: usize,
+    /// Most recent swing highs and lows to keep.
+    #[serde(default = "default_max_swings")]
+    pub max_swings: usize,
+    /// Number of price buckets in the volume profile.
+    #[serde(default = "default_profile_bins")]
+    pub profile_bins: usize,
+    /// Highest-volume buckets reported as volume nodes.
+    #[serde(default = "default_profile_nodes")]
+    pub profile_nodes: usize,
+    /// Share of total volume (percent) the value area must cover.
+    #[serde(default = "default_value_area_percent")]
+    pub value_area_percent: u32,
+}
+
+impl Default for LevelsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            swing_timeframe: default_swing_timeframe(),
+            swing_strength: default_swing_strength(),
+            max_swings: default_max_swings(),
+            profile_bins: default_profile_bins(),
+            profile_nodes: default_profile_nodes(),
+            value_area_percent: default_value_area_percent(),
+        }
+    }
+}
+
+/// How the loader handles candle data problems it can fix.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum QualityAction {
+    /// Only report issues; cache the bars as read.
+    Label,
+    /// Fix what can be fixed (sort, dedupe, clamp OHLC and wick spikes,
+    /// back-adjust splits) and drop bars that can't be.
+    #[default]
+    Repair,
+    /// Drop every bar with an issue (and all bars before a suspected split).
+    Drop,
+}
+
+/// Candle validation before caching; results are written as `quality:{symbol}`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct QualityConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub action: QualityAction,
+    /// A bar moving more than this many ATRs from the previous close is a spike.
+    #[serde(default = "default_spike_atr_multiple")]
+    pub spike_atr_multiple: f64,
+    /// Bars in the ATR used for spike detection.
+    #[serde(default = "default_atr_period")]
+    pub atr_period: usize,
+    /// Relative tolerance when matching an overnight move to a split ratio.
+    #[serde(default = "default_split_tolerance")]
+    pub split_tolerance: f64,
+    /// Most recent issues listed in the quality entry (counts cover all).
+    #[serde(default = "default_max_issues")]
+    pub max_issues: usize,
+}
+
+impl Default for QualityConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            action: QualityAction::default(),
+            spike_atr_multiple: default_spike_atr_multiple(),
+            atr_period: default_atr_period(),
+            split_tolerance: default_split_tolerance(),
+            max_issues: default_max_issues(),
+        }
+    }
+}
+
+/// Market and per-symbol regime classification written as `regime:market`
+/// and `regime:{symbol}`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RegimeConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Symbol whose trend defines the market trend. Must be loaded.
+    #[serde(default = "default_benchmark")]
+    pub benchmark: String,
+    /// Volatility index used for the market volatility bucket when loaded;
+    /// otherwise the benchmark's ATR is used.
+    #[serde(default = "default_volatility_index")]
+    pub volatility_index: String,
+    /// Timeframe of the bars the trend and ATR are measured on.
+    #[serde(default = "default_regime_timeframe")]
+    pub timeframe: Timeframe,
+    #[serde(default = "default_fast_period")]
+    pub fast_period: usize,
+    #[serde(default = "default_slow_period")]
+    pub slow_period: usize,
+    #[serde(default = "default_atr_period")]
+    pub atr_period: usize,
+    /// Efficiency ratio below which price action is classified as a range.
+    #[serde(default = "default_range_efficiency")]
+    pub range_efficiency: f64,
+    /// Volatility index levels separating low/normal/elevated/extreme.
+    #[serde(default = "default_vix_thresholds")]
+    pub vix_thresholds: [f64; 3],
+    /// ATR-percent-of-price levels separating low/normal/elevated/extreme.
+    #[serde(default = "default_atr_percent_thresholds")]
+    pub atr_percent_thresholds: [f64; 3],
+    /// Sector ETFs used for breadth. Only loaded symbols are counted.
+    #[serde(default = "default_breadth_symbols")]
+    pub breadth_symbols: Vec<String>,
+}
+
+impl Default for RegimeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            benchmark: default_benchmark(),
+            volatility_index: default_volatility_index(),
+            timeframe: default_regime_timeframe(),
+            fast_period: default_fast_period(),
+            slow_period: default_slow_period(),
+            atr_period: default_atr_period(),
+            range_efficiency: default_range_efficiency(),
+            vix_thresholds: default_vix_thresholds(),
+            atr_percent_thresholds: default_atr_percent_thresholds(),
+            breadth_symbols: default_breadth_symbols(),
+        }
+    }
+}
+
+/// A data family refreshed by a schedule.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum RefreshTask {
+    /// `quote:{symbol}` rows.
+    Quotes,
+    /// `bars:{symbol}:{tf}` rows, plus `quality:` and `meta:` rows.
+    Bars,
+    Indicators,
+    Levels,
+    Derived,
+    Regime,
+}
+
+impl RefreshTask {
+    pub const ALL: [RefreshTask; 6] = [
+        Self::Quotes,
+        Self::Bars,
+        Self::Indicators,
+        Self::Levels,
+        Self::Derived,
+        Self::Regime,
+    ];
+}
+
+/// Symbols covered by a schedule: a group name or an explicit list.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum ScheduleSymbols {
+    Group(SymbolGroup),
+    List(Vec<String>),
+}
+
+impl Default for ScheduleSymbols {
+    fn default() -> Self {
+        Self::Group(SymbolGroup::All)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SymbolGroup {
+    /// Configured and reference symbols.
+    All,
+    /// `market_data.symbols`.
+    Symbols,
+    /// `market_data.reference_symbols`.
+    Reference,
+}
+
+/// One `[[schedule]]`: which symbols and tasks to refresh, and when. Exactly
+/// one of `every_seconds` and `cron` must be set.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ScheduleConfig {
+    pub name: String,
+    #[serde(default)]
+    pub symbols: ScheduleSymbols,
+    #[serde(default = "default_refresh_tasks")]
+    pub tasks: Vec<RefreshTask>,
+    /// Bar timeframes written by this schedule (defaults to `market_data.timeframes`).
+    #[serde(default)]
+    pub timeframes: Option<Vec<Timeframe>>,
+    /// Fixed interval between run starts.
+    #[serde(default)]
+    pub every_seconds: Option<u64>,
+    /// Five-field cron expression (minute hour day-of-month month day-of-week).
+    #[serde(default)]
+    pub cron: Option<String>,
+    /// IANA timezone the cron expression is evaluated in.
+    #[serde(default = "default_schedule_timezone")]
+    pub timezone: String,
+    /// Random delay of up to this many seconds added to each run.
+    #[serde(default)]
+    pub jitter_seconds: u64,
+    /// Run once at startup. Defaults to true for interval schedules and false
+    /// for cron schedules.
+    #[serde(default)]
+    pub run_on_start: Option<bool>,
+}
+
+impl ScheduleConfig {
+    /// An interval schedule covering every symbol and task.
+    pub fn every(name: &str, seconds: u64) -> Self {
+        Self {
+            name: name.to_string(),
+            symbols: ScheduleSymbols::default(),
+            tasks: default_refresh_tasks(),
+            timeframes: None,
+            every_seconds: Some(seconds),
+            cron: None,
+            timezone: default_schedule_timezone(),
+            jitter_seconds: 0,
+            run_on_start: None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -71,24 +708,147 @@ pub struct StreamConfig {
 fn default_cleanup_interval() -> u64 {
     300
 }
+fn default_vacuum_interval() -> u64 {
+    86_400
+}
+fn default_history_retention_days() -> u64 {
+    30
+}
 fn default_reference_symbols() -> Vec<String> {
     vec!["SPY".to_string(), "VIX".to_string(), "QQQ".to_string()]
 }
 fn default_refresh_interval() -> u64 {
     300
 }
-fn default_lookback_days() -> u32 {
+fn default_timeframes() -> Vec<Timeframe> {
+    vec![Timeframe::FiveMinutes, Timeframe::OneDay]
+}
+fn default_request_poll_interval() -> u64 {
     5
 }
+fn default_lookback_days() -> u32 {
+    40
+}
 fn default_market_ttl() -> u64 {
     600
 }
+fn default_indicator_timeframe() -> Timeframe {
+    Timeframe::FiveMinutes
+}
+fn default_benchmark() -> String {
+    "SPY".to_string()
+}
+fn default_derived_timeframe() -> Timeframe {
+    Timeframe::FiveMinutes
+}
+fn default_derived_window() -> usize {
+    78
+}
+fn default_spike_atr_multiple() -> f64 {
+    8.0
+}
+fn default_split_tolerance() -> f64 {
+    0.03
+}
+fn default_max_issues() -> usize {
+    20
+}
+fn default_volatility_index() -> String {
+    "VIX".to_string()
+}
+fn default_regime_timeframe() -> Timeframe {
+    Timeframe::OneHour
+}
+fn default_fast_period() -> usize {
+    10
+}
+fn default_slow_period() -> usize {
+    30
+}
+fn default_atr_period() -> usize {
+    14
+}
+fn default_range_efficiency() -> f64 {
+    0.3
+}
+fn default_vix_thresholds() -> [f64; 3] {
+    [15.0, 20.0, 30.0]
+}
+fn default_atr_percent_thresholds() -> [f64; 3] {
+    [0.5, 1.0, 2.0]
+}
+fn default_breadth_symbols() -> Vec<String> {
+    SECTOR_ETFS.iter().map(|(_, etf)| etf.to_string()).collect()
+}
+fn default_swing_timeframe() -> Timeframe {
+    Timeframe::FifteenMinutes
+}
+fn default_swing_strength() -> usize {
+    3
+}
+fn default_max_swings() -> usize {
+    5
+}
+fn default_profile_bins() -> usize {
+    24
+}
+fn default_profile_nodes() -> usize {
+    3
+}
+fn default_value_area_percent() -> u32 {
+    70
+}
+fn default_warmup_bars() -> usize {
+    200
+}
+fn default_full_recompute_every() -> u32 {
+    12
+}
 fn default_indicator_ttl() -> u64 {
     600
 }
 fn default_stream_ttl() -> u64 {
     1800
 }
+fn default_backfill_chunk_days() -> usize {
+    30
+}
+fn default_timestamp_format() -> String {
+    "rfc3339".to_string()
+}
+fn default_files_timezone() -> String {
+    "UTC".to_string()
+}
+fn default_providers() -> Vec<ProviderSettings> {
+    vec![ProviderSettings::new(ProviderKind::Yahoo)]
+}
+fn default_max_retries() -> u32 {
+    2
+}
+fn default_backoff_ms() -> u64 {
+    500
+}
+fn default_failure_threshold() -> u32 {
+    3
+}
+fn default_cooldown_seconds() -> u64 {
+    300
+}
+fn default_queue_capacity() -> usize {
+    1024
+}
+fn default_batch_window_ms() -> u64 {
+    50
+}
+fn default_max_batch_rows() -> usize {
+    5000
+}
+fn default_refresh_tasks() -> Vec<RefreshTask> {
+    RefreshTask::ALL.to_vec()
+}
+fn default_schedule_timezone() -> String {
+    "America/New_York".to_string()
+}
 fn default_true() -> bool {
     true
 }
@@ -122,10 +882,62 @@ ttl_seconds = 1800
         let config: LoaderConfig = toml::from_str(toml_str).unwrap();
         assert_eq!(config.cache.sqlite_path, "data/tirds_cache.db");
         assert_eq!(config.market_data.symbols, vec!["AAPL", "TSLA"]);
-        assert_eq!(config.calculations.indicators, vec!["sma", "rsi"]);
+        assert_eq!(
+            config.calculations.indicators,
+            vec![IndicatorEntry::from("sma"), IndicatorEntry::from("rsi")]
+        );
         assert!(config.stream.enabled);
         // Provider defaults to Yahoo when omitted
-        assert_eq!(config.market_data.provider, ProviderKind::Yahoo);
+        assert_eq!(
+            config.market_data.provider,
+            vec![ProviderSettings::new(ProviderKind::Yahoo)]
+        );
+        assert_eq!(config.market_data.sector_map_path, None);
+        assert_eq!(config.market_data.backfill_chunk_days, 30);
+        assert!(config.market_data.holidays.is_empty());
+    }
+
+    #[test]
+    fn apply_sector_map_adds_etfs() {
+        let toml_str = r#"
+[cache]
+sqlite_path = "data/tirds_cache.db"
+
+[market_data]
+data_path = "/data"
+symbols = ["AAPL", "JPM"]
+reference_symbols = ["SPY", "XLK"]
+
+[calculations]
+indicators = ["rsi"]
+
+[stream]
+
+[derived.sector_etfs]
+JPM = "KBE"
+"#;
+        let mut config: LoaderConfig = toml::from_str(toml_str).unwrap();
+        let map = SectorMap::from_toml_str(
+            r#"
+            [symbols.AAPL]
+            sector = "Information Technology"
+            [symbols.JPM]
+            sector = "Financials"
+            [symbols.XOM]
+            sector = "Energy"
+            "#,
+        )
+        .unwrap();
+        config.apply_sector_map(&map);
+
+        assert_eq!(
+            config.market_data.reference_symbols,
+            vec!["SPY", "XLK", "XLF"]
+        );
+        assert_eq!(config.derived.sector_etfs["AAPL"], "XLK");
+        assert_eq!(config.derived.sector_etfs["JPM"], "KBE");
+        // Unconfigured symbols are mapped for on-demand requests, but their ETF isn't loaded upfront
+        assert_eq!(config.derived.sector_etfs["XOM"], "XLE");
     }
 
     #[test]
@@ -145,10 +957,27 @@ indicators = ["rsi"]
 "#;
         let config: LoaderConfig = toml::from_str(toml_str).unwrap();
         assert_eq!(config.market_data.refresh_interval_seconds, 300);
-        assert_eq!(config.market_data.lookback_days, 5);
+        assert_eq!(config.market_data.lookback_days, 40);
+        assert_eq!(config.market_data.request_poll_interval_seconds, 5);
+        assert!(config.calculations.incremental);
+        assert_eq!(config.derived, DerivedConfig::default());
+        assert_eq!(config.levels, LevelsConfig::default());
+        assert_eq!(config.regime, RegimeConfig::default());
+        assert_eq!(config.quality, QualityConfig::default());
+        assert_eq!(config.quality.action, QualityAction::Repair);
+        assert_eq!(config.regime.breadth_symbols.len(), 11);
+        assert_eq!(config.calculations.warmup_bars, 200);
+        assert_eq!(config.calculations.full_recompute_every, 12);
+        assert_eq!(
+            config.market_data.timeframes,
+            vec![Timeframe::FiveMinutes, Timeframe::OneDay]
+        );
         assert_eq!(config.stream.ttl_seconds, 1800);
         assert!(config.stream.enabled);
-        assert_eq!(config.market_data.provider, ProviderKind::Yahoo);
+        assert_eq!(
+            config.market_data.provider,
+            vec![ProviderSettings::new(ProviderKind::Yahoo)]
+        );
     }
 
     #[test]
@@ -168,7 +997,122 @@ indicators = ["rsi"]
 [stream]
 "#;
         let config: LoaderConfig = toml::from_str(toml_str).unwrap();
-        assert_eq!(config.market_data.provider, ProviderKind::Alpaca);
+        assert_eq!(
+            config.market_data.provider,
+            vec![ProviderSettings::new(ProviderKind::Alpaca)]
+        );
+    }
+
+    #[test]
+    fn deserialize_provider_chain() {
+        let toml_str = r#"
+[cache]
+sqlite_path = "data/tirds_cache.db"
+
+[market_data]
+data_path = "/data"
+symbols = ["AAPL"]
+provider = ["alpaca", { kind = "yahoo", requests_per_minute = 60, max_retries = 0 }]
+
+[calculations]
+indicators = ["rsi"]
+
+[stream]
+"#;
+        let config: LoaderConfig = toml::from_str(toml_str).unwrap();
+        let providers = &config.market_data.provider;
+        assert_eq!(providers.len(), 2);
+        assert_eq!(providers[0], ProviderSettings::new(ProviderKind::Alpaca));
+        assert_eq!(providers[1].kind, ProviderKind::Yahoo);
+        assert_eq!(providers[1].requests_per_minute, Some(60));
+        assert_eq!(providers[1].max_retries, 0);
+        assert_eq!(providers[1].failure_threshold, 3);
+    }
+
+    #[test]
+    fn deserialize_provider_tables() {
+        let toml_str = r#"
+[cache]
+sqlite_path = "data/tirds_cache.db"
+
+[market_data]
+data_path = "/data"
+symbols = ["AAPL"]
+
+[[market_data.provider]]
+kind = "alpaca"
+requests_per_minute = 200
+
+[[market_data.provider]]
+kind = "yahoo"
+cooldown_seconds = 60
+
+[calculations]
+indicators = ["rsi"]
+
+[stream]
+"#;
+        let config: LoaderConfig = toml::from_str(toml_str).unwrap();
+        let kinds: Vec<&ProviderKind> = config
+            .market_data
+            .provider
+            .iter()
+            .map(|p| &p.kind)
+            .collect();
+        assert_eq!(kinds, vec![&ProviderKind::Alpaca, &ProviderKind::Yahoo]);
+        assert_eq!(config.market_data.provider[1].cooldown_seconds, 60);
+    }
+
+    #[test]
+    fn deserialize_timeframes() {
+        let toml_str = r#"
+[cache]
+sqlite_path = "data/tirds_cache.db"
+
+[market_data]
+data_path = "/data"
+symbols = ["AAPL"]
+timeframes = ["5m", "15m", "1h", "1d", "1w"]
+
+[calculations]
+indicators = ["rsi_14@1d"]
+
+[stream]
+"#;
+        let config: LoaderConfig = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.market_data.timeframes.len(), 5);
+        assert_eq!(config.market_data.timeframes[4], Timeframe::OneWeek);
+
+        let bad = toml_str.replace("\"1w\"", "\"2w\"");
+        assert!(toml::from_str::<LoaderConfig>(&bad).is_err());
+    }
+
+    #[test]
+    fn deserialize_structured_indicators() {
+        let toml_str = r#"
+indicators = [
+    "rsi_14@1d",
+    { id = "macd", params = { fast = 12, slow = 26, signal = 9 }, timeframe = "1h", alias = "macd_hourly" },
+    { id = "bollinger_bands", params = { period = 20, std_dev = 2.5 } },
+]
+"#;
+        let config: CalculationsConfig = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.indicators.len(), 3);
+        assert_eq!(config.indicators[0], IndicatorEntry::from("rsi_14@1d"));
+
+        let IndicatorEntry::Spec(macd) = &config.indicators[1] else {
+            panic!("expected structured spec");
+        };
+        assert_eq!(macd.id, "macd");
+        assert_eq!(macd.timeframe, Timeframe::OneHour);
+        assert_eq!(macd.params["fast"], IndicatorParam::Integer(12));
+        assert_eq!(macd.alias.as_deref(), Some("macd_hourly"));
+
+        let IndicatorEntry::Spec(bb) = &config.indicators[2] else {
+            panic!("expected structured spec");
+        };
+        assert_eq!(bb.timeframe, Timeframe::FiveMinutes);
+        assert_eq!(bb.params["std_dev"], IndicatorParam::Float(2.5));
     }
 
     #[test]
@@ -177,6 +1121,12 @@ indicators = ["rsi"]
             cache: LoaderCacheConfig {
                 sqlite_path: "test.db".to_string(),
                 cleanup_interval_seconds: 300,
+                vacuum_interval_seconds: 86_400,
+                writer: WriterConfig {
+                    batch_window_ms: 20,
+                    ..WriterConfig::default()
+                },
+                history: HistoryConfig::default(),
             },
             market_data: MarketDataConfig {
                 data_path: "/data".to_string(),
@@ -185,20 +1135,163 @@ indicators = ["rsi"]
                 refresh_interval_seconds: 300,
                 lookback_days: 5,
                 ttl_seconds: 600,
-                provider: ProviderKind::Yahoo,
+                provider: vec![
+                    ProviderSettings::new(ProviderKind::Alpaca),
+                    ProviderSettings {
+                        requests_per_minute: Some(120),
+                        ..ProviderSettings::new(ProviderKind::Yahoo)
+                    },
+                ],
+                timeframes: vec![Timeframe::FiveMinutes, Timeframe::OneHour],
+                request_poll_interval_seconds: 5,
+                sector_map_path: Some("config/sectors.toml".to_string()),
+                backfill_chunk_days: 20,
+                holidays: vec![NaiveDate::from_ymd_opt(2025, 1, 9).unwrap()],
+                files: Some(FilesProviderConfig::new("/imports")),
             },
             calculations: CalculationsConfig {
-                indicators: vec!["sma".to_string()],
-                ttl_seconds: 600,
+                indicators: vec!["sma".into()],
+                ..CalculationsConfig::default()
             },
             stream: StreamConfig {
                 enabled: true,
                 ttl_seconds: 1800,
             },
+            derived: DerivedConfig::default(),
+            levels: LevelsConfig::default(),
+            regime: RegimeConfig::default(),
+            quality: QualityConfig::default(),
+            schedules: vec![
+                ScheduleConfig::every("default", 300),
+                ScheduleConfig {
+                    symbols: ScheduleSymbols::List(vec!["AAPL".to_string()]),
+                    tasks: vec![RefreshTask::Bars],
+                    timeframes: Some(vec![Timeframe::OneDay]),
+                    every_seconds: None,
+                    cron: Some("0 18 * * 1-5".to_string()),
+                    jitter_seconds: 30,
+                    ..ScheduleConfig::every("nightly", 0)
+                },
+            ],
         };
         let toml_str = toml::to_string(&config).unwrap();
         let parsed: LoaderConfig = toml::from_str(&toml_str).unwrap();
         assert_eq!(parsed.cache.sqlite_path, config.cache.sqlite_path);
         assert_eq!(parsed.market_data.symbols, config.market_data.symbols);
+        assert_eq!(parsed.market_data.timeframes, config.market_data.timeframes);
+        assert_eq!(parsed.market_data.provider, config.market_data.provider);
+        assert_eq!(parsed.market_data.files, config.market_data.files);
+        assert_eq!(parsed.market_data.holidays, config.market_data.holidays);
+        assert_eq!(parsed.schedules, config.schedules);
+        assert_eq!(parsed.cache.writer, config.cache.writer);
+    }
+
+    #[test]
+    fn deserialize_schedules() {
+        let toml_str = r#"
+[cache]
+sqlite_path = "data/tirds_cache.db"
+
+[market_data]
+data_path = "/data"
+symbols = ["AAPL"]
+
+[calculations]
+indicators = ["sma"]
+
+[stream]
+
+[[schedule]]
+name = "quotes"
+symbols = "symbols"
+tasks = ["quotes"]
+every_seconds = 15
+jitter_seconds = 2
+
+[[schedule]]
+name = "daily-bars"
+symbols = ["AAPL", "SPY"]
+tasks = ["bars", "indicators"]
+timeframes = ["1d"]
+cron = "30 18 * * mon-fri"
+"#;
+        let config: LoaderConfig = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.schedules.len(), 2);
+        let quotes = &config.schedules[0];
+        assert_eq!(quotes.symbols, ScheduleSymbols::Group(SymbolGroup::Symbols));
+        assert_eq!(quotes.tasks, vec![RefreshTask::Quotes]);
+        assert_eq!(quotes.every_seconds, Some(15));
+        assert_eq!(quotes.timezone, "America/New_York");
+        let bars = &config.schedules[1];
+        assert_eq!(
+            bars.symbols,
+            ScheduleSymbols::List(vec!["AAPL".to_string(), "SPY".to_string()])
+        );
+        assert_eq!(bars.timeframes, Some(vec![Timeframe::OneDay]));
+        assert_eq!(bars.cron.as_deref(), Some("30 18 * * mon-fri"));
+    }
+
+    #[test]
+    fn heartbeat_interval_follows_schedules() {
+        let toml_str = r#"
+[cache]
+sqlite_path = "data/tirds_cache.db"
+cleanup_interval_seconds = 600
+
+[market_data]
+data_path = "/data"
+symbols = ["AAPL"]
+refresh_interval_seconds = 60
+
+[calculations]
+indicators = ["sma"]
+
+[stream]
+"#;
+        let mut config: LoaderConfig = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.heartbeat_interval_seconds(), 60);
+
+        let mut nightly = ScheduleConfig::every("nightly", 0);
+        nightly.every_seconds = None;
+        nightly.cron = Some("0 18 * * 1-5".to_string());
+        config.schedules = vec![nightly];
+        assert_eq!(config.heartbeat_interval_seconds(), 600);
+
+        let mut quotes = ScheduleConfig::every("quotes", 15);
+        quotes.jitter_seconds = 2;
+        config.schedules.push(quotes);
+        assert_eq!(config.heartbeat_interval_seconds(), 17);
+    }
+
+    #[test]
+    fn deserialize_files_provider() {
+        let toml_str = r#"
+[cache]
+sqlite_path = "data/tirds_cache.db"
+
+[market_data]
+data_path = "/data"
+symbols = ["AAPL"]
+provider = ["files"]
+
+[market_data.files]
+path = "/imports"
+timestamp_format = "%Y-%m-%d %H:%M"
+timezone = "America/New_York"
+columns = { timestamp = "Date", volume = "Vol" }
+
+[calculations]
+indicators = ["rsi"]
+
+[stream]
+"#;
+        let config: LoaderConfig = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.market_data.provider[0].kind, ProviderKind::Files);
+        let files = config.market_data.files.unwrap();
+        assert_eq!(files.path, "/imports");
+        assert_eq!(files.columns.timestamp, "Date");
+        assert_eq!(files.columns.volume, "Vol");
+        assert_eq!(files.columns.close, "close");
+        assert_eq!(files.timezone, "America/New_York");
     }
 }
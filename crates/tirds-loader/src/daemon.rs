@@ -1,51 +1,158 @@
-use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::Arc;
 
-use chrono::Duration;
+use chrono::{Duration, NaiveDate, Utc};
+use market_data_core::candle::Candle;
 use market_data_core::store::CandleStore;
+use serde::Serialize;
+use tirds_models::cache_schema::key_patterns;
+use tirds_models::quality::DataQuality;
 use tokio_util::sync::CancellationToken;
 use tracing;
 
-use crate::config::LoaderConfig;
+use crate::config::{LoaderCacheConfig, LoaderConfig, RefreshTask};
 use crate::error::LoaderError;
-use crate::sources::{calculations, market_data, stream};
+use crate::schedule::{build_schedules, Schedule};
+use crate::sector::SectorMap;
+use crate::sources::calendar::ExchangeCalendar;
+use crate::sources::incremental::IncrementalIndicators;
+use crate::sources::providers::ProviderChain;
+use crate::sources::{calculations, derived, levels, market_data, quality, regime, stream};
+use crate::status::LoaderStatusTracker;
+use crate::write_queue::CacheWriter;
 use crate::writer::SqliteWriter;
 
 /// The loader daemon. Orchestrates periodic market data/calculation refreshes
 /// and real-time stream ingestion.
 pub struct Daemon {
     config: LoaderConfig,
-    writer: Arc<Mutex<SqliteWriter>>,
+    writer: CacheWriter,
+    status: LoaderStatusTracker,
+    sectors: Arc<SectorMap>,
+    providers: Arc<ProviderChain>,
     cancel: CancellationToken,
 }
 
 impl Daemon {
     pub fn new(config: LoaderConfig, writer: SqliteWriter) -> Self {
+        let status = LoaderStatusTracker::new(
+            env!("CARGO_PKG_VERSION"),
+            config.heartbeat_interval_seconds(),
+        );
         Self {
-            config,
-            writer: Arc::new(Mutex::new(writer)),
+            writer: CacheWriter::spawn(writer, &config.cache.writer),
+            status,
+            sectors: Arc::new(SectorMap::default()),
+            providers: Arc::new(ProviderChain::from_config(&config.market_data)),
             cancel: CancellationToken::new(),
+            config,
         }
     }
 
+    /// Write `meta:{symbol}` entries from a sector map and track configured
+    /// symbols' sector ETFs (see [`LoaderConfig::apply_sector_map`]).
+    pub fn with_sector_map(mut self, sectors: SectorMap) -> Self {
+        self.config.apply_sector_map(&sectors);
+        self.sectors = Arc::new(sectors);
+        self
+    }
+
+    /// The effective configuration (after applying the sector map).
+    pub fn config(&self) -> &LoaderConfig {
+        &self.config
+    }
+
+    /// Returns a handle to the loader's status (heartbeat, errors, lag counters).
+    pub fn status(&self) -> LoaderStatusTracker {
+        self.status.clone()
+    }
+
     /// Returns a CancellationToken that can be used to trigger shutdown.
     pub fn cancel_token(&self) -> CancellationToken {
         self.cancel.clone()
     }
 
+    /// Run a single refresh cycle (fill missing data, market data, indicators,
+    /// levels, derived metrics and regimes) and report what was written.
+    pub async fn run_once(&self) -> RefreshSummary {
+        let summary = self
+            .cycle(&self.providers)
+            .refresh(
+                &configured_symbols(&self.config),
+                &RefreshTask::ALL.into(),
+                Some(&mut IncrementalIndicators::new()),
+            )
+            .await;
+        if let Err(e) = self.status.write_heartbeat(&self.writer).await {
+            tracing::error!(error = %e, "Failed to write loader status");
+        }
+        self.flush().await;
+        summary
+    }
+
+    /// Fill the local candle store for `symbols` between `from` and `to` from
+    /// the provider chain. The cache is not touched.
+    pub async fn backfill(
+        &self,
+        symbols: &[String],
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<BackfillSummary, LoaderError> {
+        backfill(&self.config, &self.providers, symbols, from, to).await
+    }
+
+    /// Rebuild indicator, level, derived and regime rows from the local candle
+    /// store only: no providers are called and no market data rows are written.
+    pub async fn recompute(&self) -> RefreshSummary {
+        let local_only = ProviderChain::new(Vec::new());
+        let tasks = BTreeSet::from([
+            RefreshTask::Indicators,
+            RefreshTask::Levels,
+            RefreshTask::Derived,
+            RefreshTask::Regime,
+        ]);
+        let summary = self
+            .cycle(&local_only)
+            .refresh(&configured_symbols(&self.config), &tasks, None)
+            .await;
+        self.flush().await;
+        summary
+    }
+
+    /// Wait until every queued cache write has been committed.
+    async fn flush(&self) {
+        if let Err(e) = self.writer.call_async(|_| Ok(())).await {
+            tracing::error!(error = %e, "Failed to flush cache writes");
+        }
+    }
+
+    fn cycle<'a>(&'a self, providers: &'a ProviderChain) -> Cycle<'a> {
+        Cycle {
+            config: &self.config,
+            writer: &self.writer,
+            status: &self.status,
+            sectors: &self.sectors,
+            providers,
+        }
+    }
+
     /// Run the daemon until cancelled.
     pub async fn run(&self) -> Result<(), LoaderError> {
         tracing::info!("TIRDS loader daemon starting");
+        let schedules = build_schedules(&self.config)?;
 
         let mut join_set = tokio::task::JoinSet::new();
 
-        // Task 1: Combined market data + calculations periodic refresh
-        {
+        // Task 1: Market data + calculations refresh, one loop per schedule
+        for schedule in schedules {
             let config = self.config.clone();
             let writer = self.writer.clone();
+            let status = self.status.clone();
+            let sectors = self.sectors.clone();
+            let providers = self.providers.clone();
             let cancel = self.cancel.clone();
             join_set.spawn(async move {
-                combined_refresh_loop(config, writer, cancel).await;
+                schedule_loop(schedule, config, writer, status, sectors, providers, cancel).await;
             });
         }
 
@@ -53,6 +160,7 @@ impl Daemon {
         if self.config.stream.enabled {
             let stream_config = self.config.stream.clone();
             let writer = self.writer.clone();
+            let status = self.status.clone();
             let cancel = self.cancel.clone();
             join_set.spawn(async move {
                 // Create StreamManager and subscribe
@@ -72,6 +180,7 @@ impl Daemon {
                     writer,
                     rx,
                     stream_config.ttl_seconds,
+                    status,
                     cancel,
                 )
                 .await;
@@ -80,13 +189,27 @@ impl Daemon {
             });
         }
 
-        // Task 3: Stale entry cleanup
+        // Task 3: On-demand symbol requests from the evaluator
         {
+            let config = self.config.clone();
             let writer = self.writer.clone();
+            let status = self.status.clone();
+            let sectors = self.sectors.clone();
+            let providers = self.providers.clone();
             let cancel = self.cancel.clone();
-            let interval_secs = self.config.cache.cleanup_interval_seconds;
             join_set.spawn(async move {
-                cleanup_loop(writer, interval_secs, cancel).await;
+                symbol_request_loop(config, writer, status, sectors, providers, cancel).await;
+            });
+        }
+
+        // Task 4: Stale entry cleanup
+        {
+            let writer = self.writer.clone();
+            let status = self.status.clone();
+            let cancel = self.cancel.clone();
+            let cache = self.config.cache.clone();
+            join_set.spawn(async move {
+                cleanup_loop(writer, status, cache, cancel).await;
             });
         }
 
@@ -95,144 +218,608 @@ impl Daemon {
         // Wait for all tasks to complete (they run until cancelled)
         while join_set.join_next().await.is_some() {}
 
+        // Every task has stopped queueing writes; commit what is left.
+        let writer = self.writer.clone();
+        tokio::task::spawn_blocking(move || writer.shutdown())
+            .await
+            .map_err(|e| LoaderError::Writer(format!("cache writer shutdown panicked: {e}")))??;
         tracing::info!("TIRDS loader daemon stopped");
         Ok(())
     }
 }
 
-/// Combined periodic loop: fetch candles, write market data, compute indicators, write indicators.
-async fn combined_refresh_loop(
-    config: LoaderConfig,
-    writer: Arc<Mutex<SqliteWriter>>,
-    cancel: CancellationToken,
-) {
-    let interval = std::time::Duration::from_secs(config.market_data.refresh_interval_seconds);
+/// What one refresh cycle wrote, per step.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct RefreshSummary {
+    pub symbols: usize,
+    pub symbols_loaded: usize,
+    pub market_data_rows: usize,
+    pub indicator_rows: usize,
+    pub level_rows: usize,
+    pub derived_rows: usize,
+    pub regime_rows: usize,
+    /// Steps that failed, with their error.
+    pub step_errors: BTreeMap<String, String>,
+    /// Symbols whose data couldn't be loaded, with their last error.
+    pub symbol_errors: BTreeMap<String, String>,
+    /// Cache batches or jobs that failed to commit during the cycle.
+    pub write_errors: u64,
+}
 
-    // Run immediately on startup
-    run_combined_refresh(&config, &writer).await;
+impl RefreshSummary {
+    fn new(symbols: &[String], candle_data: &HashMap<String, Vec<Candle>>, rows: usize) -> Self {
+        Self {
+            symbols: symbols.len(),
+            symbols_loaded: candle_data.values().filter(|c| !c.is_empty()).count(),
+            market_data_rows: rows,
+            ..Self::default()
+        }
+    }
 
-    loop {
-        tokio::select! {
-            _ = cancel.cancelled() => {
-                tracing::info!("Combined refresh loop shutting down");
-                break;
+    fn finish(&mut self, status: &LoaderStatusTracker) {
+        self.symbol_errors = status.snapshot().symbol_errors.into_iter().collect();
+    }
+
+    /// True when every step succeeded.
+    pub fn is_ok(&self) -> bool {
+        self.step_errors.is_empty()
+    }
+}
+
+/// Fill the local candle store for `symbols` between `from` and `to` from
+/// `providers`. The cache is not touched, so no writer is needed.
+pub async fn backfill(
+    config: &LoaderConfig,
+    providers: &ProviderChain,
+    symbols: &[String],
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<BackfillSummary, LoaderError> {
+    if providers.is_empty() {
+        return Err(LoaderError::Config(
+            "no market data provider available for backfill".to_string(),
+        ));
+    }
+    let data_path = &config.market_data.data_path;
+    let store = CandleStore::new(data_path);
+    let calendar = ExchangeCalendar::new(config.market_data.holidays.iter().copied());
+    let backfill = market_data::Backfill {
+        store: &store,
+        providers,
+        calendar: &calendar,
+        chunk_days: config.market_data.backfill_chunk_days,
+    };
+
+    let mut summary = BackfillSummary::default();
+    for symbol in symbols {
+        let _guard = providers.backfill_guard().await;
+        let mut day_log = market_data::DayLog::load(data_path, symbol);
+        let before = day_log.clone();
+        match market_data::fill_missing_data(&backfill, &mut day_log, symbol, from, to).await {
+            Ok(days) => {
+                summary.days_written.insert(symbol.clone(), days);
             }
-            _ = tokio::time::sleep(interval) => {
-                run_combined_refresh(&config, &writer).await;
+            Err(e) => {
+                tracing::warn!(symbol, error = %e, "Backfill failed");
+                summary.errors.insert(symbol.clone(), e.to_string());
             }
         }
+        if day_log != before {
+            day_log.save(data_path, symbol)?;
+        }
     }
+    Ok(summary)
 }
 
-/// Execute one refresh cycle: fill missing data from provider, read candles, write to cache, compute indicators.
-async fn run_combined_refresh(config: &LoaderConfig, writer: &Arc<Mutex<SqliteWriter>>) {
-    let store = CandleStore::new(&config.market_data.data_path);
-    let end_date = chrono::Utc::now().date_naive();
-    let start_date = end_date - Duration::days(config.market_data.lookback_days as i64);
+/// Days written to the local store per symbol by a backfill.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct BackfillSummary {
+    pub days_written: BTreeMap<String, usize>,
+    pub errors: BTreeMap<String, String>,
+}
 
-    let all_symbols: Vec<String> = config
+/// Configured symbols followed by reference symbols.
+pub fn configured_symbols(config: &LoaderConfig) -> Vec<String> {
+    config
         .market_data
         .symbols
         .iter()
         .chain(config.market_data.reference_symbols.iter())
         .cloned()
-        .collect();
+        .collect()
+}
 
-    // Create provider for filling missing data (degrades gracefully on failure)
-    let provider = match market_data::create_provider(&config.market_data.provider) {
-        Ok(p) => Some(p),
-        Err(e) => {
-            tracing::warn!(error = %e, "Could not create provider; using local data only");
-            None
-        }
+/// Periodic refresh for one schedule. Each run finishes before the next is
+/// scheduled, so a slow cycle never overlaps the next one; runs missed in the
+/// meantime are skipped.
+async fn schedule_loop(
+    schedule: Schedule,
+    mut config: LoaderConfig,
+    writer: CacheWriter,
+    status: LoaderStatusTracker,
+    sectors: Arc<SectorMap>,
+    providers: Arc<ProviderChain>,
+    cancel: CancellationToken,
+) {
+    if let Some(timeframes) = &schedule.timeframes {
+        config.market_data.timeframes = timeframes.clone();
+    }
+    let cycle = Cycle {
+        config: &config,
+        writer: &writer,
+        status: &status,
+        sectors: &sectors,
+        providers: &providers,
+    };
+    let mut indicators = IncrementalIndicators::new();
+    tracing::info!(
+        schedule = %schedule.name,
+        symbols = schedule.symbols.len(),
+        tasks = ?schedule.tasks,
+        "Refresh schedule started"
+    );
+
+    let mut due = if schedule.run_on_start {
+        None
+    } else {
+        Some(schedule.next_run(None, Utc::now()))
     };
+    loop {
+        let next = due.unwrap_or_else(Utc::now);
+        let wait = (next - Utc::now()).to_std().unwrap_or_default() + schedule.jitter();
+        tokio::select! {
+            _ = cancel.cancelled() => {
+                tracing::info!(schedule = %schedule.name, "Refresh schedule shutting down");
+                break;
+            }
+            _ = tokio::time::sleep(wait) => {
+                let started = Utc::now();
+                cycle
+                    .refresh(&schedule.symbols, &schedule.tasks, Some(&mut indicators))
+                    .await;
+                if let Err(e) = status.write_heartbeat(&writer).await {
+                    tracing::error!(error = %e, "Failed to write loader status");
+                }
+                let now = Utc::now();
+                let following = schedule.next_run(Some(next), now);
+                if following > schedule.next_run(Some(next), started) {
+                    tracing::warn!(
+                        schedule = %schedule.name,
+                        seconds = (now - started).num_seconds(),
+                        "Refresh cycle overran its schedule; skipping missed runs"
+                    );
+                }
+                due = Some(following);
+            }
+        }
+    }
+}
 
-    // Collect candles for all symbols (used by both market data writes and calculations)
-    let mut candle_data: HashMap<String, Vec<market_data_core::candle::Candle>> = HashMap::new();
-    let mut total_market_rows = 0;
+/// What a refresh cycle needs from the daemon.
+#[derive(Clone, Copy)]
+struct Cycle<'a> {
+    config: &'a LoaderConfig,
+    writer: &'a CacheWriter,
+    status: &'a LoaderStatusTracker,
+    sectors: &'a SectorMap,
+    providers: &'a ProviderChain,
+}
 
-    for symbol in &all_symbols {
-        let category = if config
-            .market_data
-            .reference_symbols
-            .iter()
-            .any(|s| s == symbol)
-        {
-            "reference_symbol"
-        } else {
-            "market_data"
-        };
+impl Cycle<'_> {
+    /// Execute one refresh cycle for `symbols`: fill missing data from
+    /// providers, read candles, write market data rows and compute the
+    /// requested tasks. Without `indicators`, every indicator is recomputed
+    /// in full.
+    async fn refresh(
+        &self,
+        symbols: &[String],
+        tasks: &BTreeSet<RefreshTask>,
+        indicators: Option<&mut IncrementalIndicators>,
+    ) -> RefreshSummary {
+        let mark = self.writer.error_mark();
+        let (mut candle_data, market_rows) = self.load_symbols(symbols, tasks).await;
+        let mut summary = RefreshSummary::new(symbols, &candle_data, 0);
+        if tasks.contains(&RefreshTask::Quotes) || tasks.contains(&RefreshTask::Bars) {
+            summary.market_data_rows = self.record_step(
+                "market_data",
+                "Market data refresh",
+                market_rows,
+                &mut summary,
+            );
+        }
+
+        if tasks.contains(&RefreshTask::Derived) && self.config.derived.enabled {
+            // Benchmark and sector ETF candles are needed for the comparisons
+            self.read_comparisons(symbols, &mut candle_data);
+        }
+        self.refresh_computed(symbols, &candle_data, tasks, indicators, &mut summary)
+            .await;
+        if let Err(e) = self.writer.flush_since(mark).await {
+            tracing::error!(error = %e, "Cache writes failed during refresh");
+            summary
+                .step_errors
+                .insert("cache_write".to_string(), e.to_string());
+        }
+        summary.write_errors = self.writer.error_mark().saturating_sub(mark);
+        summary.finish(self.status);
+        summary
+    }
 
-        // Fill missing data from provider before reading
-        if let Some(ref provider) = provider {
-            if let Err(e) = market_data::fill_missing_data(
-                &store,
-                provider.as_ref(),
-                symbol,
-                start_date,
-                end_date,
+    /// Compute and write the requested indicator, level, derived and regime
+    /// rows from already loaded candles.
+    async fn refresh_computed(
+        &self,
+        symbols: &[String],
+        candle_data: &HashMap<String, Vec<Candle>>,
+        tasks: &BTreeSet<RefreshTask>,
+        indicators: Option<&mut IncrementalIndicators>,
+        summary: &mut RefreshSummary,
+    ) {
+        let config = self.config;
+        let writer = self.writer;
+        let ttl = config.calculations.ttl_seconds;
+        // The market regime needs the benchmark; partial symbol sets leave it alone.
+        let include_market = symbols.contains(&config.regime.benchmark);
+        let run = |task| tasks.contains(&task);
+
+        if run(RefreshTask::Indicators) {
+            let result = match indicators {
+                Some(indicators) if config.calculations.incremental => {
+                    indicators
+                        .refresh(symbols, candle_data, &config.calculations, writer)
+                        .await
+                }
+                _ => {
+                    calculations::refresh_calculations(
+                        symbols,
+                        candle_data,
+                        &config.calculations,
+                        writer,
+                    )
+                    .await
+                }
+            };
+            summary.indicator_rows =
+                self.record_step("calculations", "Indicator refresh", result, summary);
+        }
+        if run(RefreshTask::Levels) {
+            let result =
+                levels::refresh_levels(symbols, candle_data, &config.levels, ttl, writer).await;
+            summary.level_rows = self.record_step("levels", "Levels refresh", result, summary);
+        }
+        if run(RefreshTask::Derived) {
+            let result =
+                derived::refresh_derived(symbols, candle_data, &config.derived, ttl, writer).await;
+            summary.derived_rows =
+                self.record_step("derived", "Derived metrics refresh", result, summary);
+        }
+        if run(RefreshTask::Regime) {
+            let result = regime::refresh_regimes(
+                symbols,
+                candle_data,
+                &config.regime,
+                ttl,
+                include_market,
+                writer,
             )
-            .await
+            .await;
+            summary.regime_rows = self.record_step("regime", "Regime refresh", result, summary);
+        }
+    }
+
+    /// Log a step's outcome, stamp its refresh time on success and record its
+    /// error on failure. Returns the rows it wrote.
+    fn record_step(
+        &self,
+        step: &str,
+        label: &str,
+        result: Result<usize, LoaderError>,
+        summary: &mut RefreshSummary,
+    ) -> usize {
+        match result {
+            Ok(count) => {
+                tracing::info!(count, "{label} complete");
+                self.status.record_refresh(step);
+                count
+            }
+            Err(e) => {
+                tracing::error!(error = %e, "{label} failed");
+                summary.step_errors.insert(step.to_string(), e.to_string());
+                0
+            }
+        }
+    }
+
+    /// Add the derived-metric benchmark and sector ETFs of `symbols` to
+    /// `candle_data` from the local store, when not already loaded.
+    fn read_comparisons(&self, symbols: &[String], candle_data: &mut HashMap<String, Vec<Candle>>) {
+        let derived = &self.config.derived;
+        let comparisons: BTreeSet<&String> = std::iter::once(&derived.benchmark)
+            .chain(symbols.iter().filter_map(|s| derived.sector_etfs.get(s)))
+            .filter(|s| !candle_data.contains_key(*s))
+            .collect();
+        if comparisons.is_empty() {
+            return;
+        }
+        let store = CandleStore::new(&self.config.market_data.data_path);
+        let (start_date, end_date) = self.lookback();
+        for symbol in comparisons {
+            if let Ok((candles, _)) =
+                read_candles(self.config, &store, symbol, start_date, end_date)
             {
-                tracing::warn!(symbol, error = %e, "Failed to fill missing data from provider");
+                candle_data.insert(symbol.clone(), candles);
             }
         }
+    }
+
+    fn lookback(&self) -> (NaiveDate, NaiveDate) {
+        let end_date = Utc::now().date_naive();
+        let start_date = end_date - Duration::days(self.config.market_data.lookback_days as i64);
+        (start_date, end_date)
+    }
+
+    /// Fill missing data from the provider chain, read each symbol's candles
+    /// from the local store, and write the market data rows `tasks` asks for
+    /// (`quote:` for quotes; `bars:`, `quality:` and `meta:` for bars). Returns
+    /// the candles read per symbol and the number of rows written. Waits for
+    /// the rows to commit; if any fail, every symbol queued here gets the
+    /// error. The row count is an error when the commit failed or no symbol
+    /// had candles.
+    async fn load_symbols(
+        &self,
+        symbols: &[String],
+        tasks: &BTreeSet<RefreshTask>,
+    ) -> (HashMap<String, Vec<Candle>>, Result<usize, LoaderError>) {
+        let config = self.config;
+        let (status, providers) = (self.status, self.providers);
+        let store = CandleStore::new(&config.market_data.data_path);
+        let (start_date, end_date) = self.lookback();
+        let write_quotes = tasks.contains(&RefreshTask::Quotes);
+        let write_bars = tasks.contains(&RefreshTask::Bars);
+        let timeframes = if write_bars {
+            config.market_data.timeframes.clone()
+        } else {
+            Vec::new()
+        };
+
+        let calendar = ExchangeCalendar::new(config.market_data.holidays.iter().copied());
+        let backfill = market_data::Backfill {
+            store: &store,
+            providers,
+            calendar: &calendar,
+            chunk_days: config.market_data.backfill_chunk_days,
+        };
 
-        match store.read_range(symbol, start_date, end_date) {
-            Ok(candles) => {
-                let rows = market_data::candles_to_cache_rows(
+        // Collect candles for all symbols (used by both market data writes and calculations)
+        let mut candle_data: HashMap<String, Vec<Candle>> = HashMap::new();
+        let mut total_market_rows = 0;
+        let mut queued = Vec::new();
+        let mark = self.writer.error_mark();
+
+        for symbol in symbols {
+            let category = if config
+                .market_data
+                .reference_symbols
+                .iter()
+                .any(|s| s == symbol)
+            {
+                "reference_symbol"
+            } else {
+                "market_data"
+            };
+
+            // Fill missing data from the provider chain before reading
+            let data_path = &config.market_data.data_path;
+            let _guard = match providers.is_empty() {
+                true => None,
+                false => Some(providers.backfill_guard().await),
+            };
+            let mut day_log = market_data::DayLog::load(data_path, symbol);
+            if !providers.is_empty() {
+                let before = day_log.clone();
+                if let Err(e) = market_data::fill_missing_data(
+                    &backfill,
+                    &mut day_log,
                     symbol,
-                    &candles,
-                    category,
-                    config.market_data.ttl_seconds,
-                );
-                if !rows.is_empty() {
-                    match writer.lock() {
-                        Ok(mut w) => {
-                            if let Err(e) = w.upsert_batch(&rows) {
-                                tracing::error!(symbol, error = %e, "Failed to write market data");
-                            } else {
-                                total_market_rows += rows.len();
-                            }
-                        }
-                        Err(e) => {
-                            tracing::error!(error = %e, "Writer lock poisoned");
+                    start_date,
+                    end_date,
+                )
+                .await
+                {
+                    tracing::warn!(symbol, error = %e, "Failed to fill missing data from providers");
+                    status.record_symbol_error(symbol, &e.to_string());
+                }
+                if day_log != before {
+                    if let Err(e) = day_log.save(data_path, symbol) {
+                        tracing::warn!(symbol, error = %e, "Failed to save day log");
+                    }
+                }
+            }
+
+            match read_candles(config, &store, symbol, start_date, end_date) {
+                Ok((candles, data_quality)) => {
+                    let mut rows = market_data::candles_to_cache_rows(
+                        symbol,
+                        &candles,
+                        category,
+                        config.market_data.ttl_seconds,
+                        &timeframes,
+                    );
+                    if !write_quotes {
+                        let quote_key = key_patterns::quote(symbol);
+                        rows.retain(|row| row.key != quote_key);
+                    }
+                    market_data::apply_day_sources(&mut rows, &candles, &day_log.sources);
+                    if let Some(report) = data_quality.filter(|_| write_bars) {
+                        if !report.is_clean() {
+                            tracing::warn!(
+                                symbol,
+                                score = report.score,
+                                dropped = report.bars_dropped,
+                                repaired = report.bars_repaired,
+                                missing = report.bars_missing,
+                                "Candle data quality issues"
+                            );
                         }
+                        rows.push(quality::quality_to_cache_row(
+                            symbol,
+                            &report,
+                            config.market_data.ttl_seconds,
+                        ));
+                    }
+                    if let Some(meta) = self.sectors.get(symbol).filter(|_| write_bars) {
+                        rows.push(market_data::meta_to_cache_row(
+                            meta,
+                            config.market_data.ttl_seconds,
+                        ));
+                    }
+                    let count = rows.len();
+                    if let Err(e) = self.writer.upsert_batch(rows).await {
+                        tracing::error!(symbol, error = %e, "Failed to write market data");
+                        status.record_symbol_error(symbol, &e.to_string());
+                    } else {
+                        total_market_rows += count;
+                        queued.push(symbol);
+                    }
+                    if candles.is_empty() {
+                        status.record_symbol_error(symbol, "No candles available");
+                    } else {
+                        status.clear_symbol_error(symbol);
                     }
+                    candle_data.insert(symbol.clone(), candles);
+                }
+                Err(e) => {
+                    tracing::warn!(symbol, error = %e, "Failed to read candles");
+                    status.record_symbol_error(symbol, &e);
                 }
-                candle_data.insert(symbol.clone(), candles);
             }
-            Err(e) => {
-                tracing::warn!(symbol, error = %e, "Failed to read candles");
+        }
+
+        if !queued.is_empty() {
+            if let Err(e) = self.writer.flush_since(mark).await {
+                for symbol in queued {
+                    status.record_symbol_error(symbol, &e.to_string());
+                }
+                return (candle_data, Err(e));
             }
         }
+        if !symbols.is_empty() && candle_data.values().all(Vec::is_empty) {
+            let e = LoaderError::MarketData("no candles loaded for any symbol".to_string());
+            return (candle_data, Err(e));
+        }
+        (candle_data, Ok(total_market_rows))
     }
+}
 
-    tracing::info!(count = total_market_rows, "Market data refresh complete");
+/// Read a symbol's candles from the local store, applying the configured
+/// quality checks.
+fn read_candles(
+    config: &LoaderConfig,
+    store: &CandleStore,
+    symbol: &str,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Result<(Vec<Candle>, Option<DataQuality>), String> {
+    let candles = store
+        .read_range(symbol, start, end)
+        .map_err(|e| e.to_string())?;
+    if !config.quality.enabled {
+        return Ok((candles, None));
+    }
+    Ok(match quality::check_candles(&candles, &config.quality) {
+        Some((cleaned, report)) => (cleaned, Some(report)),
+        None => (candles, None),
+    })
+}
 
-    // Now compute indicators using the fetched candles
-    match calculations::refresh_calculations(
-        &all_symbols,
-        &candle_data,
-        &config.calculations,
-        writer,
-    ) {
-        Ok(count) => {
-            tracing::info!(count, "Indicator refresh complete");
+/// Poll the cache for on-demand symbol requests from the evaluator.
+async fn symbol_request_loop(
+    config: LoaderConfig,
+    writer: CacheWriter,
+    status: LoaderStatusTracker,
+    sectors: Arc<SectorMap>,
+    providers: Arc<ProviderChain>,
+    cancel: CancellationToken,
+) {
+    let interval = std::time::Duration::from_secs(config.market_data.request_poll_interval_seconds);
+
+    loop {
+        tokio::select! {
+            _ = cancel.cancelled() => {
+                tracing::info!("Symbol request loop shutting down");
+                break;
+            }
+            _ = tokio::time::sleep(interval) => {
+                let cycle = Cycle {
+                    config: &config,
+                    writer: &writer,
+                    status: &status,
+                    sectors: &sectors,
+                    providers: &providers,
+                };
+                run_symbol_requests(&cycle).await;
+            }
         }
+    }
+}
+
+/// Load candles and indicators for every pending symbol request, then mark
+/// each request loaded or failed.
+async fn run_symbol_requests(cycle: &Cycle<'_>) {
+    let Cycle { writer, status, .. } = *cycle;
+    let pending = match writer.call_async(|w| w.pending_symbol_requests()).await {
+        Ok(pending) => pending,
         Err(e) => {
-            tracing::error!(error = %e, "Indicator refresh failed");
+            tracing::error!(error = %e, "Failed to read symbol requests");
+            return;
+        }
+    };
+    if pending.is_empty() {
+        return;
+    }
+
+    tracing::info!(symbols = ?pending, "Loading on-demand symbol requests");
+    let summary = cycle
+        .refresh(&pending, &RefreshTask::ALL.into(), None)
+        .await;
+    // A failed step leaves every requested symbol incomplete
+    let step_error = (!summary.is_ok()).then(|| {
+        summary
+            .step_errors
+            .iter()
+            .map(|(step, e)| format!("{step}: {e}"))
+            .collect::<Vec<_>>()
+            .join("; ")
+    });
+    for symbol in &pending {
+        let error = summary
+            .symbol_errors
+            .get(symbol)
+            .cloned()
+            .or_else(|| step_error.clone());
+
+        let key = symbol.clone();
+        let completed = writer
+            .call_async(move |w| w.complete_symbol_request(&key, error.as_deref()))
+            .await;
+        if let Err(e) = completed {
+            tracing::error!(symbol, error = %e, "Failed to update symbol request");
         }
     }
+    status.record_refresh("symbol_requests");
 }
 
-/// Periodically clean up expired cache entries.
+/// Periodically clean up expired cache entries, checkpoint the WAL, vacuum
+/// when due and record the cache size.
 async fn cleanup_loop(
-    writer: Arc<Mutex<SqliteWriter>>,
-    interval_secs: u64,
+    writer: CacheWriter,
+    status: LoaderStatusTracker,
+    cache: LoaderCacheConfig,
     cancel: CancellationToken,
 ) {
-    let interval = std::time::Duration::from_secs(interval_secs);
+    let interval = std::time::Duration::from_secs(cache.cleanup_interval_seconds);
+    let vacuum_interval = std::time::Duration::from_secs(cache.vacuum_interval_seconds);
+    let mut last_vacuum = std::time::Instant::now();
 
     loop {
         tokio::select! {
@@ -241,22 +828,54 @@ async fn cleanup_loop(
                 break;
             }
             _ = tokio::time::sleep(interval) => {
-                match writer.lock() {
-                    Ok(w) => {
-                        match w.expire_stale() {
-                            Ok(deleted) if deleted > 0 => {
-                                tracing::info!(deleted, "Cleaned up stale cache entries");
-                            }
-                            Ok(_) => {}
-                            Err(e) => {
-                                tracing::error!(error = %e, "Stale cleanup failed");
-                            }
-                        }
+                match writer.call_async(|w| w.expire_stale()).await {
+                    Ok(deleted) if deleted > 0 => {
+                        tracing::info!(deleted, "Cleaned up stale cache entries");
                     }
+                    Ok(_) => {}
                     Err(e) => {
-                        tracing::error!(error = %e, "Writer lock poisoned during cleanup");
+                        tracing::error!(error = %e, "Stale cleanup failed");
+                    }
+                }
+                status.record_refresh("cleanup");
+                let retention_days = cache.history.retention_days;
+                if cache.history.enabled && retention_days > 0 {
+                    let cutoff = Utc::now() - Duration::days(retention_days as i64);
+                    match writer.call_async(move |w| w.prune_history(cutoff)).await {
+                        Ok(pruned) if pruned > 0 => {
+                            tracing::info!(pruned, "Pruned cache history");
+                        }
+                        Ok(_) => {}
+                        Err(e) => tracing::error!(error = %e, "Cache history pruning failed"),
+                    }
+                }
+                match writer.call_async(|w| w.checkpoint()).await {
+                    Ok(true) => status.record_refresh("checkpoint"),
+                    Ok(false) => tracing::debug!("WAL checkpoint blocked by readers; retrying next run"),
+                    Err(e) => tracing::error!(error = %e, "WAL checkpoint failed"),
+                }
+                if !vacuum_interval.is_zero() && last_vacuum.elapsed() >= vacuum_interval {
+                    last_vacuum = std::time::Instant::now();
+                    match writer.call_async(|w| w.vacuum()).await {
+                        Ok(()) => status.record_refresh("vacuum"),
+                        Err(e) => tracing::error!(error = %e, "Cache vacuum failed"),
                     }
                 }
+                match writer.call_async(|w| w.size()).await {
+                    Ok(size) => {
+                        tracing::debug!(
+                            db_bytes = size.db_bytes,
+                            wal_bytes = size.wal_bytes,
+                            free_bytes = size.free_bytes,
+                            "Cache size"
+                        );
+                        status.record_cache_size(size);
+                    }
+                    Err(e) => tracing::error!(error = %e, "Failed to measure cache size"),
+                }
+                if let Err(e) = status.write_heartbeat(&writer).await {
+                    tracing::error!(error = %e, "Failed to write loader status");
+                }
             }
         }
     }
@@ -23,6 +23,12 @@ pub enum LoaderError {
     #[error("Provider error: {0}")]
     Provider(String),
 
+    #[error("Cache writer error: {0}")]
+    Writer(String),
+
+    #[error("Cache schema error: {0}")]
+    Schema(String),
+
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 }
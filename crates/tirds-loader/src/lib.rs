@@ -1,5 +1,9 @@
 pub mod config;
 pub mod daemon;
 pub mod error;
+pub mod schedule;
+pub mod sector;
 pub mod sources;
+pub mod status;
+pub mod write_queue;
 pub mod writer;
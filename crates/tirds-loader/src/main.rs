@@ -1,9 +1,14 @@
 use anyhow::{Context, Result};
-use clap::Parser;
+use chrono::NaiveDate;
+use clap::{Parser, Subcommand};
+use serde::Serialize;
 use tracing_subscriber::EnvFilter;
 
 use tirds_loader::config::LoaderConfig;
-use tirds_loader::daemon::Daemon;
+use tirds_loader::daemon::{backfill, configured_symbols, Daemon};
+use tirds_loader::sector::SectorMap;
+use tirds_loader::sources::calculations::validate_indicators;
+use tirds_loader::sources::providers::ProviderChain;
 use tirds_loader::writer::SqliteWriter;
 
 #[derive(Parser, Debug)]
@@ -15,6 +20,31 @@ struct Cli {
     /// Path to loader configuration file
     #[arg(short, long, default_value = "config/tirds-loader.toml")]
     config: String,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Run the daemon until interrupted (the default)
+    Run,
+    /// Run one full refresh cycle, print a JSON summary and exit
+    Once,
+    /// Fill the local candle store from the providers without touching the cache
+    Backfill {
+        /// Symbols to backfill (comma-separated; defaults to all configured symbols)
+        #[arg(long, value_delimiter = ',')]
+        symbols: Vec<String>,
+        /// First date to fill (YYYY-MM-DD)
+        #[arg(long)]
+        from: NaiveDate,
+        /// Last date to fill (YYYY-MM-DD; defaults to today)
+        #[arg(long)]
+        to: Option<NaiveDate>,
+    },
+    /// Rebuild indicator rows from the local candle store only, then exit
+    Recompute,
 }
 
 #[tokio::main]
@@ -30,24 +60,91 @@ async fn main() -> Result<()> {
         .with_context(|| format!("Failed to read config: {}", cli.config))?;
     let config: LoaderConfig =
         toml::from_str(&config_str).with_context(|| "Failed to parse loader config")?;
+    validate_indicators(&config.calculations).context("Invalid indicator configuration")?;
 
-    let writer = SqliteWriter::open(&config.cache.sqlite_path)
-        .with_context(|| format!("Failed to open cache DB: {}", config.cache.sqlite_path))?;
+    let sector_map = match &config.market_data.sector_map_path {
+        Some(path) => Some(
+            SectorMap::load(path).with_context(|| format!("Failed to load sector map: {path}"))?,
+        ),
+        None => None,
+    };
+
+    match cli.command.unwrap_or(Command::Run) {
+        Command::Run => {
+            let daemon = open_daemon(config, sector_map)?;
+            let cancel = daemon.cancel_token();
 
-    let daemon = Daemon::new(config, writer);
-    let cancel = daemon.cancel_token();
+            // Handle shutdown signals
+            tokio::spawn(async move {
+                let _ = tokio::signal::ctrl_c().await;
+                tracing::info!("Received shutdown signal");
+                cancel.cancel();
+            });
 
-    // Handle shutdown signals
-    tokio::spawn(async move {
-        let _ = tokio::signal::ctrl_c().await;
-        tracing::info!("Received shutdown signal");
-        cancel.cancel();
-    });
+            daemon
+                .run()
+                .await
+                .map_err(|e| anyhow::anyhow!("Daemon error: {e}"))?;
+        }
+        Command::Once => {
+            let summary = open_daemon(config, sector_map)?.run_once().await;
+            print_summary(&summary)?;
+            if !summary.is_ok() {
+                std::process::exit(1);
+            }
+        }
+        Command::Backfill { symbols, from, to } => {
+            // Only the local candle store is written: leave the cache closed
+            let mut config = config;
+            if let Some(sector_map) = &sector_map {
+                config.apply_sector_map(sector_map);
+            }
+            let symbols = if symbols.is_empty() {
+                configured_symbols(&config)
+            } else {
+                symbols
+            };
+            let to = to.unwrap_or_else(|| chrono::Utc::now().date_naive());
+            let providers = ProviderChain::from_config(&config.market_data);
+            let summary = backfill(&config, &providers, &symbols, from, to)
+                .await
+                .map_err(|e| anyhow::anyhow!("Backfill error: {e}"))?;
+            print_summary(&summary)?;
+            if !summary.errors.is_empty() {
+                std::process::exit(1);
+            }
+        }
+        Command::Recompute => {
+            let summary = open_daemon(config, sector_map)?.recompute().await;
+            print_summary(&summary)?;
+            if !summary.is_ok() {
+                std::process::exit(1);
+            }
+        }
+    }
 
-    daemon
-        .run()
-        .await
-        .map_err(|e| anyhow::anyhow!("Daemon error: {e}"))?;
+    Ok(())
+}
+
+/// Open (and migrate) the cache for writing and build the daemon around it.
+fn open_daemon(config: LoaderConfig, sector_map: Option<SectorMap>) -> Result<Daemon> {
+    let mut writer = SqliteWriter::open(&config.cache.sqlite_path)
+        .with_context(|| format!("Failed to open cache DB: {}", config.cache.sqlite_path))?;
+    if config.cache.history.enabled {
+        writer = writer
+            .with_history()
+            .context("Failed to set up cache history")?;
+    }
+
+    let mut daemon = Daemon::new(config, writer);
+    if let Some(sector_map) = sector_map {
+        tracing::info!(symbols = sector_map.len(), "Loaded sector map");
+        daemon = daemon.with_sector_map(sector_map);
+    }
+    Ok(daemon)
+}
 
+fn print_summary(summary: &impl Serialize) -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(summary)?);
     Ok(())
 }
@@ -0,0 +1,432 @@
+use std::collections::BTreeSet;
+use std::str::FromStr;
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, TimeZone, Timelike, Utc};
+use chrono_tz::Tz;
+use rand::Rng;
+
+use crate::config::{
+    LoaderConfig, RefreshTask, ScheduleConfig, ScheduleSymbols, SymbolGroup, Timeframe,
+};
+use crate::daemon::configured_symbols;
+use crate::error::LoaderError;
+
+/// A resolved `[[schedule]]`: the symbols and tasks it refreshes and when.
+#[derive(Debug, Clone)]
+pub struct Schedule {
+    pub name: String,
+    pub symbols: Vec<String>,
+    pub tasks: BTreeSet<RefreshTask>,
+    pub timeframes: Option<Vec<Timeframe>>,
+    pub run_on_start: bool,
+    trigger: Trigger,
+    jitter_seconds: u64,
+}
+
+#[derive(Debug, Clone)]
+enum Trigger {
+    Every(Duration),
+    Cron(CronExpr, Tz),
+}
+
+impl Schedule {
+    pub fn from_config(
+        schedule: &ScheduleConfig,
+        config: &LoaderConfig,
+    ) -> Result<Self, LoaderError> {
+        let invalid =
+            |msg: String| LoaderError::Config(format!("schedule {}: {msg}", schedule.name));
+        let trigger = match (schedule.every_seconds, &schedule.cron) {
+            (Some(0), None) => return Err(invalid("every_seconds must be positive".to_string())),
+            (Some(seconds), None) => Trigger::Every(Duration::seconds(seconds as i64)),
+            (None, Some(cron)) => {
+                let expr = cron.parse::<CronExpr>().map_err(invalid)?;
+                let tz = Tz::from_str(&schedule.timezone)
+                    .map_err(|_| invalid(format!("unknown timezone {}", schedule.timezone)))?;
+                if expr.next_after(Utc::now(), tz).is_none() {
+                    return Err(invalid(format!("cron {cron:?} never fires")));
+                }
+                Trigger::Cron(expr, tz)
+            }
+            _ => {
+                return Err(invalid(
+                    "set exactly one of every_seconds and cron".to_string(),
+                ))
+            }
+        };
+
+        let market_data = &config.market_data;
+        let symbols = match &schedule.symbols {
+            ScheduleSymbols::Group(SymbolGroup::All) => configured_symbols(config),
+            ScheduleSymbols::Group(SymbolGroup::Symbols) => market_data.symbols.clone(),
+            ScheduleSymbols::Group(SymbolGroup::Reference) => market_data.reference_symbols.clone(),
+            ScheduleSymbols::List(symbols) => symbols.clone(),
+        };
+        let run_on_start = schedule
+            .run_on_start
+            .unwrap_or(matches!(trigger, Trigger::Every(_)));
+
+        Ok(Self {
+            name: schedule.name.clone(),
+            symbols,
+            tasks: schedule.tasks.iter().copied().collect(),
+            timeframes: schedule.timeframes.clone(),
+            run_on_start,
+            trigger,
+            jitter_seconds: schedule.jitter_seconds,
+        })
+    }
+
+    /// When to run next, given when the previous run was due (if any) and the
+    /// current time. Interval runs that were missed while a slow cycle was
+    /// still going are skipped rather than run back to back. Jitter is not
+    /// included (see [`Schedule::jitter`]).
+    pub fn next_run(&self, previous: Option<DateTime<Utc>>, now: DateTime<Utc>) -> DateTime<Utc> {
+        match &self.trigger {
+            Trigger::Every(interval) => match previous {
+                None => now,
+                Some(previous) => {
+                    let next = previous + *interval;
+                    if next >= now {
+                        next
+                    } else {
+                        let missed = (now - next).num_milliseconds() / interval.num_milliseconds();
+                        next + *interval * (missed as i32 + 1)
+                    }
+                }
+            },
+            Trigger::Cron(expr, tz) => expr
+                .next_after(now, *tz)
+                .unwrap_or(now + Duration::days(365)),
+        }
+    }
+
+    /// A random delay of up to `jitter_seconds`.
+    pub fn jitter(&self) -> std::time::Duration {
+        if self.jitter_seconds == 0 {
+            return std::time::Duration::ZERO;
+        }
+        let millis = rand::rng().random_range(0..=self.jitter_seconds * 1000);
+        std::time::Duration::from_millis(millis)
+    }
+}
+
+/// Resolve `[[schedule]]` entries, or the single default schedule covering
+/// every symbol and task every `market_data.refresh_interval_seconds`.
+pub fn build_schedules(config: &LoaderConfig) -> Result<Vec<Schedule>, LoaderError> {
+    if config.schedules.is_empty() {
+        let default = ScheduleConfig::every("default", config.market_data.refresh_interval_seconds);
+        return Ok(vec![Schedule::from_config(&default, config)?]);
+    }
+    config
+        .schedules
+        .iter()
+        .map(|s| Schedule::from_config(s, config))
+        .collect()
+}
+
+/// A five-field cron expression: minute, hour, day of month, month, day of
+/// week. Fields accept `*`, values, ranges, lists and `/step`; months and
+/// weekdays also accept three-letter names.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronExpr {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    /// Whether the day-of-month/day-of-week fields exclude any day. When both
+    /// do, a day matching either one fires (standard cron behavior).
+    days_restricted: bool,
+    weekdays_restricted: bool,
+}
+
+const MONTHS: [&str; 12] = [
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+const WEEKDAYS: [&str; 7] = ["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+/// Bitmasks of every day of the month (1-31) and every weekday (0-6).
+const ALL_DAYS: u64 = ((1 << 32) - 1) & !1;
+const ALL_WEEKDAYS: u64 = (1 << 7) - 1;
+
+impl FromStr for CronExpr {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let fields: Vec<&str> = s.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            return Err(format!("cron {s:?} must have five fields"));
+        };
+        let mut weekdays = parse_field(weekday, 0, 7, &WEEKDAYS, 0)?;
+        // 7 is Sunday too
+        if weekdays & (1 << 7) != 0 {
+            weekdays = (weekdays | 1) & !(1 << 7);
+        }
+        let days = parse_field(day, 1, 31, &[], 1)?;
+        Ok(Self {
+            minutes: parse_field(minute, 0, 59, &[], 0)?,
+            hours: parse_field(hour, 0, 23, &[], 0)?,
+            days,
+            months: parse_field(month, 1, 12, &MONTHS, 1)?,
+            weekdays,
+            days_restricted: days != ALL_DAYS,
+            weekdays_restricted: weekdays != ALL_WEEKDAYS,
+        })
+    }
+}
+
+/// Parse one cron field into a bitmask of allowed values.
+fn parse_field(field: &str, min: u32, max: u32, names: &[&str], first: u32) -> Result<u64, String> {
+    let value = |s: &str| -> Result<u32, String> {
+        let lower = s.to_ascii_lowercase();
+        if let Some(i) = names.iter().position(|n| *n == lower) {
+            return Ok(i as u32 + first);
+        }
+        s.parse::<u32>()
+            .ok()
+            .filter(|v| (min..=max).contains(v))
+            .ok_or_else(|| format!("invalid cron value {s:?} (expected {min}-{max})"))
+    };
+
+    let mut mask = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step = step
+                    .parse::<u32>()
+                    .ok()
+                    .filter(|s| *s > 0)
+                    .ok_or_else(|| format!("invalid cron step {step:?}"))?;
+                (range, step)
+            }
+            None => (part, 1),
+        };
+        let (start, end) = match range {
+            "*" => (min, max),
+            _ => match range.split_once('-') {
+                Some((start, end)) => (value(start)?, value(end)?),
+                // `5/15` means every 15 from 5
+                None if step > 1 => (value(range)?, max),
+                None => {
+                    let v = value(range)?;
+                    (v, v)
+                }
+            },
+        };
+        if start > end {
+            return Err(format!("invalid cron range {range:?}"));
+        }
+        for v in (start..=end).step_by(step as usize) {
+            mask |= 1 << v;
+        }
+    }
+    Ok(mask)
+}
+
+impl CronExpr {
+    fn day_matches(&self, date: NaiveDate) -> bool {
+        let day = self.days & (1 << date.day()) != 0;
+        let weekday = self.weekdays & (1 << date.weekday().num_days_from_sunday()) != 0;
+        match (self.days_restricted, self.weekdays_restricted) {
+            (true, true) => day || weekday,
+            _ => day && weekday,
+        }
+    }
+
+    /// The first matching minute strictly after `after`, evaluated in `tz`.
+    /// Local times skipped by a DST change never fire. Gives up after five
+    /// years (e.g. `0 0 30 2 *`).
+    pub fn next_after(&self, after: DateTime<Utc>, tz: Tz) -> Option<DateTime<Utc>> {
+        let local = after.with_timezone(&tz).naive_local();
+        let mut t = local.with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        let limit = t + Duration::days(5 * 366);
+        while t < limit {
+            if self.months & (1 << t.month()) == 0 || !self.day_matches(t.date()) {
+                t = start_of_day(t.date() + Duration::days(1));
+                continue;
+            }
+            if self.hours & (1 << t.hour()) == 0 {
+                t = t.with_minute(0)? + Duration::hours(1);
+                continue;
+            }
+            if self.minutes & (1 << t.minute()) != 0 {
+                if let Some(at) = tz.from_local_datetime(&t).earliest() {
+                    let at = at.with_timezone(&Utc);
+                    if at > after {
+                        return Some(at);
+                    }
+                }
+            }
+            t += Duration::minutes(1);
+        }
+        None
+    }
+}
+
+fn start_of_day(date: NaiveDate) -> NaiveDateTime {
+    date.and_hms_opt(0, 0, 0).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono_tz::America::New_York;
+
+    fn utc(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, m, d, h, min, 0).unwrap()
+    }
+
+    fn config(schedules: &str) -> LoaderConfig {
+        let toml_str = format!(
+            r#"
+[cache]
+sqlite_path = "cache.db"
+
+[market_data]
+data_path = "/data"
+symbols = ["AAPL", "MSFT"]
+reference_symbols = ["SPY"]
+refresh_interval_seconds = 300
+
+[calculations]
+indicators = ["sma"]
+
+[stream]
+{schedules}
+"#
+        );
+        toml::from_str(&toml_str).unwrap()
+    }
+
+    #[test]
+    fn cron_fields() {
+        let expr: CronExpr = "*/15 9-16 * * mon-fri".parse().unwrap();
+        // Friday 2025-01-17 16:50 NY -> Monday 09:00 NY
+        let next = expr.next_after(utc(2025, 1, 17, 21, 50), New_York).unwrap();
+        assert_eq!(next, utc(2025, 1, 20, 14, 0));
+        // 09:07 NY -> 09:15 NY
+        let next = expr.next_after(utc(2025, 1, 21, 14, 7), New_York).unwrap();
+        assert_eq!(next, utc(2025, 1, 21, 14, 15));
+
+        let sundays: CronExpr = "0 0 * * 7".parse().unwrap();
+        let next = sundays.next_after(utc(2025, 1, 15, 0, 0), Tz::UTC).unwrap();
+        assert_eq!(next, utc(2025, 1, 19, 0, 0));
+    }
+
+    #[test]
+    fn cron_day_of_month_or_weekday() {
+        // The 1st of the month or any Monday
+        let expr: CronExpr = "0 12 1 * mon".parse().unwrap();
+        let next = expr.next_after(utc(2025, 1, 28, 13, 0), Tz::UTC).unwrap();
+        assert_eq!(next, utc(2025, 2, 1, 12, 0));
+        let next = expr.next_after(utc(2025, 2, 1, 13, 0), Tz::UTC).unwrap();
+        assert_eq!(next, utc(2025, 2, 3, 12, 0));
+
+        // Fields spelled out to cover every day don't restrict
+        for every_day in ["0 12 1-31 * mon", "0 12 */1 * mon"] {
+            let expr: CronExpr = every_day.parse().unwrap();
+            let next = expr.next_after(utc(2025, 1, 28, 13, 0), Tz::UTC).unwrap();
+            assert_eq!(next, utc(2025, 2, 3, 12, 0), "{every_day}");
+        }
+        let expr: CronExpr = "0 12 1 * 0-7".parse().unwrap();
+        let next = expr.next_after(utc(2025, 1, 28, 13, 0), Tz::UTC).unwrap();
+        assert_eq!(next, utc(2025, 2, 1, 12, 0));
+        // A stepped day field still restricts: odd days or Mondays
+        let expr: CronExpr = "0 12 */2 * mon".parse().unwrap();
+        let next = expr.next_after(utc(2025, 2, 1, 13, 0), Tz::UTC).unwrap();
+        assert_eq!(next, utc(2025, 2, 3, 12, 0));
+        let next = expr.next_after(utc(2025, 2, 3, 13, 0), Tz::UTC).unwrap();
+        assert_eq!(next, utc(2025, 2, 5, 12, 0));
+    }
+
+    #[test]
+    fn cron_rejects_bad_expressions() {
+        for bad in [
+            "* * * *",
+            "60 * * * *",
+            "* * * * foo",
+            "*/0 * * * *",
+            "5-1 * * * *",
+        ] {
+            assert!(bad.parse::<CronExpr>().is_err(), "{bad}");
+        }
+        let never: CronExpr = "0 0 30 2 *".parse().unwrap();
+        assert!(never.next_after(utc(2025, 1, 1, 0, 0), Tz::UTC).is_none());
+    }
+
+    #[test]
+    fn cron_follows_dst() {
+        // 18:00 New York is 23:00 UTC in winter and 22:00 UTC in summer.
+        let expr: CronExpr = "0 18 * * *".parse().unwrap();
+        assert_eq!(
+            expr.next_after(utc(2025, 1, 10, 12, 0), New_York),
+            Some(utc(2025, 1, 10, 23, 0))
+        );
+        assert_eq!(
+            expr.next_after(utc(2025, 7, 10, 12, 0), New_York),
+            Some(utc(2025, 7, 10, 22, 0))
+        );
+    }
+
+    #[test]
+    fn default_schedule_covers_everything() {
+        let schedules = build_schedules(&config("")).unwrap();
+        assert_eq!(schedules.len(), 1);
+        let schedule = &schedules[0];
+        assert_eq!(schedule.symbols, vec!["AAPL", "MSFT", "SPY"]);
+        assert_eq!(schedule.tasks.len(), RefreshTask::ALL.len());
+        assert!(schedule.run_on_start);
+        let now = utc(2025, 1, 13, 15, 0);
+        assert_eq!(schedule.next_run(None, now), now);
+        assert_eq!(
+            schedule.next_run(Some(now), now),
+            now + Duration::seconds(300)
+        );
+    }
+
+    #[test]
+    fn slow_cycles_skip_missed_runs() {
+        let schedule = &build_schedules(&config("")).unwrap()[0];
+        let due = utc(2025, 1, 13, 15, 0);
+        // The run due at 15:00 finished at 15:12: 15:05 and 15:10 are skipped.
+        let now = due + Duration::minutes(12);
+        assert_eq!(
+            schedule.next_run(Some(due), now),
+            due + Duration::minutes(15)
+        );
+    }
+
+    #[test]
+    fn schedule_groups_and_validation() {
+        let config = config(
+            r#"
+[[schedule]]
+name = "reference"
+symbols = "reference"
+tasks = ["quotes"]
+every_seconds = 60
+jitter_seconds = 5
+
+[[schedule]]
+name = "nightly"
+tasks = ["bars"]
+cron = "0 18 * * mon-fri"
+"#,
+        );
+        let schedules = build_schedules(&config).unwrap();
+        assert_eq!(schedules[0].symbols, vec!["SPY"]);
+        assert!(schedules[0].jitter() <= std::time::Duration::from_secs(5));
+        assert_eq!(schedules[1].symbols.len(), 3);
+        assert!(!schedules[1].run_on_start);
+
+        let mut bad = config.clone();
+        bad.schedules[0].cron = Some("* * * * *".to_string());
+        assert!(build_schedules(&bad).is_err());
+        let mut bad = config.clone();
+        bad.schedules[1].timezone = "Mars/Olympus".to_string();
+        assert!(build_schedules(&bad).is_err());
+        let mut bad = config;
+        bad.schedules[0].every_seconds = Some(0);
+        assert!(build_schedules(&bad).is_err());
+    }
+}
@@ -0,0 +1,238 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde::Deserialize;
+use tirds_models::sector::{self, SymbolMeta};
+
+use crate::error::LoaderError;
+
+/// Entry shape in the TOML form, keyed by symbol.
+#[derive(Debug, Deserialize)]
+struct TomlEntry {
+    sector: String,
+    #[serde(default)]
+    industry: Option<String>,
+    #[serde(default, alias = "etf")]
+    sector_etf: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TomlSectorMap {
+    #[serde(default)]
+    symbols: BTreeMap<String, TomlEntry>,
+}
+
+/// Symbol → sector mapping loaded from a local TOML or CSV file.
+///
+/// TOML:
+///
+/// ```toml
+/// [symbols.AAPL]
+/// sector = "Information Technology"
+/// industry = "Technology Hardware, Storage & Peripherals"
+/// # sector_etf = "XLK"  # optional override
+/// ```
+///
+/// CSV with a header row: `symbol,sector,industry,sector_etf` (the last two
+/// columns optional; fields containing commas must be double-quoted).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SectorMap {
+    symbols: BTreeMap<String, SymbolMeta>,
+}
+
+impl SectorMap {
+    /// Load a map from disk, choosing the format by file extension (`.csv` or TOML).
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, LoaderError> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            LoaderError::Config(format!("Failed to read sector map {}: {e}", path.display()))
+        })?;
+        let is_csv = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("csv"));
+        if is_csv {
+            Self::from_csv_str(&content)
+        } else {
+            Self::from_toml_str(&content)
+        }
+    }
+
+    pub fn from_toml_str(s: &str) -> Result<Self, LoaderError> {
+        let parsed: TomlSectorMap = toml::from_str(s).map_err(parse_error)?;
+        Ok(Self::from_entries(parsed.symbols.into_iter().map(
+            |(symbol, entry)| SymbolMeta {
+                symbol,
+                sector: entry.sector,
+                industry: entry.industry,
+                sector_etf: entry.sector_etf,
+            },
+        )))
+    }
+
+    pub fn from_csv_str(s: &str) -> Result<Self, LoaderError> {
+        let mut reader = csv::ReaderBuilder::new()
+            .trim(csv::Trim::All)
+            .flexible(true)
+            .from_reader(s.as_bytes());
+        let header: Vec<String> = reader
+            .headers()
+            .map_err(parse_error)?
+            .iter()
+            .map(|h| h.to_ascii_lowercase())
+            .collect();
+        if header.iter().all(String::is_empty) {
+            return Ok(Self::default());
+        }
+        let column = |name: &str| header.iter().position(|h| h == name);
+        let (Some(symbol_col), Some(sector_col)) = (column("symbol"), column("sector")) else {
+            return Err(parse_error("CSV header must include `symbol` and `sector`"));
+        };
+        let industry_col = column("industry");
+        let etf_col = column("sector_etf").or_else(|| column("etf"));
+
+        let mut entries = Vec::new();
+        for record in reader.records() {
+            let record = record.map_err(parse_error)?;
+            if record.iter().all(str::is_empty) {
+                continue;
+            }
+            let field = |col: Option<usize>| {
+                col.and_then(|c| record.get(c))
+                    .filter(|f| !f.is_empty())
+                    .map(str::to_string)
+            };
+            let (Some(symbol), Some(sector)) = (field(Some(symbol_col)), field(Some(sector_col)))
+            else {
+                let line = record.position().map_or(0, |p| p.line());
+                return Err(parse_error(format!(
+                    "line {line}: missing symbol or sector"
+                )));
+            };
+            entries.push(SymbolMeta {
+                symbol,
+                sector,
+                industry: field(industry_col),
+                sector_etf: field(etf_col),
+            });
+        }
+        Ok(Self::from_entries(entries))
+    }
+
+    /// Build a map, normalizing symbols to upper case and filling in the
+    /// default sector ETF where none is given.
+    pub fn from_entries(entries: impl IntoIterator<Item = SymbolMeta>) -> Self {
+        let symbols = entries
+            .into_iter()
+            .map(|mut meta| {
+                meta.symbol = meta.symbol.trim().to_ascii_uppercase();
+                if meta.sector_etf.is_none() {
+                    meta.sector_etf = sector::sector_etf(&meta.sector).map(str::to_string);
+                }
+                (meta.symbol.clone(), meta)
+            })
+            .collect();
+        Self { symbols }
+    }
+
+    pub fn get(&self, symbol: &str) -> Option<&SymbolMeta> {
+        self.symbols.get(&symbol.to_ascii_uppercase())
+    }
+
+    /// The sector ETF for a symbol, if the symbol is mapped and its sector has one.
+    pub fn sector_etf(&self, symbol: &str) -> Option<&str> {
+        self.get(symbol)?.sector_etf.as_deref()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &SymbolMeta> {
+        self.symbols.values()
+    }
+
+    pub fn len(&self) -> usize {
+        self.symbols.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty()
+    }
+}
+
+fn parse_error(e: impl std::fmt::Display) -> LoaderError {
+    LoaderError::Config(format!("Invalid sector map: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_toml_with_default_and_override_etf() {
+        let map = SectorMap::from_toml_str(
+            r#"
+            [symbols.AAPL]
+            sector = "Information Technology"
+            industry = "Technology Hardware, Storage & Peripherals"
+
+            [symbols.jpm]
+            sector = "Financials"
+            etf = "KBE"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.sector_etf("AAPL"), Some("XLK"));
+        assert_eq!(map.sector_etf("JPM"), Some("KBE"));
+        assert_eq!(
+            map.get("aapl").unwrap().industry.as_deref(),
+            Some("Technology Hardware, Storage & Peripherals")
+        );
+    }
+
+    #[test]
+    fn parses_csv_with_quoted_fields() {
+        let map = SectorMap::from_csv_str(
+            "symbol,sector,industry,sector_etf\n\
+             AAPL,Information Technology,\"Technology Hardware, Storage & Peripherals\",\n\
+             XOM,Energy,\"Oil, Gas & Consumable Fuels\",XOP\n\
+             \n\
+             \"MSFT\",Information Technology,\"Software\n(Systems)\",\n\
+             PLD,Real Estate,,\n",
+        )
+        .unwrap();
+
+        assert_eq!(map.len(), 4);
+        assert_eq!(map.sector_etf("AAPL"), Some("XLK"));
+        assert_eq!(map.sector_etf("XOM"), Some("XOP"));
+        assert_eq!(map.sector_etf("PLD"), Some("XLRE"));
+        assert_eq!(
+            map.get("XOM").unwrap().industry.as_deref(),
+            Some("Oil, Gas & Consumable Fuels")
+        );
+        assert_eq!(map.get("PLD").unwrap().industry, None);
+        assert_eq!(
+            map.get("MSFT").unwrap().industry.as_deref(),
+            Some("Software\n(Systems)")
+        );
+    }
+
+    #[test]
+    fn csv_requires_symbol_and_sector_columns() {
+        let err = SectorMap::from_csv_str("ticker,sector\nAAPL,Energy\n").unwrap_err();
+        assert!(matches!(err, LoaderError::Config(_)));
+
+        let err = SectorMap::from_csv_str("symbol,sector\nAAPL,\n").unwrap_err();
+        assert!(err.to_string().contains("line 2"));
+    }
+
+    #[test]
+    fn unknown_sector_has_no_etf() {
+        let map = SectorMap::from_entries([SymbolMeta {
+            symbol: "BTC".to_string(),
+            sector: "Crypto".to_string(),
+            industry: None,
+            sector_etf: None,
+        }]);
+        assert_eq!(map.sector_etf("BTC"), None);
+        assert!(map.get("BTC").is_some());
+    }
+}
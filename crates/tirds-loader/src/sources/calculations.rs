@@ -1,5 +1,4 @@
 use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
 
 use chrono::{Duration, Utc};
 use market_calculations::{CalculationOutput, CalculationRegistry, ParamValue, Pipeline};
@@ -7,12 +6,13 @@ use market_data_core::candle::Candle as MdCandle;
 use tirds_models::cache_schema::{key_patterns, CacheRow};
 use tracing;
 
-use crate::config::CalculationsConfig;
+use crate::config::{CalculationsConfig, IndicatorEntry, IndicatorParam, Timeframe};
 use crate::error::LoaderError;
-use crate::writer::SqliteWriter;
+use crate::sources::resample::resample;
+use crate::write_queue::CacheWriter;
 
 /// Convert a market-data `Candle` (Decimal prices) to a market-calculations `Candle` (f64 prices).
-fn convert_candle(candle: &MdCandle) -> market_calculations::Candle {
+pub(crate) fn convert_candle(candle: &MdCandle) -> market_calculations::Candle {
     use rust_decimal::prelude::ToPrimitive;
     market_calculations::Candle {
         timestamp: candle.timestamp.timestamp_millis(),
@@ -65,7 +65,129 @@ pub fn indicator_to_cache_row(
     }
 }
 
-/// Compute all configured indicators for a symbol's candles and return cache rows.
+/// An indicator entry resolved into what the calculation pipeline needs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedIndicator {
+    /// Output key name (`indicator:{name}:{symbol}`).
+    pub name: String,
+    pub calc_id: String,
+    pub params: HashMap<String, ParamValue>,
+    pub timeframe: Timeframe,
+}
+
+/// Resolve a configured indicator entry (shorthand or structured).
+pub fn resolve_indicator(entry: &IndicatorEntry) -> Result<ResolvedIndicator, LoaderError> {
+    match entry {
+        IndicatorEntry::Shorthand(spec) => {
+            let (base_spec, timeframe) = split_timeframe(spec).ok_or_else(|| {
+                LoaderError::Config(format!("Unknown timeframe in indicator spec: {spec}"))
+            })?;
+            let (calc_id, params) = parse_indicator_spec(base_spec);
+            Ok(ResolvedIndicator {
+                name: spec.clone(),
+                calc_id,
+                params,
+                timeframe,
+            })
+        }
+        IndicatorEntry::Spec(spec) => {
+            let name = match &spec.alias {
+                Some(alias) => alias.clone(),
+                None if spec.timeframe == Timeframe::FiveMinutes => spec.id.clone(),
+                None => format!("{}@{}", spec.id, spec.timeframe.as_str()),
+            };
+            let params = spec
+                .params
+                .iter()
+                .map(|(k, v)| (k.clone(), to_param_value(v)))
+                .collect();
+            Ok(ResolvedIndicator {
+                name,
+                calc_id: spec.id.clone(),
+                params,
+                timeframe: spec.timeframe,
+            })
+        }
+    }
+}
+
+fn to_param_value(param: &IndicatorParam) -> ParamValue {
+    match param {
+        IndicatorParam::Bool(b) => ParamValue::Bool(*b),
+        IndicatorParam::Integer(i) => ParamValue::Integer(*i),
+        IndicatorParam::Float(f) => ParamValue::Float(*f),
+        IndicatorParam::String(s) => ParamValue::String(s.clone()),
+    }
+}
+
+/// Resolve every configured indicator and check it against the calculation
+/// registry, then dry-run it on a synthetic series so bad parameters surface
+/// too. Called at startup so a typo fails fast instead of logging a warning
+/// every cycle. Also rejects two entries writing the same key.
+pub fn validate_indicators(
+    config: &CalculationsConfig,
+) -> Result<Vec<ResolvedIndicator>, LoaderError> {
+    let registry = CalculationRegistry::with_defaults();
+    let pipeline = Pipeline::new(&registry);
+    let mut resolved: Vec<ResolvedIndicator> = Vec::new();
+
+    for entry in &config.indicators {
+        let indicator = resolve_indicator(entry)?;
+        if !registry.contains(&indicator.calc_id) {
+            return Err(LoaderError::Config(format!(
+                "Unknown calculation '{}' for indicator '{}'",
+                indicator.calc_id, indicator.name
+            )));
+        }
+        if resolved.iter().any(|r| r.name == indicator.name) {
+            return Err(LoaderError::Config(format!(
+                "Duplicate indicator output key: {}",
+                indicator.name
+            )));
+        }
+        let candles = synthetic_candles(&indicator.params);
+        if let Err(e) = pipeline.run(&indicator.calc_id, &candles, &indicator.params) {
+            return Err(LoaderError::Config(format!(
+                "Invalid parameters for indicator '{}': {e:?}",
+                indicator.name
+            )));
+        }
+        resolved.push(indicator);
+    }
+
+    Ok(resolved)
+}
+
+/// A 5-minute series long enough to warm up an indicator with these
+/// parameters, for validation only.
+fn synthetic_candles(params: &HashMap<String, ParamValue>) -> Vec<market_calculations::Candle> {
+    let longest = params
+        .values()
+        .filter_map(|p| match p {
+            ParamValue::Integer(n) => usize::try_from(*n).ok(),
+            _ => None,
+        })
+        .max()
+        .unwrap_or(0)
+        .min(10_000);
+    (0..100 + 3 * longest)
+        .map(|i| {
+            let close = 100.0 + (i as f64 / 10.0).sin() * 5.0;
+            market_calculations::Candle {
+                timestamp: i as i64 * 300_000,
+                open: close - 0.5,
+                high: close + 1.0,
+                low: close - 1.0,
+                close,
+                volume: 1_000.0 + (i % 7) as f64 * 100.0,
+            }
+        })
+        .collect()
+}
+
+/// Compute all configured indicators for a symbol's 5-minute candles and return cache rows.
+///
+/// Each indicator runs on candles resampled to its timeframe.
 pub fn compute_indicators(
     symbol: &str,
     candles: &[MdCandle],
@@ -75,19 +197,30 @@ pub fn compute_indicators(
         return Vec::new();
     }
 
-    let calc_candles: Vec<market_calculations::Candle> =
-        candles.iter().map(convert_candle).collect();
     let registry = CalculationRegistry::with_defaults();
     let pipeline = Pipeline::new(&registry);
+    let mut by_timeframe: HashMap<Timeframe, Vec<market_calculations::Candle>> = HashMap::new();
     let mut rows = Vec::new();
 
-    for indicator_spec in &config.indicators {
-        let (calc_id, params) = parse_indicator_spec(indicator_spec);
+    for entry in &config.indicators {
+        let indicator = match resolve_indicator(entry) {
+            Ok(indicator) => indicator,
+            Err(e) => {
+                tracing::warn!(symbol, error = %e, "Skipping invalid indicator");
+                continue;
+            }
+        };
+        let calc_candles = by_timeframe.entry(indicator.timeframe).or_insert_with(|| {
+            resample(candles, indicator.timeframe)
+                .iter()
+                .map(convert_candle)
+                .collect()
+        });
 
-        match pipeline.run(&calc_id, &calc_candles, &params) {
+        match pipeline.run(&indicator.calc_id, calc_candles, &indicator.params) {
             Ok(output) => {
                 rows.push(indicator_to_cache_row(
-                    indicator_spec,
+                    &indicator.name,
                     symbol,
                     &output,
                     config.ttl_seconds,
@@ -96,7 +229,7 @@ pub fn compute_indicators(
             Err(e) => {
                 tracing::warn!(
                     symbol,
-                    indicator = indicator_spec,
+                    indicator = indicator.name,
                     error = ?e,
                     "Failed to compute indicator"
                 );
@@ -107,6 +240,15 @@ pub fn compute_indicators(
     rows
 }
 
+/// Split an optional `@{timeframe}` suffix off an indicator spec.
+/// Returns None if the suffix is not a known timeframe.
+fn split_timeframe(spec: &str) -> Option<(&str, Timeframe)> {
+    match spec.split_once('@') {
+        Some((base, tf)) => Timeframe::parse(tf).map(|tf| (base, tf)),
+        None => Some((spec, Timeframe::FiveMinutes)),
+    }
+}
+
 /// Parse an indicator spec like "sma_20" or "rsi_14" into (calc_id, params).
 /// For specs with a numeric suffix, extract the period parameter.
 /// Plain names like "sma" or "daily_profile" use default params.
@@ -133,11 +275,11 @@ fn parse_indicator_spec(spec: &str) -> (String, HashMap<String, ParamValue>) {
 }
 
 /// Run indicators for all symbols and write results.
-pub fn refresh_calculations(
+pub async fn refresh_calculations(
     symbols: &[String],
     candle_data: &HashMap<String, Vec<MdCandle>>,
     config: &CalculationsConfig,
-    writer: &Arc<Mutex<SqliteWriter>>,
+    writer: &CacheWriter,
 ) -> Result<usize, LoaderError> {
     let mut total_rows = 0;
 
@@ -145,12 +287,9 @@ pub fn refresh_calculations(
         if let Some(candles) = candle_data.get(symbol) {
             let rows = compute_indicators(symbol, candles, config);
             if !rows.is_empty() {
-                let mut w = writer
-                    .lock()
-                    .map_err(|e| LoaderError::Calculation(format!("Writer lock: {e}")))?;
-                w.upsert_batch(&rows)?;
                 total_rows += rows.len();
                 tracing::debug!(symbol, count = rows.len(), "Wrote indicator entries");
+                writer.upsert_batch(rows).await?;
             }
         }
     }
@@ -218,6 +357,137 @@ mod tests {
         assert!(params.is_empty());
     }
 
+    #[test]
+    fn split_timeframe_suffix() {
+        assert_eq!(
+            split_timeframe("rsi_14@1d"),
+            Some(("rsi_14", Timeframe::OneDay))
+        );
+        assert_eq!(
+            split_timeframe("rsi_14"),
+            Some(("rsi_14", Timeframe::FiveMinutes))
+        );
+        assert_eq!(split_timeframe("rsi_14@2d"), None);
+    }
+
+    #[test]
+    fn compute_indicators_on_resampled_timeframe() {
+        let candles = sample_md_candles(30);
+        let config = CalculationsConfig {
+            indicators: vec!["sma_2@15m".into(), "sma_2@3h".into()],
+            incremental: false,
+            ..CalculationsConfig::default()
+        };
+        let rows = compute_indicators("AAPL", &candles, &config);
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].key, "indicator:sma_2@15m:AAPL");
+    }
+
+    fn spec(id: &str, params: &[(&str, IndicatorParam)], timeframe: Timeframe) -> IndicatorEntry {
+        IndicatorEntry::Spec(crate::config::IndicatorSpec {
+            id: id.to_string(),
+            params: params
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.clone()))
+                .collect(),
+            timeframe,
+            alias: None,
+        })
+    }
+
+    #[test]
+    fn resolve_structured_spec_maps_params() {
+        let entry = spec(
+            "macd",
+            &[
+                ("fast", IndicatorParam::Integer(12)),
+                ("slow", IndicatorParam::Integer(26)),
+                ("adjust", IndicatorParam::Bool(true)),
+            ],
+            Timeframe::OneDay,
+        );
+        let resolved = resolve_indicator(&entry).unwrap();
+        assert_eq!(resolved.name, "macd@1d");
+        assert_eq!(resolved.calc_id, "macd");
+        assert_eq!(resolved.params.get("fast"), Some(&ParamValue::Integer(12)));
+        assert_eq!(resolved.params.get("adjust"), Some(&ParamValue::Bool(true)));
+        assert_eq!(resolved.timeframe, Timeframe::OneDay);
+    }
+
+    #[test]
+    fn resolve_structured_spec_uses_alias() {
+        let mut entry = spec("bollinger_bands", &[], Timeframe::FiveMinutes);
+        if let IndicatorEntry::Spec(ref mut s) = entry {
+            s.alias = Some("bb_wide".to_string());
+        }
+        assert_eq!(resolve_indicator(&entry).unwrap().name, "bb_wide");
+    }
+
+    #[test]
+    fn validate_rejects_unknown_calculation() {
+        let config = CalculationsConfig {
+            indicators: vec!["sma_20".into(), spec("not_a_calc", &[], Timeframe::OneDay)],
+            incremental: false,
+            ..CalculationsConfig::default()
+        };
+        let err = validate_indicators(&config).unwrap_err();
+        assert!(err.to_string().contains("not_a_calc"));
+    }
+
+    #[test]
+    fn validate_rejects_duplicate_keys() {
+        let config = CalculationsConfig {
+            indicators: vec!["macd@1d".into(), spec("macd", &[], Timeframe::OneDay)],
+            incremental: false,
+            ..CalculationsConfig::default()
+        };
+        let err = validate_indicators(&config).unwrap_err();
+        assert!(err.to_string().contains("Duplicate"));
+    }
+
+    #[test]
+    fn validate_rejects_unknown_timeframe() {
+        let config = CalculationsConfig {
+            indicators: vec!["rsi_14@2d".into()],
+            incremental: false,
+            ..CalculationsConfig::default()
+        };
+        assert!(validate_indicators(&config).is_err());
+    }
+
+    #[test]
+    fn validate_rejects_bad_params() {
+        let config = CalculationsConfig {
+            indicators: vec![spec(
+                "macd",
+                &[("fst", IndicatorParam::Integer(12))],
+                Timeframe::OneDay,
+            )],
+            incremental: false,
+            ..CalculationsConfig::default()
+        };
+        let err = validate_indicators(&config).unwrap_err();
+        assert!(err.to_string().contains("macd@1d"), "{err}");
+    }
+
+    #[test]
+    fn validate_accepts_shorthand_and_specs() {
+        let config = CalculationsConfig {
+            indicators: vec![
+                "sma_20".into(),
+                "daily_profile".into(),
+                spec(
+                    "stochastic",
+                    &[("k_period", IndicatorParam::Integer(14))],
+                    Timeframe::OneHour,
+                ),
+            ],
+            incremental: false,
+            ..CalculationsConfig::default()
+        };
+        assert_eq!(validate_indicators(&config).unwrap().len(), 3);
+    }
+
     #[test]
     fn indicator_to_row_single_series() {
         let output = CalculationOutput::single(vec![1.0, 2.0, 3.0]);
@@ -232,8 +502,9 @@ mod tests {
     fn compute_indicators_with_enough_candles() {
         let candles = sample_md_candles(30); // enough for SMA(20)
         let config = CalculationsConfig {
-            indicators: vec!["sma_20".to_string()],
-            ttl_seconds: 600,
+            indicators: vec!["sma_20".into()],
+            incremental: false,
+            ..CalculationsConfig::default()
         };
         let rows = compute_indicators("AAPL", &candles, &config);
         assert_eq!(rows.len(), 1);
@@ -244,8 +515,9 @@ mod tests {
     fn compute_indicators_insufficient_data_skips() {
         let candles = sample_md_candles(3); // not enough for SMA(20)
         let config = CalculationsConfig {
-            indicators: vec!["sma_20".to_string()],
-            ttl_seconds: 600,
+            indicators: vec!["sma_20".into()],
+            incremental: false,
+            ..CalculationsConfig::default()
         };
         let rows = compute_indicators("AAPL", &candles, &config);
         // Should either produce 0 rows (insufficient data error) or 1 row
@@ -0,0 +1,231 @@
+use std::collections::BTreeSet;
+
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+
+/// One-off full-day NYSE closures not covered by the holiday rules.
+const SPECIAL_CLOSURES: [(i32, u32, u32); 2] = [
+    (2018, 12, 5), // National day of mourning, George H.W. Bush
+    (2025, 1, 9),  // National day of mourning, Jimmy Carter
+];
+
+/// US equity (NYSE) trading calendar: weekends, the standard holidays with
+/// their observed dates, known special closures, plus configured extra closures.
+#[derive(Debug, Clone, Default)]
+pub struct ExchangeCalendar {
+    extra_closures: BTreeSet<NaiveDate>,
+}
+
+impl ExchangeCalendar {
+    pub fn new(extra_closures: impl IntoIterator<Item = NaiveDate>) -> Self {
+        Self {
+            extra_closures: extra_closures.into_iter().collect(),
+        }
+    }
+
+    pub fn is_trading_day(&self, date: NaiveDate) -> bool {
+        !matches!(date.weekday(), Weekday::Sat | Weekday::Sun)
+            && !is_holiday(date)
+            && !self.extra_closures.contains(&date)
+    }
+
+    /// Coalesce dates into runs of consecutive trading days (non-trading days
+    /// between them don't break a run), each at most `max_days` trading days long.
+    /// Non-trading dates in the input are skipped.
+    pub fn trading_ranges(
+        &self,
+        dates: &[NaiveDate],
+        max_days: usize,
+    ) -> Vec<(NaiveDate, NaiveDate)> {
+        let mut dates: Vec<NaiveDate> = dates
+            .iter()
+            .copied()
+            .filter(|d| self.is_trading_day(*d))
+            .collect();
+        dates.sort();
+        dates.dedup();
+
+        let max_days = max_days.max(1);
+        let mut ranges = Vec::new();
+        let mut run: Option<(NaiveDate, NaiveDate, usize)> = None;
+        for date in dates {
+            run = match run {
+                Some((start, end, len)) if len < max_days && self.next_trading_day(end) == date => {
+                    Some((start, date, len + 1))
+                }
+                Some((start, end, _)) => {
+                    ranges.push((start, end));
+                    Some((date, date, 1))
+                }
+                None => Some((date, date, 1)),
+            };
+        }
+        ranges.extend(run.map(|(start, end, _)| (start, end)));
+        ranges
+    }
+
+    /// The first trading day after `date`.
+    pub fn next_trading_day(&self, date: NaiveDate) -> NaiveDate {
+        let mut next = date + Duration::days(1);
+        while !self.is_trading_day(next) {
+            next += Duration::days(1);
+        }
+        next
+    }
+}
+
+/// NYSE full-day holiday (observed date) or special closure.
+pub fn is_holiday(date: NaiveDate) -> bool {
+    let year = date.year();
+    if SPECIAL_CLOSURES.contains(&(year, date.month(), date.day())) {
+        return true;
+    }
+    let ymd = |m: u32, d: u32| NaiveDate::from_ymd_opt(year, m, d);
+
+    // New Year's Day: Sunday moves to Monday; Saturday is not observed.
+    let new_year = ymd(1, 1)
+        .filter(|d| d.weekday() != Weekday::Sat)
+        .map(observed);
+    let mut holidays = vec![
+        new_year,
+        nth_weekday(year, 1, Weekday::Mon, 3), // Martin Luther King Jr. Day
+        nth_weekday(year, 2, Weekday::Mon, 3), // Washington's Birthday
+        easter(year).map(|d| d - Duration::days(2)), // Good Friday
+        last_weekday(year, 5, Weekday::Mon),   // Memorial Day
+        ymd(7, 4).map(observed),               // Independence Day
+        nth_weekday(year, 9, Weekday::Mon, 1), // Labor Day
+        nth_weekday(year, 11, Weekday::Thu, 4), // Thanksgiving
+        ymd(12, 25).map(observed),             // Christmas
+    ];
+    if year >= 2022 {
+        holidays.push(ymd(6, 19).map(observed)); // Juneteenth
+    }
+    holidays.contains(&Some(date))
+}
+
+/// Saturday holidays are observed the Friday before, Sunday holidays the Monday after.
+fn observed(date: NaiveDate) -> NaiveDate {
+    match date.weekday() {
+        Weekday::Sat => date - Duration::days(1),
+        Weekday::Sun => date + Duration::days(1),
+        _ => date,
+    }
+}
+
+fn nth_weekday(year: i32, month: u32, weekday: Weekday, n: u8) -> Option<NaiveDate> {
+    NaiveDate::from_weekday_of_month_opt(year, month, weekday, n)
+}
+
+fn last_weekday(year: i32, month: u32, weekday: Weekday) -> Option<NaiveDate> {
+    nth_weekday(year, month, weekday, 5).or_else(|| nth_weekday(year, month, weekday, 4))
+}
+
+/// Western (Gregorian) Easter Sunday.
+fn easter(year: i32) -> Option<NaiveDate> {
+    let a = year % 19;
+    let b = year / 100;
+    let c = year % 100;
+    let d = b / 4;
+    let e = b % 4;
+    let f = (b + 8) / 25;
+    let g = (b - f + 1) / 3;
+    let h = (19 * a + b - d - g + 15) % 30;
+    let i = c / 4;
+    let k = c % 4;
+    let l = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m = (a + 11 * h + 22 * l) / 451;
+    let month = (h + l - 7 * m + 114) / 31;
+    let day = (h + l - 7 * m + 114) % 31 + 1;
+    NaiveDate::from_ymd_opt(year, month as u32, day as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn nyse_holidays_2024() {
+        let holidays: Vec<NaiveDate> = date(2024, 1, 1)
+            .iter_days()
+            .take_while(|d| d.year() == 2024)
+            .filter(|d| is_holiday(*d))
+            .collect();
+        assert_eq!(
+            holidays,
+            vec![
+                date(2024, 1, 1),
+                date(2024, 1, 15),
+                date(2024, 2, 19),
+                date(2024, 3, 29),
+                date(2024, 5, 27),
+                date(2024, 6, 19),
+                date(2024, 7, 4),
+                date(2024, 9, 2),
+                date(2024, 11, 28),
+                date(2024, 12, 25),
+            ]
+        );
+    }
+
+    #[test]
+    fn observed_dates_and_special_closures() {
+        // Independence Day 2026 is a Saturday, observed Friday the 3rd.
+        assert!(is_holiday(date(2026, 7, 3)));
+        // Christmas 2022 is a Sunday, observed Monday the 26th.
+        assert!(is_holiday(date(2022, 12, 26)));
+        // New Year's Day 2022 is a Saturday and isn't observed on Dec 31.
+        assert!(!is_holiday(date(2021, 12, 31)));
+        // Juneteenth only from 2022.
+        assert!(!is_holiday(date(2021, 6, 18)));
+        assert!(is_holiday(date(2025, 1, 9)));
+    }
+
+    #[test]
+    fn trading_days_respect_extra_closures() {
+        let calendar = ExchangeCalendar::new([date(2025, 1, 14)]);
+        assert!(calendar.is_trading_day(date(2025, 1, 13)));
+        assert!(!calendar.is_trading_day(date(2025, 1, 14)));
+        assert!(!calendar.is_trading_day(date(2025, 1, 18)));
+        assert_eq!(
+            calendar.next_trading_day(date(2025, 1, 13)),
+            date(2025, 1, 15)
+        );
+    }
+
+    #[test]
+    fn ranges_bridge_weekends_and_holidays_but_not_gaps() {
+        let calendar = ExchangeCalendar::default();
+        let dates = [
+            date(2024, 1, 11),
+            date(2024, 1, 12),
+            date(2024, 1, 13), // Saturday: skipped
+            date(2024, 1, 16), // after the weekend and MLK day: same run
+            date(2024, 1, 18), // the 17th isn't requested: new run
+        ];
+        assert_eq!(
+            calendar.trading_ranges(&dates, 30),
+            vec![
+                (date(2024, 1, 11), date(2024, 1, 16)),
+                (date(2024, 1, 18), date(2024, 1, 18)),
+            ]
+        );
+    }
+
+    #[test]
+    fn ranges_split_into_chunks() {
+        let calendar = ExchangeCalendar::default();
+        let dates: Vec<NaiveDate> = date(2024, 2, 5).iter_days().take(12).collect();
+        let ranges = calendar.trading_ranges(&dates, 4);
+        assert_eq!(
+            ranges,
+            vec![
+                (date(2024, 2, 5), date(2024, 2, 8)),
+                (date(2024, 2, 9), date(2024, 2, 14)),
+                (date(2024, 2, 15), date(2024, 2, 16)),
+            ]
+        );
+    }
+}
@@ -0,0 +1,353 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Duration, Utc};
+use market_data_core::candle::Candle as MdCandle;
+use rust_decimal::prelude::ToPrimitive;
+use tirds_models::cache_schema::{key_patterns, CacheRow};
+use tracing;
+
+use crate::config::DerivedConfig;
+use crate::error::LoaderError;
+use crate::sources::resample::resample;
+use crate::write_queue::CacheWriter;
+
+/// Closes of two symbols on the bars where both have data, oldest first.
+fn aligned_closes(
+    candles: &[MdCandle],
+    other: &[MdCandle],
+    config: &DerivedConfig,
+) -> Vec<(f64, f64)> {
+    let other_closes: HashMap<DateTime<Utc>, f64> = resample(other, config.timeframe)
+        .iter()
+        .filter_map(|c| c.close.to_f64().map(|close| (c.timestamp, close)))
+        .collect();
+
+    resample(candles, config.timeframe)
+        .iter()
+        .filter_map(|c| {
+            let close = c.close.to_f64()?;
+            let other_close = other_closes.get(&c.timestamp)?;
+            Some((close, *other_close))
+        })
+        .filter(|(a, b)| *a > 0.0 && *b > 0.0)
+        .collect()
+}
+
+fn returns(pairs: &[(f64, f64)]) -> Vec<(f64, f64)> {
+    pairs
+        .windows(2)
+        .map(|w| (w[1].0 / w[0].0 - 1.0, w[1].1 / w[0].1 - 1.0))
+        .collect()
+}
+
+/// Ratio of the symbol's price to the comparison's, normalized to 1.0 at the
+/// first aligned bar. Rising means the symbol is outperforming.
+pub fn relative_strength(pairs: &[(f64, f64)]) -> Vec<f64> {
+    let Some(&(base_a, base_b)) = pairs.first() else {
+        return Vec::new();
+    };
+    pairs
+        .iter()
+        .map(|(a, b)| (a / base_a) / (b / base_b))
+        .collect()
+}
+
+/// Rolling beta and Pearson correlation of the symbol's returns against the
+/// comparison's, one value per full window. Windows where the comparison
+/// (or, for correlation, either side) has zero variance are skipped.
+pub fn rolling_beta_correlation(returns: &[(f64, f64)], window: usize) -> (Vec<f64>, Vec<f64>) {
+    let mut betas = Vec::new();
+    let mut correlations = Vec::new();
+    if window < 2 {
+        return (betas, correlations);
+    }
+
+    for w in returns.windows(window) {
+        let n = w.len() as f64;
+        let mean_a = w.iter().map(|(a, _)| a).sum::<f64>() / n;
+        let mean_b = w.iter().map(|(_, b)| b).sum::<f64>() / n;
+        let (mut cov, mut var_a, mut var_b) = (0.0, 0.0, 0.0);
+        for (a, b) in w {
+            cov += (a - mean_a) * (b - mean_b);
+            var_a += (a - mean_a).powi(2);
+            var_b += (b - mean_b).powi(2);
+        }
+        if var_b > 0.0 {
+            betas.push(cov / var_b);
+            if var_a > 0.0 {
+                correlations.push(cov / (var_a.sqrt() * var_b.sqrt()));
+            }
+        }
+    }
+
+    (betas, correlations)
+}
+
+fn metric_row(
+    name: &str,
+    symbol: &str,
+    versus: &str,
+    series: &[f64],
+    ttl_seconds: u64,
+) -> Option<CacheRow> {
+    let latest = *series.last()?;
+    let now = Utc::now();
+    let value = serde_json::json!({
+        "versus": versus,
+        "latest": latest,
+        "series": series,
+    });
+    Some(CacheRow {
+        key: key_patterns::indicator(name, symbol),
+        category: "indicator".to_string(),
+        value_json: serde_json::to_string(&value).unwrap_or_default(),
+        source: "tirds-loader:derived".to_string(),
+        symbol: Some(symbol.to_string()),
+        created_at: now.to_rfc3339(),
+        expires_at: (now + Duration::seconds(ttl_seconds as i64)).to_rfc3339(),
+        updated_at: now.to_rfc3339(),
+    })
+}
+
+/// Relative strength, beta and correlation rows for one comparison, named
+/// `rel_strength_{suffix}`, `beta_{suffix}` and `corr_{suffix}`.
+fn comparison_rows(
+    symbol: &str,
+    candles: &[MdCandle],
+    versus: &str,
+    versus_candles: &[MdCandle],
+    suffix: &str,
+    config: &DerivedConfig,
+    ttl_seconds: u64,
+) -> Vec<CacheRow> {
+    let pairs = aligned_closes(candles, versus_candles, config);
+    let (betas, correlations) = rolling_beta_correlation(&returns(&pairs), config.window);
+
+    [
+        (format!("rel_strength_{suffix}"), relative_strength(&pairs)),
+        (format!("beta_{suffix}"), betas),
+        (format!("corr_{suffix}"), correlations),
+    ]
+    .iter()
+    .filter_map(|(name, series)| metric_row(name, symbol, versus, series, ttl_seconds))
+    .collect()
+}
+
+/// Compute cross-symbol metrics for one symbol against the benchmark
+/// (`*_{benchmark}`, e.g. `rel_strength_spy`) and its sector ETF (`*_sector`).
+pub fn compute_derived_metrics(
+    symbol: &str,
+    candle_data: &HashMap<String, Vec<MdCandle>>,
+    config: &DerivedConfig,
+    ttl_seconds: u64,
+) -> Vec<CacheRow> {
+    let Some(candles) = candle_data.get(symbol).filter(|c| !c.is_empty()) else {
+        return Vec::new();
+    };
+    let mut rows = Vec::new();
+
+    if symbol != config.benchmark {
+        match candle_data.get(&config.benchmark) {
+            Some(benchmark) => rows.extend(comparison_rows(
+                symbol,
+                candles,
+                &config.benchmark,
+                benchmark,
+                &config.benchmark.to_lowercase(),
+                config,
+                ttl_seconds,
+            )),
+            None => {
+                tracing::debug!(symbol, benchmark = %config.benchmark, "Benchmark candles not loaded");
+            }
+        }
+    }
+
+    if let Some(etf) = config.sector_etfs.get(symbol).filter(|etf| *etf != symbol) {
+        match candle_data.get(etf) {
+            Some(etf_candles) => rows.extend(comparison_rows(
+                symbol,
+                candles,
+                etf,
+                etf_candles,
+                "sector",
+                config,
+                ttl_seconds,
+            )),
+            None => {
+                tracing::debug!(symbol, etf = %etf, "Sector ETF candles not loaded");
+            }
+        }
+    }
+
+    rows
+}
+
+/// Compute derived metrics for all symbols and write results.
+pub async fn refresh_derived(
+    symbols: &[String],
+    candle_data: &HashMap<String, Vec<MdCandle>>,
+    config: &DerivedConfig,
+    ttl_seconds: u64,
+    writer: &CacheWriter,
+) -> Result<usize, LoaderError> {
+    if !config.enabled {
+        return Ok(0);
+    }
+
+    let mut total_rows = 0;
+    for symbol in symbols {
+        let rows = compute_derived_metrics(symbol, candle_data, config, ttl_seconds);
+        if !rows.is_empty() {
+            total_rows += rows.len();
+            writer.upsert_batch(rows).await?;
+        }
+    }
+
+    Ok(total_rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use rust_decimal::Decimal;
+
+    fn candles(closes: &[f64]) -> Vec<MdCandle> {
+        let base = Utc.with_ymd_and_hms(2024, 1, 16, 14, 30, 0).unwrap();
+        closes
+            .iter()
+            .enumerate()
+            .map(|(i, close)| {
+                let price = Decimal::try_from(*close).unwrap();
+                MdCandle {
+                    timestamp: base + Duration::minutes(i as i64 * 5),
+                    open: price,
+                    high: price,
+                    low: price,
+                    close: price,
+                    volume: 1,
+                }
+            })
+            .collect()
+    }
+
+    fn config(window: usize) -> DerivedConfig {
+        DerivedConfig {
+            window,
+            ..DerivedConfig::default()
+        }
+    }
+
+    #[test]
+    fn relative_strength_tracks_outperformance() {
+        let rs = relative_strength(&[(100.0, 50.0), (110.0, 50.0), (110.0, 55.0)]);
+        assert_eq!(rs.len(), 3);
+        assert!((rs[0] - 1.0).abs() < 1e-12);
+        assert!((rs[1] - 1.1).abs() < 1e-12);
+        assert!((rs[2] - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn beta_of_leveraged_series_is_its_leverage() {
+        // Symbol returns are exactly twice the benchmark's
+        let rets: Vec<(f64, f64)> = [0.01, -0.02, 0.015, 0.005, -0.01]
+            .iter()
+            .map(|r| (2.0 * r, *r))
+            .collect();
+        let (betas, corrs) = rolling_beta_correlation(&rets, 5);
+        assert_eq!(betas.len(), 1);
+        assert!((betas[0] - 2.0).abs() < 1e-9);
+        assert!((corrs[0] - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn flat_benchmark_yields_no_beta() {
+        let rets = vec![(0.01, 0.0), (0.02, 0.0), (-0.01, 0.0)];
+        let (betas, corrs) = rolling_beta_correlation(&rets, 3);
+        assert!(betas.is_empty());
+        assert!(corrs.is_empty());
+    }
+
+    #[test]
+    fn aligned_closes_skip_missing_bars() {
+        let a = candles(&[10.0, 11.0, 12.0]);
+        let mut b = candles(&[20.0, 21.0, 22.0]);
+        b.remove(1);
+        let pairs = aligned_closes(&a, &b, &config(2));
+        assert_eq!(pairs, vec![(10.0, 20.0), (12.0, 22.0)]);
+    }
+
+    #[test]
+    fn computes_benchmark_and_sector_metrics() {
+        let mut data = HashMap::new();
+        data.insert(
+            "AAPL".to_string(),
+            candles(&[100.0, 102.0, 101.0, 104.0, 106.0]),
+        );
+        data.insert(
+            "SPY".to_string(),
+            candles(&[400.0, 401.0, 402.0, 401.0, 404.0]),
+        );
+        data.insert(
+            "XLK".to_string(),
+            candles(&[200.0, 202.0, 201.0, 203.0, 205.0]),
+        );
+        let mut config = config(3);
+        config
+            .sector_etfs
+            .insert("AAPL".to_string(), "XLK".to_string());
+
+        let rows = compute_derived_metrics("AAPL", &data, &config, 600);
+        let keys: Vec<&str> = rows.iter().map(|r| r.key.as_str()).collect();
+        assert_eq!(
+            keys,
+            vec![
+                "indicator:rel_strength_spy:AAPL",
+                "indicator:beta_spy:AAPL",
+                "indicator:corr_spy:AAPL",
+                "indicator:rel_strength_sector:AAPL",
+                "indicator:beta_sector:AAPL",
+                "indicator:corr_sector:AAPL",
+            ]
+        );
+
+        let value: serde_json::Value = serde_json::from_str(&rows[3].value_json).unwrap();
+        assert_eq!(value["versus"], "XLK");
+        assert_eq!(value["series"].as_array().unwrap().len(), 5);
+    }
+
+    #[test]
+    fn benchmark_itself_and_missing_data_produce_nothing() {
+        let mut data = HashMap::new();
+        data.insert("SPY".to_string(), candles(&[400.0, 401.0, 402.0]));
+        assert!(compute_derived_metrics("SPY", &data, &config(2), 600).is_empty());
+        assert!(compute_derived_metrics("AAPL", &data, &config(2), 600).is_empty());
+    }
+
+    #[tokio::test]
+    async fn refresh_respects_disabled() {
+        let writer = CacheWriter::open_in_memory().unwrap();
+        let mut data = HashMap::new();
+        data.insert("AAPL".to_string(), candles(&[100.0, 101.0, 102.0]));
+        data.insert("SPY".to_string(), candles(&[400.0, 402.0, 401.0]));
+        let symbols = vec!["AAPL".to_string()];
+
+        let disabled = DerivedConfig {
+            enabled: false,
+            ..config(2)
+        };
+        assert_eq!(
+            refresh_derived(&symbols, &data, &disabled, 600, &writer)
+                .await
+                .unwrap(),
+            0
+        );
+        assert_eq!(
+            refresh_derived(&symbols, &data, &config(2), 600, &writer)
+                .await
+                .unwrap(),
+            3
+        );
+    }
+}
@@ -0,0 +1,728 @@
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::America::New_York;
+use chrono_tz::Tz;
+use market_data_core::candle::Candle;
+use market_data_providers::error::ProviderError;
+use market_data_providers::provider::CandleProvider;
+use parquet::record::Field;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+
+use crate::config::{ColumnMapping, FilesProviderConfig};
+use crate::error::LoaderError;
+
+/// Reads candles from local CSV, NDJSON and Parquet files so the loader can run
+/// without network access. Days are grouped by New York trading date. Clones
+/// share the cache of the last read.
+#[derive(Clone)]
+pub struct FilesProvider {
+    root: PathBuf,
+    columns: ColumnMapping,
+    timestamp_format: String,
+    timezone: Tz,
+    /// The last symbol read, so the range requests of one fill parse its
+    /// files once.
+    last_read: Arc<Mutex<Option<SymbolRead>>>,
+}
+
+struct SymbolRead {
+    symbol: String,
+    files: Vec<FileStamp>,
+    candles: Arc<Vec<Candle>>,
+}
+
+/// Path, modification time and size: a file is parsed again when any changes.
+type FileStamp = (PathBuf, Option<SystemTime>, u64);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum FileFormat {
+    Csv,
+    Ndjson,
+    Parquet,
+}
+
+impl FileFormat {
+    fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+        match ext.as_str() {
+            "csv" => Some(Self::Csv),
+            "ndjson" | "jsonl" => Some(Self::Ndjson),
+            "parquet" => Some(Self::Parquet),
+            _ => None,
+        }
+    }
+}
+
+/// A cell before conversion to a candle field.
+enum Value {
+    Text(String),
+    Int(i64),
+    Float(f64),
+    /// Native decimal (Parquet).
+    Decimal(Decimal),
+    /// Native timestamp (Parquet), in milliseconds since the epoch.
+    Millis(i64),
+}
+
+impl FilesProvider {
+    pub fn new(config: &FilesProviderConfig) -> Result<Self, LoaderError> {
+        let timezone = Tz::from_str(&config.timezone).map_err(|e| {
+            LoaderError::Config(format!("files provider timezone {}: {e}", config.timezone))
+        })?;
+        Ok(Self {
+            root: PathBuf::from(&config.path),
+            columns: config.columns.clone(),
+            timestamp_format: config.timestamp_format.clone(),
+            timezone,
+            last_read: Arc::new(Mutex::new(None)),
+        })
+    }
+
+    /// `{root}/{SYMBOL}.{ext}` plus every supported file in `{root}/{SYMBOL}/`, sorted by path.
+    fn files_for(&self, symbol: &str) -> Vec<(PathBuf, FileFormat)> {
+        let mut files = Vec::new();
+        if let Ok(entries) = std::fs::read_dir(&self.root) {
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if path.is_file() && path.file_stem().and_then(|s| s.to_str()) == Some(symbol) {
+                    files.extend(FileFormat::from_path(&path).map(|f| (path, f)));
+                }
+            }
+        }
+        if let Ok(entries) = std::fs::read_dir(self.root.join(symbol)) {
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if path.is_file() {
+                    files.extend(FileFormat::from_path(&path).map(|f| (path, f)));
+                }
+            }
+        }
+        files.sort();
+        files
+    }
+
+    /// All candles for a symbol across its files, sorted by timestamp. When
+    /// files overlap, the bar from the later file (by path) wins.
+    pub fn read_symbol(&self, symbol: &str) -> Result<Vec<Candle>, LoaderError> {
+        self.read_files(&self.files_for(symbol))
+    }
+
+    fn read_files(&self, files: &[(PathBuf, FileFormat)]) -> Result<Vec<Candle>, LoaderError> {
+        let mut by_timestamp = BTreeMap::new();
+        for (path, format) in files {
+            let candles = match format {
+                FileFormat::Csv => self.read_csv(path),
+                FileFormat::Ndjson => self.read_ndjson(path),
+                FileFormat::Parquet => self.read_parquet(path),
+            }
+            .map_err(|e| LoaderError::Provider(format!("{}: {e}", path.display())))?;
+            by_timestamp.extend(candles.into_iter().map(|c| (c.timestamp, c)));
+        }
+        Ok(by_timestamp.into_values().collect())
+    }
+
+    /// Like [`FilesProvider::read_symbol`], reusing the previous call's
+    /// candles when it was for the same symbol and its files haven't changed.
+    fn read_symbol_cached(&self, symbol: &str) -> Result<Arc<Vec<Candle>>, LoaderError> {
+        let files = self.files_for(symbol);
+        let stamps: Vec<FileStamp> = files
+            .iter()
+            .map(|(path, _)| {
+                let meta = std::fs::metadata(path).ok();
+                let modified = meta.as_ref().and_then(|m| m.modified().ok());
+                (path.clone(), modified, meta.map_or(0, |m| m.len()))
+            })
+            .collect();
+
+        let mut last = self.last_read.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(read) = last
+            .as_ref()
+            .filter(|r| r.symbol == symbol && r.files == stamps)
+        {
+            return Ok(read.candles.clone());
+        }
+        let candles = Arc::new(self.read_files(&files)?);
+        *last = Some(SymbolRead {
+            symbol: symbol.to_string(),
+            files: stamps,
+            candles: candles.clone(),
+        });
+        Ok(candles)
+    }
+
+    fn read_csv(&self, path: &Path) -> Result<Vec<Candle>, String> {
+        let mut reader = csv::ReaderBuilder::new()
+            .trim(csv::Trim::All)
+            .from_path(path)
+            .map_err(|e| e.to_string())?;
+        let headers = reader.headers().map_err(|e| e.to_string())?.clone();
+        let index = |name: &str| {
+            headers
+                .iter()
+                .position(|h| h.eq_ignore_ascii_case(name))
+                .ok_or_else(|| format!("missing column {name}"))
+        };
+        let c = &self.columns;
+        let columns = [
+            index(&c.timestamp)?,
+            index(&c.open)?,
+            index(&c.high)?,
+            index(&c.low)?,
+            index(&c.close)?,
+            index(&c.volume)?,
+        ];
+
+        let mut candles = Vec::new();
+        for (line, record) in reader.records().enumerate() {
+            let record = record.map_err(|e| e.to_string())?;
+            let cell =
+                |i: usize| Value::Text(record.get(columns[i]).unwrap_or_default().to_string());
+            let candle = self
+                .candle([cell(0), cell(1), cell(2), cell(3), cell(4), cell(5)])
+                .map_err(|e| format!("row {}: {e}", line + 1))?;
+            candles.push(candle);
+        }
+        Ok(candles)
+    }
+
+    fn read_ndjson(&self, path: &Path) -> Result<Vec<Candle>, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let mut candles = Vec::new();
+        for (line, json) in text.lines().enumerate() {
+            if json.trim().is_empty() {
+                continue;
+            }
+            let object: serde_json::Value =
+                serde_json::from_str(json).map_err(|e| format!("line {}: {e}", line + 1))?;
+            let field = |name: &str| -> Result<Value, String> {
+                match object.get(name) {
+                    Some(serde_json::Value::String(s)) => Ok(Value::Text(s.clone())),
+                    Some(serde_json::Value::Number(n)) => Ok(n
+                        .as_i64()
+                        .map(Value::Int)
+                        .unwrap_or_else(|| Value::Float(n.as_f64().unwrap_or(f64::NAN)))),
+                    _ => Err(format!("missing field {name}")),
+                }
+            };
+            let c = &self.columns;
+            let candle = (|| {
+                self.candle([
+                    field(&c.timestamp)?,
+                    field(&c.open)?,
+                    field(&c.high)?,
+                    field(&c.low)?,
+                    field(&c.close)?,
+                    field(&c.volume)?,
+                ])
+            })()
+            .map_err(|e| format!("line {}: {e}", line + 1))?;
+            candles.push(candle);
+        }
+        Ok(candles)
+    }
+
+    fn read_parquet(&self, path: &Path) -> Result<Vec<Candle>, String> {
+        let file = File::open(path).map_err(|e| e.to_string())?;
+        let reader = parquet::file::serialized_reader::SerializedFileReader::new(file)
+            .map_err(|e| e.to_string())?;
+        let c = &self.columns;
+        let names = [&c.timestamp, &c.open, &c.high, &c.low, &c.close, &c.volume];
+
+        let mut candles = Vec::new();
+        for (line, row) in reader.into_iter().enumerate() {
+            let row = row.map_err(|e| e.to_string())?;
+            let mut values: [Option<Value>; 6] = Default::default();
+            for (name, field) in row.get_column_iter() {
+                if let Some(i) = names.iter().position(|n| n.eq_ignore_ascii_case(name)) {
+                    values[i] = Some(parquet_value(field).map_err(|e| format!("{name}: {e}"))?);
+                }
+            }
+            let [ts, open, high, low, close, volume] = values;
+            let missing = |i: usize| format!("row {}: missing column {}", line + 1, names[i]);
+            let candle = self
+                .candle([
+                    ts.ok_or_else(|| missing(0))?,
+                    open.ok_or_else(|| missing(1))?,
+                    high.ok_or_else(|| missing(2))?,
+                    low.ok_or_else(|| missing(3))?,
+                    close.ok_or_else(|| missing(4))?,
+                    volume.ok_or_else(|| missing(5))?,
+                ])
+                .map_err(|e| format!("row {}: {e}", line + 1))?;
+            candles.push(candle);
+        }
+        Ok(candles)
+    }
+
+    /// Build a candle from (timestamp, open, high, low, close, volume) cells.
+    fn candle(&self, [ts, open, high, low, close, volume]: [Value; 6]) -> Result<Candle, String> {
+        Ok(Candle {
+            timestamp: self.timestamp(ts)?,
+            open: price(open)?,
+            high: price(high)?,
+            low: price(low)?,
+            close: price(close)?,
+            volume: volume_of(volume)?,
+        })
+    }
+
+    fn timestamp(&self, value: Value) -> Result<DateTime<Utc>, String> {
+        let invalid = |v: &dyn std::fmt::Display| format!("invalid timestamp {v}");
+        let epoch = |n: i64| match self.timestamp_format.as_str() {
+            "unix" => DateTime::from_timestamp(n, 0),
+            "unix_ms" => DateTime::from_timestamp_millis(n),
+            _ => None,
+        };
+        match value {
+            Value::Millis(ms) => DateTime::from_timestamp_millis(ms).ok_or_else(|| invalid(&ms)),
+            Value::Int(n) => epoch(n).ok_or_else(|| invalid(&n)),
+            Value::Float(f) => epoch(f as i64).ok_or_else(|| invalid(&f)),
+            Value::Decimal(d) => d
+                .trunc()
+                .to_i64()
+                .and_then(epoch)
+                .ok_or_else(|| invalid(&d)),
+            Value::Text(text) => match self.timestamp_format.as_str() {
+                "rfc3339" => DateTime::parse_from_rfc3339(&text)
+                    .map(|t| t.with_timezone(&Utc))
+                    .map_err(|_| invalid(&text)),
+                "unix" | "unix_ms" => text
+                    .parse::<i64>()
+                    .ok()
+                    .and_then(epoch)
+                    .ok_or_else(|| invalid(&text)),
+                pattern => {
+                    if let Ok(t) = DateTime::parse_from_str(&text, pattern) {
+                        return Ok(t.with_timezone(&Utc));
+                    }
+                    let naive = NaiveDateTime::parse_from_str(&text, pattern)
+                        .or_else(|_| {
+                            NaiveDate::parse_from_str(&text, pattern)
+                                .map(|d| d.and_hms_opt(0, 0, 0).unwrap_or_default())
+                        })
+                        .map_err(|_| invalid(&text))?;
+                    self.timezone
+                        .from_local_datetime(&naive)
+                        .earliest()
+                        .map(|t| t.with_timezone(&Utc))
+                        .ok_or_else(|| invalid(&text))
+                }
+            },
+        }
+    }
+}
+
+fn parquet_value(field: &Field) -> Result<Value, String> {
+    Ok(match field {
+        Field::Str(s) => Value::Text(s.clone()),
+        Field::Byte(n) => Value::Int(*n as i64),
+        Field::Short(n) => Value::Int(*n as i64),
+        Field::Int(n) => Value::Int(*n as i64),
+        Field::Long(n) => Value::Int(*n),
+        Field::UByte(n) => Value::Int(*n as i64),
+        Field::UShort(n) => Value::Int(*n as i64),
+        Field::UInt(n) => Value::Int(*n as i64),
+        Field::ULong(n) => Value::Int(i64::try_from(*n).map_err(|e| e.to_string())?),
+        Field::Float(f) => Value::Float(*f as f64),
+        Field::Double(f) => Value::Float(*f),
+        Field::TimestampMillis(ms) => Value::Millis(*ms),
+        Field::TimestampMicros(us) => Value::Millis(us / 1000),
+        Field::Decimal(d) => Value::Decimal(parquet_decimal(d)?),
+        other => return Err(format!("unsupported value {other}")),
+    })
+}
+
+/// Convert a Parquet decimal (big-endian two's complement unscaled value).
+fn parquet_decimal(decimal: &parquet::data_type::Decimal) -> Result<Decimal, String> {
+    let bytes = decimal.data();
+    if bytes.is_empty() || bytes.len() > 16 {
+        return Err(format!("unsupported decimal width {}", bytes.len()));
+    }
+    let fill = if bytes[0] & 0x80 != 0 { 0xff } else { 0 };
+    let mut wide = [fill; 16];
+    wide[16 - bytes.len()..].copy_from_slice(bytes);
+    let unscaled = i128::from_be_bytes(wide);
+    let scale = u32::try_from(decimal.scale()).map_err(|_| "negative decimal scale".to_string())?;
+    Decimal::try_from_i128_with_scale(unscaled, scale).map_err(|e| e.to_string())
+}
+
+fn price(value: Value) -> Result<Decimal, String> {
+    match value {
+        Value::Text(text) => Decimal::from_str(&text)
+            .or_else(|_| Decimal::from_scientific(&text))
+            .map_err(|_| format!("invalid price {text}")),
+        Value::Int(n) => Ok(Decimal::from(n)),
+        Value::Float(f) => Decimal::try_from(f).map_err(|_| format!("invalid price {f}")),
+        Value::Decimal(d) => Ok(d),
+        Value::Millis(_) => Err("timestamp where a price was expected".to_string()),
+    }
+}
+
+fn volume_of(value: Value) -> Result<u64, String> {
+    let whole = |f: f64| (f.is_finite() && f >= 0.0).then_some(f.round() as u64);
+    match value {
+        Value::Text(text) => text
+            .parse::<u64>()
+            .ok()
+            .or_else(|| text.parse::<f64>().ok().and_then(whole))
+            .ok_or_else(|| format!("invalid volume {text}")),
+        Value::Int(n) => u64::try_from(n).map_err(|_| format!("invalid volume {n}")),
+        Value::Float(f) => whole(f).ok_or_else(|| format!("invalid volume {f}")),
+        Value::Decimal(d) => d
+            .round()
+            .to_u64()
+            .ok_or_else(|| format!("invalid volume {d}")),
+        Value::Millis(_) => Err("timestamp where a volume was expected".to_string()),
+    }
+}
+
+#[async_trait]
+impl CandleProvider for FilesProvider {
+    fn name(&self) -> &str {
+        "files"
+    }
+
+    async fn fetch_candles(
+        &self,
+        symbol: &str,
+        date: NaiveDate,
+    ) -> Result<Vec<Candle>, ProviderError> {
+        Ok(self
+            .fetch_candles_range(symbol, date, date)
+            .await?
+            .into_iter()
+            .flat_map(|(_, candles)| candles)
+            .collect())
+    }
+
+    /// Returns the days in range that have candles. The symbol's files are
+    /// parsed once across consecutive calls until they change.
+    async fn fetch_candles_range(
+        &self,
+        symbol: &str,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<Vec<(NaiveDate, Vec<Candle>)>, ProviderError> {
+        // Listing, stat and parsing are blocking file IO
+        let provider = self.clone();
+        let key = symbol.to_string();
+        let candles = tokio::task::spawn_blocking(move || provider.read_symbol_cached(&key))
+            .await
+            .map_err(|e| ProviderError::Config(format!("file read task failed: {e}")))?
+            .map_err(|e| ProviderError::Config(e.to_string()))?;
+        let mut days: BTreeMap<NaiveDate, Vec<Candle>> = BTreeMap::new();
+        for candle in candles.iter() {
+            let date = candle.timestamp.with_timezone(&New_York).date_naive();
+            if date >= start && date <= end {
+                days.entry(date).or_default().push(candle.clone());
+            }
+        }
+        Ok(days.into_iter().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parquet::data_type::{ByteArray, ByteArrayType, DoubleType, Int32Type, Int64Type};
+    use parquet::file::properties::WriterProperties;
+    use parquet::file::writer::SerializedFileWriter;
+    use rust_decimal_macros::dec;
+    use std::sync::Arc;
+
+    fn provider(dir: &Path, config: impl FnOnce(&mut FilesProviderConfig)) -> FilesProvider {
+        let mut files = FilesProviderConfig::new(dir.to_str().unwrap());
+        config(&mut files);
+        FilesProvider::new(&files).unwrap()
+    }
+
+    fn day(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 1, d).unwrap()
+    }
+
+    #[tokio::test]
+    async fn reads_csv_grouped_by_trading_day() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("AAPL.csv"),
+            "timestamp,open,high,low,close,volume\n\
+             2025-01-13T14:30:00Z,150.00,151.50,149.50,151.00,100000\n\
+             2025-01-13T14:35:00Z,151.00,152.00,150.50,151.75,85000\n\
+             2025-01-14T14:30:00Z,152.00,153.00,151.00,152.50,90000\n",
+        )
+        .unwrap();
+        let files = provider(dir.path(), |_| {});
+
+        let days = files
+            .fetch_candles_range("AAPL", day(13), day(14))
+            .await
+            .unwrap();
+        assert_eq!(days.len(), 2);
+        assert_eq!(days[0].0, day(13));
+        assert_eq!(days[0].1.len(), 2);
+        assert_eq!(days[0].1[1].close, dec!(151.75));
+        assert_eq!(days[1].1[0].volume, 90_000);
+
+        let single = files.fetch_candles("AAPL", day(14)).await.unwrap();
+        assert_eq!(single.len(), 1);
+        assert!(files
+            .fetch_candles("MSFT", day(14))
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn range_requests_reuse_parsed_files_until_they_change() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("AAPL.csv");
+        let csv = "timestamp,open,high,low,close,volume\n\
+                   2025-01-13T14:30:00Z,150.00,151.50,149.50,151.00,100000\n\
+                   2025-01-14T14:30:00Z,152.00,153.00,151.00,152.50,90000\n";
+        std::fs::write(&path, csv).unwrap();
+        let files = provider(dir.path(), |_| {});
+        assert_eq!(
+            files
+                .fetch_candles_range("AAPL", day(13), day(13))
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+
+        // Same size and modification time: the parsed candles are reused
+        let modified = std::fs::metadata(&path).unwrap().modified().unwrap();
+        std::fs::write(&path, csv.replace('1', "x")).unwrap();
+        File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+        let days = files
+            .fetch_candles_range("AAPL", day(14), day(14))
+            .await
+            .unwrap();
+        assert_eq!(days[0].1[0].close, dec!(152.50));
+
+        // A changed file is parsed again
+        std::fs::write(
+            &path,
+            format!("{csv}2025-01-15T14:30:00Z,153,154,152,153.5,1000\n"),
+        )
+        .unwrap();
+        let days = files
+            .fetch_candles_range("AAPL", day(13), day(15))
+            .await
+            .unwrap();
+        assert_eq!(days.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn maps_columns_and_local_timestamps() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("SPY")).unwrap();
+        std::fs::write(
+            dir.path().join("SPY").join("2025-01.csv"),
+            "Date,O,H,L,C,Vol\n2025-01-13 09:30,580,581,579,580.5,1.2e6\n",
+        )
+        .unwrap();
+        let files = provider(dir.path(), |f| {
+            f.columns = ColumnMapping {
+                timestamp: "Date".to_string(),
+                open: "O".to_string(),
+                high: "H".to_string(),
+                low: "L".to_string(),
+                close: "C".to_string(),
+                volume: "Vol".to_string(),
+            };
+            f.timestamp_format = "%Y-%m-%d %H:%M".to_string();
+            f.timezone = "America/New_York".to_string();
+        });
+
+        let candles = files.read_symbol("SPY").unwrap();
+        assert_eq!(
+            candles[0].timestamp,
+            Utc.with_ymd_and_hms(2025, 1, 13, 14, 30, 0).unwrap()
+        );
+        assert_eq!(candles[0].volume, 1_200_000);
+    }
+
+    #[test]
+    fn reads_ndjson_with_numeric_fields_and_unix_ms() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("QQQ.ndjson"),
+            "{\"timestamp\": 1736778600000, \"open\": 510.25, \"high\": 511, \"low\": \"509.5\", \"close\": 510.75, \"volume\": 5000}\n\n",
+        )
+        .unwrap();
+        let files = provider(dir.path(), |f| f.timestamp_format = "unix_ms".to_string());
+
+        let candles = files.read_symbol("QQQ").unwrap();
+        assert_eq!(candles.len(), 1);
+        assert_eq!(
+            candles[0].timestamp,
+            Utc.with_ymd_and_hms(2025, 1, 13, 14, 30, 0).unwrap()
+        );
+        assert_eq!(candles[0].open, dec!(510.25));
+        assert_eq!(candles[0].low, dec!(509.5));
+    }
+
+    #[test]
+    fn reads_parquet() {
+        let dir = tempfile::tempdir().unwrap();
+        let schema = Arc::new(
+            parquet::schema::parser::parse_message_type(
+                "message candles {
+                    required binary timestamp (UTF8);
+                    required double open;
+                    required double high;
+                    required double low;
+                    required double close;
+                    required int64 volume;
+                }",
+            )
+            .unwrap(),
+        );
+        let file = File::create(dir.path().join("IWM.parquet")).unwrap();
+        let mut writer =
+            SerializedFileWriter::new(file, schema, Arc::new(WriterProperties::builder().build()))
+                .unwrap();
+        let mut group = writer.next_row_group().unwrap();
+        let mut column = group.next_column().unwrap().unwrap();
+        column
+            .typed::<ByteArrayType>()
+            .write_batch(&[ByteArray::from("2025-01-13T14:30:00Z")], None, None)
+            .unwrap();
+        column.close().unwrap();
+        for value in [220.0, 221.0, 219.5, 220.5] {
+            let mut column = group.next_column().unwrap().unwrap();
+            column
+                .typed::<DoubleType>()
+                .write_batch(&[value], None, None)
+                .unwrap();
+            column.close().unwrap();
+        }
+        let mut column = group.next_column().unwrap().unwrap();
+        column
+            .typed::<Int64Type>()
+            .write_batch(&[7_000], None, None)
+            .unwrap();
+        column.close().unwrap();
+        group.close().unwrap();
+        writer.close().unwrap();
+
+        let candles = provider(dir.path(), |_| {}).read_symbol("IWM").unwrap();
+        assert_eq!(candles.len(), 1);
+        assert_eq!(candles[0].high, dec!(221));
+        assert_eq!(candles[0].close, dec!(220.5));
+        assert_eq!(candles[0].volume, 7_000);
+    }
+
+    #[test]
+    fn reads_parquet_decimal_columns() {
+        let dir = tempfile::tempdir().unwrap();
+        let schema = Arc::new(
+            parquet::schema::parser::parse_message_type(
+                "message candles {
+                    required binary timestamp (UTF8);
+                    required int64 open (DECIMAL(12,4));
+                    required int64 high (DECIMAL(12,4));
+                    required int64 low (DECIMAL(12,4));
+                    required int64 close (DECIMAL(12,4));
+                    required int32 volume (DECIMAL(9,0));
+                }",
+            )
+            .unwrap(),
+        );
+        let file = File::create(dir.path().join("IWM.parquet")).unwrap();
+        let mut writer =
+            SerializedFileWriter::new(file, schema, Arc::new(WriterProperties::builder().build()))
+                .unwrap();
+        let mut group = writer.next_row_group().unwrap();
+        let mut column = group.next_column().unwrap().unwrap();
+        column
+            .typed::<ByteArrayType>()
+            .write_batch(&[ByteArray::from("2025-01-13T14:30:00Z")], None, None)
+            .unwrap();
+        column.close().unwrap();
+        for value in [2_200_125, 2_210_000, 2_195_000, 2_205_050] {
+            let mut column = group.next_column().unwrap().unwrap();
+            column
+                .typed::<Int64Type>()
+                .write_batch(&[value], None, None)
+                .unwrap();
+            column.close().unwrap();
+        }
+        let mut column = group.next_column().unwrap().unwrap();
+        column
+            .typed::<Int32Type>()
+            .write_batch(&[7_000], None, None)
+            .unwrap();
+        column.close().unwrap();
+        group.close().unwrap();
+        writer.close().unwrap();
+
+        let candles = provider(dir.path(), |_| {}).read_symbol("IWM").unwrap();
+        assert_eq!(candles[0].open, dec!(220.0125));
+        assert_eq!(candles[0].close, dec!(220.505));
+        assert_eq!(candles[0].volume, 7_000);
+    }
+
+    #[test]
+    fn parquet_decimal_sign_extends() {
+        let negative =
+            parquet::data_type::Decimal::from_bytes(ByteArray::from(vec![0xff, 0x85]), 4, 2);
+        assert_eq!(parquet_decimal(&negative).unwrap(), dec!(-1.23));
+    }
+
+    #[test]
+    fn later_files_override_overlapping_bars() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("AAPL")).unwrap();
+        let header = "timestamp,open,high,low,close,volume\n";
+        std::fs::write(
+            dir.path().join("AAPL").join("a.csv"),
+            format!("{header}2025-01-13T14:30:00Z,1,1,1,1,1\n"),
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("AAPL").join("b.csv"),
+            format!("{header}2025-01-13T14:30:00Z,2,2,2,2,2\n"),
+        )
+        .unwrap();
+
+        let candles = provider(dir.path(), |_| {}).read_symbol("AAPL").unwrap();
+        assert_eq!(candles.len(), 1);
+        assert_eq!(candles[0].close, dec!(2));
+    }
+
+    #[test]
+    fn bad_rows_and_config_are_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("AAPL.csv"),
+            "timestamp,open,high,low,close\n2025-01-13T14:30:00Z,1,1,1,1\n",
+        )
+        .unwrap();
+        let err = provider(dir.path(), |_| {})
+            .read_symbol("AAPL")
+            .unwrap_err();
+        assert!(err.to_string().contains("missing column volume"));
+
+        let mut config = FilesProviderConfig::new(dir.path().to_str().unwrap());
+        config.timezone = "Mars/Olympus".to_string();
+        assert!(matches!(
+            FilesProvider::new(&config),
+            Err(LoaderError::Config(_))
+        ));
+    }
+}
@@ -0,0 +1,352 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use market_calculations::{CalculationOutput, CalculationRegistry, Pipeline};
+use market_data_core::candle::Candle as MdCandle;
+use tirds_models::cache_schema::CacheRow;
+use tracing;
+
+use crate::config::{CalculationsConfig, Timeframe};
+use crate::error::LoaderError;
+use crate::sources::calculations::{
+    convert_candle, indicator_to_cache_row, resolve_indicator, ResolvedIndicator,
+};
+use crate::sources::resample::resample;
+use crate::write_queue::CacheWriter;
+
+/// Per-symbol indicator series kept between refresh cycles.
+///
+/// On an incremental cycle each indicator is recomputed only over the last
+/// `warmup_bars` bars before the first new bar plus the new bars, and the tail
+/// of that output replaces the tail of the cached series. This assumes output
+/// series end at the last input bar. Every `full_recompute_every` cycles (and
+/// whenever the cached state can't be extended) the symbol is recomputed from
+/// scratch.
+#[derive(Debug, Clone, Default)]
+pub struct IncrementalIndicators {
+    cycle: u32,
+    symbols: HashMap<String, HashMap<String, IndicatorSeries>>,
+}
+
+#[derive(Debug, Clone)]
+struct IndicatorSeries {
+    /// Timestamp of the last bar (in the indicator's timeframe) folded in.
+    last_bar: DateTime<Utc>,
+    output: CalculationOutput,
+    /// Per output series: number of leading bars without a value (the
+    /// indicator's own warm-up), measured at the last full recompute. Folded
+    /// series are trimmed so they cover the same bars a full recompute would.
+    offsets: HashMap<String, usize>,
+}
+
+impl IncrementalIndicators {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Compute indicators for all symbols and write results, recomputing from
+    /// scratch on every `full_recompute_every`-th call.
+    pub async fn refresh(
+        &mut self,
+        symbols: &[String],
+        candle_data: &HashMap<String, Vec<MdCandle>>,
+        config: &CalculationsConfig,
+        writer: &CacheWriter,
+    ) -> Result<usize, LoaderError> {
+        let full = self
+            .cycle
+            .is_multiple_of(config.full_recompute_every.max(1));
+        self.cycle = self.cycle.wrapping_add(1);
+        if full {
+            tracing::debug!("Full indicator recompute cycle");
+        }
+
+        let mut total_rows = 0;
+        for symbol in symbols {
+            if let Some(candles) = candle_data.get(symbol) {
+                let rows = self.compute(symbol, candles, config, full);
+                if !rows.is_empty() {
+                    total_rows += rows.len();
+                    writer.upsert_batch(rows).await?;
+                }
+            }
+        }
+
+        Ok(total_rows)
+    }
+
+    /// Compute a symbol's indicator rows, folding in new candles when possible.
+    pub fn compute(
+        &mut self,
+        symbol: &str,
+        candles: &[MdCandle],
+        config: &CalculationsConfig,
+        full: bool,
+    ) -> Vec<CacheRow> {
+        if candles.is_empty() {
+            return Vec::new();
+        }
+
+        let indicators: Vec<ResolvedIndicator> = config
+            .indicators
+            .iter()
+            .filter_map(|entry| resolve_indicator(entry).ok())
+            .collect();
+        let mut bars: HashMap<Timeframe, Vec<market_calculations::Candle>> = HashMap::new();
+        for indicator in &indicators {
+            bars.entry(indicator.timeframe).or_insert_with(|| {
+                resample(candles, indicator.timeframe)
+                    .iter()
+                    .map(convert_candle)
+                    .collect()
+            });
+        }
+
+        let folded = match self.symbols.get(symbol) {
+            Some(state) if !full => fold(state, &indicators, &bars, config.warmup_bars),
+            _ => None,
+        };
+        let state = folded.unwrap_or_else(|| {
+            tracing::debug!(symbol, "Recomputing indicators from scratch");
+            compute_full(symbol, &indicators, &bars)
+        });
+
+        let rows = indicators
+            .iter()
+            .filter_map(|indicator| {
+                state.get(&indicator.name).map(|series| {
+                    indicator_to_cache_row(
+                        &indicator.name,
+                        symbol,
+                        &series.output,
+                        config.ttl_seconds,
+                    )
+                })
+            })
+            .collect();
+        self.symbols.insert(symbol.to_string(), state);
+        rows
+    }
+}
+
+fn bar_time(bar: &market_calculations::Candle) -> Option<DateTime<Utc>> {
+    DateTime::from_timestamp_millis(bar.timestamp)
+}
+
+fn compute_full(
+    symbol: &str,
+    indicators: &[ResolvedIndicator],
+    bars: &HashMap<Timeframe, Vec<market_calculations::Candle>>,
+) -> HashMap<String, IndicatorSeries> {
+    let registry = CalculationRegistry::with_defaults();
+    let pipeline = Pipeline::new(&registry);
+    let mut state = HashMap::new();
+
+    for indicator in indicators {
+        let tf_bars = &bars[&indicator.timeframe];
+        let Some(last_bar) = tf_bars.last().and_then(bar_time) else {
+            continue;
+        };
+        match pipeline.run(&indicator.calc_id, tf_bars, &indicator.params) {
+            Ok(output) => {
+                let offsets = output
+                    .series
+                    .iter()
+                    .map(|(name, values)| {
+                        (name.clone(), tf_bars.len().saturating_sub(values.len()))
+                    })
+                    .collect();
+                state.insert(
+                    indicator.name.clone(),
+                    IndicatorSeries {
+                        last_bar,
+                        output,
+                        offsets,
+                    },
+                );
+            }
+            Err(e) => {
+                tracing::warn!(
+                    symbol,
+                    indicator = indicator.name,
+                    error = ?e,
+                    "Failed to compute indicator"
+                );
+            }
+        }
+    }
+
+    state
+}
+
+/// Extend every cached series with the new bars. Returns None if any indicator
+/// can't be extended (new indicator, gap beyond the cached bar, short output),
+/// in which case the caller recomputes the symbol from scratch.
+fn fold(
+    state: &HashMap<String, IndicatorSeries>,
+    indicators: &[ResolvedIndicator],
+    bars: &HashMap<Timeframe, Vec<market_calculations::Candle>>,
+    warmup_bars: usize,
+) -> Option<HashMap<String, IndicatorSeries>> {
+    let registry = CalculationRegistry::with_defaults();
+    let pipeline = Pipeline::new(&registry);
+    let mut next = HashMap::new();
+
+    for indicator in indicators {
+        let cached = state.get(&indicator.name)?;
+        let tf_bars = &bars[&indicator.timeframe];
+
+        // The cached last bar is recomputed too: it may have been in progress.
+        let idx = tf_bars
+            .iter()
+            .position(|b| bar_time(b) == Some(cached.last_bar))?;
+        let tail = tf_bars.len() - idx;
+
+        let window = &tf_bars[idx.saturating_sub(warmup_bars)..];
+        let windowed = pipeline
+            .run(&indicator.calc_id, window, &indicator.params)
+            .ok()?;
+
+        let mut output = cached.output.clone();
+        for (name, values) in &windowed.series {
+            let series = output.series.get_mut(name)?;
+            if values.len() < tail || series.is_empty() {
+                return None;
+            }
+            series.pop();
+            series.extend_from_slice(&values[values.len() - tail..]);
+            let len = tf_bars.len().saturating_sub(*cached.offsets.get(name)?);
+            if series.len() > len {
+                series.drain(..series.len() - len);
+            }
+        }
+
+        next.insert(
+            indicator.name.clone(),
+            IndicatorSeries {
+                last_bar: tf_bars.last().and_then(bar_time)?,
+                output,
+                offsets: cached.offsets.clone(),
+            },
+        );
+    }
+
+    Some(next)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sources::calculations::compute_indicators;
+    use chrono::TimeZone;
+    use rust_decimal::Decimal;
+    use rust_decimal_macros::dec;
+
+    fn md_candles(count: usize) -> Vec<MdCandle> {
+        let base = Utc.with_ymd_and_hms(2024, 1, 16, 14, 30, 0).unwrap();
+        (0..count)
+            .map(|i| {
+                let price = dec!(100) + Decimal::from((i * 7 % 13) as u32);
+                MdCandle {
+                    timestamp: base + chrono::Duration::minutes(i as i64 * 5),
+                    open: price,
+                    high: price + dec!(1),
+                    low: price - dec!(1),
+                    close: price,
+                    volume: 1_000,
+                }
+            })
+            .collect()
+    }
+
+    fn config(indicators: &[&str]) -> CalculationsConfig {
+        CalculationsConfig {
+            indicators: indicators.iter().map(|s| (*s).into()).collect(),
+            warmup_bars: 20,
+            ..CalculationsConfig::default()
+        }
+    }
+
+    fn value_of(rows: &[CacheRow], key: &str) -> serde_json::Value {
+        let row = rows.iter().find(|r| r.key == key).unwrap();
+        serde_json::from_str(&row.value_json).unwrap()
+    }
+
+    #[test]
+    fn incremental_matches_full_for_windowed_indicator() {
+        let config = config(&["sma_5", "sma_3@15m"]);
+        let candles = md_candles(60);
+        let mut engine = IncrementalIndicators::new();
+
+        engine.compute("AAPL", &candles[..50], &config, true);
+        let incremental = engine.compute("AAPL", &candles, &config, false);
+        let full = compute_indicators("AAPL", &candles, &config);
+
+        for key in ["indicator:sma_5:AAPL", "indicator:sma_3@15m:AAPL"] {
+            assert_eq!(value_of(&incremental, key), value_of(&full, key), "{key}");
+        }
+    }
+
+    #[test]
+    fn no_new_candles_reuses_cached_series() {
+        let config = config(&["sma_5"]);
+        let candles = md_candles(30);
+        let mut engine = IncrementalIndicators::new();
+
+        let first = engine.compute("AAPL", &candles, &config, true);
+        let second = engine.compute("AAPL", &candles, &config, false);
+        assert_eq!(
+            value_of(&first, "indicator:sma_5:AAPL"),
+            value_of(&second, "indicator:sma_5:AAPL")
+        );
+    }
+
+    #[test]
+    fn gap_beyond_cached_bar_falls_back_to_full() {
+        let config = config(&["sma_5"]);
+        let candles = md_candles(60);
+        let mut engine = IncrementalIndicators::new();
+
+        engine.compute("AAPL", &candles[..20], &config, true);
+        // Lookback moved past the cached last bar entirely
+        let rows = engine.compute("AAPL", &candles[30..], &config, false);
+        let full = compute_indicators("AAPL", &candles[30..], &config);
+        assert_eq!(
+            value_of(&rows, "indicator:sma_5:AAPL"),
+            value_of(&full, "indicator:sma_5:AAPL")
+        );
+    }
+
+    #[test]
+    fn newly_configured_indicator_is_computed() {
+        let candles = md_candles(40);
+        let mut engine = IncrementalIndicators::new();
+        engine.compute("AAPL", &candles[..30], &config(&["sma_5"]), true);
+
+        let rows = engine.compute("AAPL", &candles, &config(&["sma_5", "sma_10"]), false);
+        assert_eq!(rows.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn refresh_runs_full_recompute_on_schedule() {
+        let writer = CacheWriter::open_in_memory().unwrap();
+        let mut config = config(&["sma_5"]);
+        config.full_recompute_every = 2;
+        let mut data = HashMap::new();
+        data.insert("AAPL".to_string(), md_candles(30));
+        let symbols = vec!["AAPL".to_string()];
+
+        let mut engine = IncrementalIndicators::new();
+        for _ in 0..3 {
+            assert_eq!(
+                engine
+                    .refresh(&symbols, &data, &config, &writer)
+                    .await
+                    .unwrap(),
+                1
+            );
+        }
+        assert_eq!(engine.cycle, 3);
+        assert_eq!(writer.call(|w| w.count()).unwrap(), 1);
+    }
+}
@@ -0,0 +1,435 @@
+use std::collections::HashMap;
+
+use chrono::{Duration, Utc};
+use market_data_core::candle::Candle as MdCandle;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use tirds_models::cache_schema::{key_patterns, CacheRow};
+use tirds_models::levels::{
+    PivotPoints, PriceLevels, PriorDay, SwingPoint, VolumeNode, VolumeProfile,
+};
+
+use crate::config::{LevelsConfig, Timeframe};
+use crate::error::LoaderError;
+use crate::sources::resample::resample;
+use crate::write_queue::CacheWriter;
+
+/// Compute support/resistance levels from a symbol's 5-minute candles.
+/// Returns None if there are no candles.
+pub fn compute_levels(candles: &[MdCandle], config: &LevelsConfig) -> Option<PriceLevels> {
+    let as_of = candles.last()?.timestamp;
+
+    let daily = resample(candles, Timeframe::OneDay);
+    let prior_day = daily.len().checked_sub(2).map(|i| PriorDay {
+        date: daily[i].timestamp,
+        high: daily[i].high,
+        low: daily[i].low,
+        close: daily[i].close,
+    });
+    let pivots = prior_day
+        .as_ref()
+        .map(|d| PivotPoints::classic(d.high, d.low, d.close));
+
+    let swing_bars = resample(candles, config.swing_timeframe);
+    let (swing_highs, swing_lows) = swings(&swing_bars, config.swing_strength, config.max_swings);
+
+    Some(PriceLevels {
+        as_of,
+        prior_day,
+        pivots,
+        swing_highs,
+        swing_lows,
+        volume_profile: volume_profile(candles, config),
+    })
+}
+
+/// Bars whose high (low) is strictly above (below) the `strength` bars before
+/// them and at least as high (low) as the `strength` bars after them. The most
+/// recent `strength` bars can't be confirmed yet and are never swings.
+fn swings(
+    bars: &[MdCandle],
+    strength: usize,
+    max_swings: usize,
+) -> (Vec<SwingPoint>, Vec<SwingPoint>) {
+    let mut highs = Vec::new();
+    let mut lows = Vec::new();
+    let strength = strength.max(1);
+    if bars.len() <= 2 * strength {
+        return (highs, lows);
+    }
+
+    for i in strength..bars.len() - strength {
+        let before = &bars[i - strength..i];
+        let after = &bars[i + 1..=i + strength];
+        let bar = &bars[i];
+        if before.iter().all(|b| bar.high > b.high) && after.iter().all(|b| bar.high >= b.high) {
+            highs.push(SwingPoint {
+                price: bar.high,
+                timestamp: bar.timestamp,
+            });
+        }
+        if before.iter().all(|b| bar.low < b.low) && after.iter().all(|b| bar.low <= b.low) {
+            lows.push(SwingPoint {
+                price: bar.low,
+                timestamp: bar.timestamp,
+            });
+        }
+    }
+
+    highs.drain(..highs.len().saturating_sub(max_swings));
+    lows.drain(..lows.len().saturating_sub(max_swings));
+    (highs, lows)
+}
+
+/// Bucket each candle's volume at its typical price ((H + L + C) / 3) across
+/// the full high/low range, then grow the value area outward from the point of
+/// control, one bucket at a time toward the heavier side.
+fn volume_profile(candles: &[MdCandle], config: &LevelsConfig) -> Option<VolumeProfile> {
+    let bins = config.profile_bins.max(1);
+    let low = candles.iter().map(|c| c.low).min()?;
+    let high = candles.iter().map(|c| c.high).max()?;
+    if high <= low {
+        return None;
+    }
+    let width = (high - low) / Decimal::from(bins);
+
+    let mut volumes = vec![0u64; bins];
+    for candle in candles {
+        let typical = (candle.high + candle.low + candle.close) / Decimal::from(3);
+        let index = ((typical - low) / width).floor().to_usize().unwrap_or(0);
+        volumes[index.min(bins - 1)] += candle.volume;
+    }
+    let total: u64 = volumes.iter().sum();
+    if total == 0 {
+        return None;
+    }
+
+    let poc = (0..bins)
+        .max_by(|a, b| volumes[*a].cmp(&volumes[*b]).then(b.cmp(a)))
+        .unwrap_or(0);
+
+    let target = total as u128 * config.value_area_percent.min(100) as u128;
+    let (mut lo, mut hi) = (poc, poc);
+    let mut covered = volumes[poc];
+    while (covered as u128) * 100 < target {
+        let below = lo.checked_sub(1).map(|i| volumes[i]);
+        let above = (hi + 1 < bins).then(|| volumes[hi + 1]);
+        match (below, above) {
+            (Some(b), Some(a)) if b > a => {
+                lo -= 1;
+                covered += b;
+            }
+            (_, Some(a)) => {
+                hi += 1;
+                covered += a;
+            }
+            (Some(b), None) => {
+                lo -= 1;
+                covered += b;
+            }
+            (None, None) => break,
+        }
+    }
+
+    let edge = |i: usize| (low + width * Decimal::from(i)).round_dp(4);
+    let midpoint = |i: usize| (low + width * (Decimal::from(i) + Decimal::new(5, 1))).round_dp(4);
+
+    let mut ranked: Vec<usize> = (0..bins).filter(|i| volumes[*i] > 0).collect();
+    ranked.sort_by(|a, b| volumes[*b].cmp(&volumes[*a]).then(a.cmp(b)));
+    ranked.truncate(config.profile_nodes);
+    ranked.sort();
+
+    Some(VolumeProfile {
+        poc: midpoint(poc),
+        value_area_high: edge(hi + 1),
+        value_area_low: edge(lo),
+        nodes: ranked
+            .into_iter()
+            .map(|i| VolumeNode {
+                price: midpoint(i),
+                volume: volumes[i],
+            })
+            .collect(),
+    })
+}
+
+/// Convert computed levels into a `levels:{symbol}` cache row.
+pub fn levels_to_cache_row(symbol: &str, levels: &PriceLevels, ttl_seconds: u64) -> CacheRow {
+    let now = Utc::now();
+    CacheRow {
+        key: key_patterns::levels(symbol),
+        category: "indicator".to_string(),
+        value_json: serde_json::to_string(levels).unwrap_or_default(),
+        source: "tirds-loader:levels".to_string(),
+        symbol: Some(symbol.to_string()),
+        created_at: now.to_rfc3339(),
+        expires_at: (now + Duration::seconds(ttl_seconds as i64)).to_rfc3339(),
+        updated_at: now.to_rfc3339(),
+    }
+}
+
+/// Compute levels for all symbols and write results.
+pub async fn refresh_levels(
+    symbols: &[String],
+    candle_data: &HashMap<String, Vec<MdCandle>>,
+    config: &LevelsConfig,
+    ttl_seconds: u64,
+    writer: &CacheWriter,
+) -> Result<usize, LoaderError> {
+    if !config.enabled {
+        return Ok(0);
+    }
+
+    let rows: Vec<CacheRow> = symbols
+        .iter()
+        .filter_map(|symbol| {
+            let levels = compute_levels(candle_data.get(symbol)?, config)?;
+            Some(levels_to_cache_row(symbol, &levels, ttl_seconds))
+        })
+        .collect();
+    let count = rows.len();
+    writer.upsert_batch(rows).await?;
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{DateTime, TimeZone};
+    use rust_decimal_macros::dec;
+
+    fn candle(
+        timestamp: DateTime<Utc>,
+        high: Decimal,
+        low: Decimal,
+        close: Decimal,
+        volume: u64,
+    ) -> MdCandle {
+        MdCandle {
+            timestamp,
+            open: close,
+            high,
+            low,
+            close,
+            volume,
+        }
+    }
+
+    /// 5-minute candles with the given (high, low) starting at 09:30 ET on 2024-01-16.
+    fn bars(ranges: &[(Decimal, Decimal)]) -> Vec<MdCandle> {
+        let base = Utc.with_ymd_and_hms(2024, 1, 16, 14, 30, 0).unwrap();
+        ranges
+            .iter()
+            .enumerate()
+            .map(|(i, (h, l))| {
+                candle(
+                    base + Duration::minutes(i as i64 * 5),
+                    *h,
+                    *l,
+                    (*h + *l) / dec!(2),
+                    100,
+                )
+            })
+            .collect()
+    }
+
+    fn five_minute_config() -> LevelsConfig {
+        LevelsConfig {
+            swing_timeframe: Timeframe::FiveMinutes,
+            swing_strength: 2,
+            ..LevelsConfig::default()
+        }
+    }
+
+    #[test]
+    fn prior_day_and_pivots_use_previous_session() {
+        let day1 = Utc.with_ymd_and_hms(2024, 1, 16, 14, 30, 0).unwrap();
+        let day2 = Utc.with_ymd_and_hms(2024, 1, 17, 14, 30, 0).unwrap();
+        let candles = vec![
+            candle(day1, dec!(108), dec!(100), dec!(104), 100),
+            candle(
+                day1 + Duration::minutes(5),
+                dec!(110),
+                dec!(103),
+                dec!(105),
+                100,
+            ),
+            candle(day2, dec!(107), dec!(104), dec!(106), 100),
+        ];
+
+        let levels = compute_levels(&candles, &LevelsConfig::default()).unwrap();
+        let prior = levels.prior_day.unwrap();
+        assert_eq!(prior.date, day1);
+        assert_eq!(
+            (prior.high, prior.low, prior.close),
+            (dec!(110), dec!(100), dec!(105))
+        );
+        assert_eq!(levels.pivots.unwrap().pivot, dec!(105));
+        assert_eq!(levels.as_of, day2);
+    }
+
+    #[test]
+    fn single_session_has_no_prior_day() {
+        let candles = bars(&[(dec!(101), dec!(99)), (dec!(102), dec!(100))]);
+        let levels = compute_levels(&candles, &LevelsConfig::default()).unwrap();
+        assert!(levels.prior_day.is_none());
+        assert!(levels.pivots.is_none());
+    }
+
+    #[test]
+    fn finds_confirmed_swings() {
+        let candles = bars(&[
+            (dec!(101), dec!(99)),
+            (dec!(103), dec!(100)),
+            (dec!(106), dec!(102)), // swing high
+            (dec!(104), dec!(98)),
+            (dec!(102), dec!(95)), // swing low
+            (dec!(103), dec!(97)),
+            (dec!(105), dec!(99)),
+            (dec!(110), dec!(90)), // unconfirmed: too recent
+            (dec!(104), dec!(100)),
+        ]);
+        let levels = compute_levels(&candles, &five_minute_config()).unwrap();
+
+        assert_eq!(levels.swing_highs.len(), 1);
+        assert_eq!(levels.swing_highs[0].price, dec!(106));
+        assert_eq!(levels.swing_highs[0].timestamp, candles[2].timestamp);
+        assert_eq!(levels.swing_lows.len(), 1);
+        assert_eq!(levels.swing_lows[0].price, dec!(95));
+    }
+
+    #[test]
+    fn equal_highs_yield_one_swing_and_respect_max() {
+        let candles = bars(&[
+            (dec!(100), dec!(99)),
+            (dec!(101), dec!(99)),
+            (dec!(105), dec!(99)),
+            (dec!(105), dec!(99)),
+            (dec!(101), dec!(99)),
+            (dec!(100), dec!(99)),
+        ]);
+        let levels = compute_levels(&candles, &five_minute_config()).unwrap();
+        assert_eq!(levels.swing_highs.len(), 1);
+        assert_eq!(levels.swing_highs[0].timestamp, candles[2].timestamp);
+
+        let (highs, _) = swings(&candles, 2, 0);
+        assert!(highs.is_empty());
+    }
+
+    #[test]
+    fn volume_profile_finds_poc_and_value_area() {
+        let base = Utc.with_ymd_and_hms(2024, 1, 16, 14, 30, 0).unwrap();
+        // Range 100-110 in 10 one-dollar buckets; heavy volume around 104-105
+        let candles: Vec<MdCandle> = [
+            (dec!(100.5), 10),
+            (dec!(102.5), 50),
+            (dec!(103.5), 100),
+            (dec!(104.5), 500),
+            (dec!(105.5), 200),
+            (dec!(107.5), 20),
+            (dec!(109.5), 10),
+        ]
+        .iter()
+        .enumerate()
+        .map(|(i, (price, volume))| {
+            candle(
+                base + Duration::minutes(i as i64 * 5),
+                *price,
+                *price,
+                *price,
+                *volume,
+            )
+        })
+        .chain([
+            candle(
+                base + Duration::minutes(40),
+                dec!(110),
+                dec!(110),
+                dec!(110),
+                0,
+            ),
+            candle(
+                base + Duration::minutes(45),
+                dec!(100),
+                dec!(100),
+                dec!(100),
+                0,
+            ),
+        ])
+        .collect();
+        let config = LevelsConfig {
+            profile_bins: 10,
+            profile_nodes: 2,
+            ..LevelsConfig::default()
+        };
+
+        let profile = compute_levels(&candles, &config)
+            .unwrap()
+            .volume_profile
+            .unwrap();
+        assert_eq!(profile.poc, dec!(104.5));
+        // 500 + 200 = 700 of 890 total covers 70%
+        assert_eq!(profile.value_area_low, dec!(104));
+        assert_eq!(profile.value_area_high, dec!(106));
+        assert_eq!(
+            profile.nodes,
+            vec![
+                VolumeNode {
+                    price: dec!(104.5),
+                    volume: 500
+                },
+                VolumeNode {
+                    price: dec!(105.5),
+                    volume: 200
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn flat_prices_have_no_profile() {
+        let candles = bars(&[(dec!(100), dec!(100)), (dec!(100), dec!(100))]);
+        let levels = compute_levels(&candles, &LevelsConfig::default()).unwrap();
+        assert!(levels.volume_profile.is_none());
+        assert!(compute_levels(&[], &LevelsConfig::default()).is_none());
+    }
+
+    #[test]
+    fn levels_row_roundtrips() {
+        let candles = bars(&[(dec!(101), dec!(99)), (dec!(102), dec!(100))]);
+        let levels = compute_levels(&candles, &LevelsConfig::default()).unwrap();
+        let row = levels_to_cache_row("AAPL", &levels, 600);
+        assert_eq!(row.key, "levels:AAPL");
+        assert_eq!(row.category, "indicator");
+        let parsed: PriceLevels = serde_json::from_str(&row.value_json).unwrap();
+        assert_eq!(parsed, levels);
+    }
+
+    #[tokio::test]
+    async fn refresh_writes_levels_rows() {
+        let writer = CacheWriter::open_in_memory().unwrap();
+        let mut data = HashMap::new();
+        data.insert(
+            "AAPL".to_string(),
+            bars(&[(dec!(101), dec!(99)), (dec!(102), dec!(100))]),
+        );
+        let symbols = vec!["AAPL".to_string(), "MSFT".to_string()];
+
+        let count = refresh_levels(&symbols, &data, &LevelsConfig::default(), 600, &writer)
+            .await
+            .unwrap();
+        assert_eq!(count, 1);
+        assert_eq!(writer.call(|w| w.count()).unwrap(), 1);
+
+        let disabled = LevelsConfig {
+            enabled: false,
+            ..LevelsConfig::default()
+        };
+        assert_eq!(
+            refresh_levels(&symbols, &data, &disabled, 600, &writer)
+                .await
+                .unwrap(),
+            0
+        );
+    }
+}
@@ -1,73 +1,199 @@
-use std::sync::{Arc, Mutex};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
 
 use chrono::{Duration, NaiveDate, Utc};
+use chrono_tz::America::New_York;
 use market_data_core::candle::Candle;
 use market_data_core::store::CandleStore;
-use market_data_providers::provider::CandleProvider;
+use serde::{Deserialize, Serialize};
 use tirds_models::cache_schema::{key_patterns, CacheRow};
+use tirds_models::sector::SymbolMeta;
 use tokio_util::sync::CancellationToken;
 use tracing;
 
-use crate::config::{MarketDataConfig, ProviderKind};
+use crate::config::{MarketDataConfig, Timeframe};
 use crate::error::LoaderError;
-use crate::writer::SqliteWriter;
-
-/// Create a market data provider based on the configured kind.
-pub fn create_provider(kind: &ProviderKind) -> Result<Box<dyn CandleProvider>, LoaderError> {
-    match kind {
-        ProviderKind::Yahoo => Ok(Box::new(market_data_providers::yahoo::YahooProvider::new())),
-        ProviderKind::Alpaca => {
-            let provider = market_data_providers::alpaca::AlpacaProvider::from_env()
-                .map_err(|e| LoaderError::Provider(format!("Alpaca provider: {e}")))?;
-            Ok(Box::new(provider))
-        }
-    }
+use crate::sources::calendar::ExchangeCalendar;
+use crate::sources::providers::ProviderChain;
+use crate::sources::resample::resample;
+use crate::write_queue::CacheWriter;
+
+/// Where and how [`fill_missing_data`] fetches missing days.
+#[derive(Clone, Copy)]
+pub struct Backfill<'a> {
+    pub store: &'a CandleStore,
+    pub providers: &'a ProviderChain,
+    pub calendar: &'a ExchangeCalendar,
+    /// Most trading days requested from a provider at once.
+    pub chunk_days: usize,
 }
 
-/// Fill missing market data by fetching from the configured provider.
-/// Returns the number of days fetched and written to the local store.
+/// Fill missing market data from the provider chain.
+///
+/// Missing trading days (per `calendar`, excluding days in `log.empty` and
+/// future days) are coalesced into ranges of at most `chunk_days` trading days.
+/// For each range, providers are asked in order for the days still missing, so
+/// a provider that fails or lacks some days falls through to the next. Days
+/// served are recorded in `log.sources`; past days that every provider answered
+/// without data are added to `log.empty` and not requested again, unless the
+/// chain includes the files provider. Returns the number of days written to
+/// the local store.
 pub async fn fill_missing_data(
-    store: &CandleStore,
-    provider: &dyn CandleProvider,
+    backfill: &Backfill<'_>,
+    log: &mut DayLog,
     symbol: &str,
     start: NaiveDate,
     end: NaiveDate,
 ) -> Result<usize, LoaderError> {
-    let missing = store.missing_dates(symbol, start, end);
-    if missing.is_empty() {
+    let Backfill {
+        store,
+        providers,
+        calendar,
+        chunk_days,
+    } = *backfill;
+    let today = Utc::now().with_timezone(&New_York).date_naive();
+    let missing: Vec<NaiveDate> = store
+        .missing_dates(symbol, start, end)
+        .into_iter()
+        .filter(|d| *d <= today && !log.empty.contains(d))
+        .collect();
+    let ranges = calendar.trading_ranges(&missing, chunk_days);
+    if ranges.is_empty() {
         return Ok(0);
     }
 
     tracing::info!(
         symbol,
         missing_days = missing.len(),
-        provider = provider.name(),
+        ranges = ranges.len(),
         "Fetching missing market data"
     );
 
-    // Fetch the contiguous range covering all missing dates
-    let fetch_start = missing[0];
-    let fetch_end = missing[missing.len() - 1];
-
-    let fetched = provider
-        .fetch_candles_range(symbol, fetch_start, fetch_end)
-        .await
-        .map_err(|e| LoaderError::Provider(format!("{symbol}: {e}")))?;
-
     let mut days_written = 0;
-    for (date, candles) in &fetched {
-        if missing.contains(date) && !candles.is_empty() {
-            store
-                .write_day(symbol, *date, candles)
-                .map_err(|e| LoaderError::Provider(format!("{symbol} write {date}: {e}")))?;
-            days_written += 1;
+    let mut errors = Vec::new();
+    for (range_start, range_end) in ranges {
+        let mut remaining: Vec<NaiveDate> = missing
+            .iter()
+            .copied()
+            .filter(|d| *d >= range_start && *d <= range_end && calendar.is_trading_day(*d))
+            .collect();
+        let mut answered = 0;
+        let mut empty_is_final = true;
+
+        for provider in providers.available() {
+            let fetched = match provider
+                .fetch_range(symbol, remaining[0], remaining[remaining.len() - 1])
+                .await
+            {
+                Ok(fetched) => fetched,
+                Err(e) => {
+                    tracing::warn!(symbol, provider = provider.name(), error = %e, "Provider failed; trying next");
+                    errors.push(e.to_string());
+                    continue;
+                }
+            };
+            answered += 1;
+            empty_is_final &= provider.empty_is_final();
+
+            for (date, candles) in &fetched {
+                if remaining.contains(date) && !candles.is_empty() {
+                    store.write_day(symbol, *date, candles).map_err(|e| {
+                        LoaderError::Provider(format!("{symbol} write {date}: {e}"))
+                    })?;
+                    log.sources.insert(*date, provider.name().to_string());
+                    days_written += 1;
+                }
+            }
+            remaining.retain(|date| !log.sources.contains_key(date));
+            if remaining.is_empty() {
+                break;
+            }
+        }
+
+        // Only trust "no data" when every provider in the chain answered and
+        // none can gain the day later, and never for today (bars may still be
+        // arriving).
+        if answered == providers.len() && empty_is_final {
+            log.empty
+                .extend(remaining.into_iter().filter(|d| *d < today));
         }
     }
 
+    if days_written == 0 && !errors.is_empty() {
+        return Err(LoaderError::Provider(errors.join("; ")));
+    }
     tracing::info!(symbol, days_written, "Finished filling missing data");
     Ok(days_written)
 }
 
+/// Per-symbol record of how locally stored days were obtained, kept next to
+/// the candle store in `{data_path}/_sources/{symbol}.json`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DayLog {
+    /// Provider that served each day fetched by the loader.
+    #[serde(default)]
+    pub sources: BTreeMap<NaiveDate, String>,
+    /// Past trading days every provider returned no data for.
+    #[serde(default)]
+    pub empty: BTreeSet<NaiveDate>,
+}
+
+impl DayLog {
+    fn path(data_path: &str, symbol: &str) -> PathBuf {
+        Path::new(data_path)
+            .join("_sources")
+            .join(format!("{symbol}.json"))
+    }
+
+    /// The symbol's log, or an empty one if it doesn't exist or can't be read.
+    pub fn load(data_path: &str, symbol: &str) -> Self {
+        std::fs::read_to_string(Self::path(data_path, symbol))
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, data_path: &str, symbol: &str) -> Result<(), LoaderError> {
+        let path = Self::path(data_path, symbol);
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, serde_json::to_string(self)?)?;
+        Ok(())
+    }
+}
+
+/// Record the serving providers in market data rows' `source`: bars rows get
+/// `market-data:{providers}` (each provider that served a day in the bars, in
+/// order of first use) and the quote gets the provider of its day. Days loaded
+/// from the local store without a record keep the plain `market-data` source.
+pub fn apply_day_sources(
+    rows: &mut [CacheRow],
+    candles: &[Candle],
+    sources: &BTreeMap<NaiveDate, String>,
+) {
+    let day = |c: &Candle| c.timestamp.with_timezone(&New_York).date_naive();
+    let mut providers: Vec<&str> = Vec::new();
+    for candle in candles {
+        if let Some(p) = sources.get(&day(candle)) {
+            if !providers.contains(&p.as_str()) {
+                providers.push(p);
+            }
+        }
+    }
+    let latest = candles.last().and_then(|c| sources.get(&day(c)));
+
+    for row in rows {
+        if row.key.starts_with("bars:") && !providers.is_empty() {
+            row.source = format!("market-data:{}", providers.join(","));
+        } else if row.key.starts_with("quote:") {
+            if let Some(p) = latest {
+                row.source = format!("market-data:{p}");
+            }
+        }
+    }
+}
+
 /// Convert a market-data `Candle` to a JSON-serializable value.
 fn candle_to_json(candle: &Candle) -> serde_json::Value {
     serde_json::json!({
@@ -80,31 +206,38 @@ fn candle_to_json(candle: &Candle) -> serde_json::Value {
     })
 }
 
-/// Build cache rows from candles for a single symbol.
+/// Build cache rows from 5-minute candles for a single symbol: one
+/// `bars:{symbol}:{tf}` entry per timeframe, plus a quote from the latest candle.
 pub fn candles_to_cache_rows(
     symbol: &str,
     candles: &[Candle],
     category: &str,
     ttl_seconds: u64,
+    timeframes: &[Timeframe],
 ) -> Vec<CacheRow> {
     let now = Utc::now();
     let expires_at = (now + Duration::seconds(ttl_seconds as i64)).to_rfc3339();
     let now_str = now.to_rfc3339();
     let mut rows = Vec::new();
 
-    // Write bars entry with all candles
+    // Write one bars entry per timeframe
     if !candles.is_empty() {
-        let bars_json: Vec<serde_json::Value> = candles.iter().map(candle_to_json).collect();
-        rows.push(CacheRow {
-            key: key_patterns::bars(symbol, "5m"),
-            category: category.to_string(),
-            value_json: serde_json::to_string(&bars_json).unwrap_or_default(),
-            source: "market-data".to_string(),
-            symbol: Some(symbol.to_string()),
-            created_at: now_str.clone(),
-            expires_at: expires_at.clone(),
-            updated_at: now_str.clone(),
-        });
+        for timeframe in timeframes {
+            let bars_json: Vec<serde_json::Value> = resample(candles, *timeframe)
+                .iter()
+                .map(candle_to_json)
+                .collect();
+            rows.push(CacheRow {
+                key: key_patterns::bars(symbol, timeframe.as_str()),
+                category: category.to_string(),
+                value_json: serde_json::to_string(&bars_json).unwrap_or_default(),
+                source: "market-data".to_string(),
+                symbol: Some(symbol.to_string()),
+                created_at: now_str.clone(),
+                expires_at: expires_at.clone(),
+                updated_at: now_str.clone(),
+            });
+        }
 
         // Write quote entry from the most recent candle
         let latest = &candles[candles.len() - 1];
@@ -128,10 +261,25 @@ pub fn candles_to_cache_rows(
     rows
 }
 
+/// Convert a symbol's sector classification into its `meta:{symbol}` cache row.
+pub fn meta_to_cache_row(meta: &SymbolMeta, ttl_seconds: u64) -> CacheRow {
+    let now = Utc::now();
+    CacheRow {
+        key: key_patterns::meta(&meta.symbol),
+        category: "meta".to_string(),
+        value_json: serde_json::to_string(meta).unwrap_or_default(),
+        source: "sector-map".to_string(),
+        symbol: Some(meta.symbol.clone()),
+        created_at: now.to_rfc3339(),
+        expires_at: (now + Duration::seconds(ttl_seconds as i64)).to_rfc3339(),
+        updated_at: now.to_rfc3339(),
+    }
+}
+
 /// Refresh market data for all configured symbols.
-fn refresh_market_data(
+async fn refresh_market_data(
     config: &MarketDataConfig,
-    writer: &Arc<Mutex<SqliteWriter>>,
+    writer: &CacheWriter,
 ) -> Result<usize, LoaderError> {
     let store = CandleStore::new(&config.data_path);
 
@@ -161,15 +309,13 @@ fn refresh_market_data(
             end_date,
             category,
             config.ttl_seconds,
+            &config.timeframes,
         ) {
             Ok(rows) => {
                 if !rows.is_empty() {
-                    let mut w = writer
-                        .lock()
-                        .map_err(|e| LoaderError::MarketData(format!("Writer lock: {e}")))?;
-                    w.upsert_batch(&rows)?;
                     total_rows += rows.len();
                     tracing::debug!(symbol, count = rows.len(), "Wrote market data entries");
+                    writer.upsert_batch(rows).await?;
                 }
             }
             Err(e) => {
@@ -188,6 +334,7 @@ fn load_symbol(
     end: NaiveDate,
     category: &str,
     ttl_seconds: u64,
+    timeframes: &[Timeframe],
 ) -> Result<Vec<CacheRow>, LoaderError> {
     let candles = store
         .read_range(symbol, start, end)
@@ -198,19 +345,20 @@ fn load_symbol(
         &candles,
         category,
         ttl_seconds,
+        timeframes,
     ))
 }
 
 /// Run the periodic market data refresh loop.
 pub async fn market_data_loop(
     config: MarketDataConfig,
-    writer: Arc<Mutex<SqliteWriter>>,
+    writer: CacheWriter,
     cancel: CancellationToken,
 ) {
     let interval = std::time::Duration::from_secs(config.refresh_interval_seconds);
 
     // Refresh immediately on startup
-    match refresh_market_data(&config, &writer) {
+    match refresh_market_data(&config, &writer).await {
         Ok(count) => tracing::info!(count, "Initial market data refresh complete"),
         Err(e) => tracing::error!(error = %e, "Initial market data refresh failed"),
     }
@@ -222,7 +370,7 @@ pub async fn market_data_loop(
                 break;
             }
             _ = tokio::time::sleep(interval) => {
-                match refresh_market_data(&config, &writer) {
+                match refresh_market_data(&config, &writer).await {
                     Ok(count) => tracing::debug!(count, "Market data refresh complete"),
                     Err(e) => tracing::error!(error = %e, "Market data refresh failed"),
                 }
@@ -234,36 +382,70 @@ pub async fn market_data_loop(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::{FilesProviderConfig, ProviderKind, ProviderSettings};
+    use crate::sources::files::FilesProvider;
     use async_trait::async_trait;
     use chrono::TimeZone;
     use market_data_providers::error::ProviderError;
+    use market_data_providers::provider::CandleProvider;
     use rust_decimal_macros::dec;
     use std::collections::HashMap;
-    use std::sync::Mutex as StdMutex;
+    use std::sync::{Arc, Mutex as StdMutex};
 
-    /// Mock provider that returns pre-configured candles per (symbol, date).
+    /// Mock provider that returns pre-configured candles per (symbol, date),
+    /// or fails every fetch when `data` is None.
     struct MockProvider {
-        data: HashMap<(String, NaiveDate), Vec<Candle>>,
-        fetch_count: StdMutex<usize>,
+        name: &'static str,
+        data: Option<HashMap<(String, NaiveDate), Vec<Candle>>>,
+        fetch_count: Arc<StdMutex<usize>>,
     }
 
     impl MockProvider {
         fn new(data: HashMap<(String, NaiveDate), Vec<Candle>>) -> Self {
+            Self::named("mock", Some(data))
+        }
+
+        fn named(
+            name: &'static str,
+            data: Option<HashMap<(String, NaiveDate), Vec<Candle>>>,
+        ) -> Self {
             Self {
+                name,
                 data,
-                fetch_count: StdMutex::new(0),
+                fetch_count: Arc::new(StdMutex::new(0)),
             }
         }
 
-        fn fetch_count(&self) -> usize {
-            *self.fetch_count.lock().unwrap_or_else(|e| e.into_inner())
+        /// Wrap in a single-provider chain, returning a handle to the fetch count.
+        fn into_chain(self) -> (ProviderChain, Arc<StdMutex<usize>>) {
+            let count = self.fetch_count.clone();
+            (chain(vec![self]), count)
         }
     }
 
+    fn chain(providers: Vec<MockProvider>) -> ProviderChain {
+        ProviderChain::new(
+            providers
+                .into_iter()
+                .map(|p| {
+                    let settings = ProviderSettings {
+                        max_retries: 0,
+                        ..ProviderSettings::new(ProviderKind::Yahoo)
+                    };
+                    (Box::new(p) as Box<dyn CandleProvider>, settings)
+                })
+                .collect(),
+        )
+    }
+
+    fn count(handle: &Arc<StdMutex<usize>>) -> usize {
+        *handle.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
     #[async_trait]
     impl CandleProvider for MockProvider {
         fn name(&self) -> &str {
-            "mock"
+            self.name
         }
 
         async fn fetch_candles(
@@ -272,12 +454,32 @@ mod tests {
             date: NaiveDate,
         ) -> Result<Vec<Candle>, ProviderError> {
             *self.fetch_count.lock().unwrap_or_else(|e| e.into_inner()) += 1;
-            Ok(self
-                .data
+            let Some(data) = &self.data else {
+                return Err(ProviderError::Http("unavailable".to_string()));
+            };
+            Ok(data
                 .get(&(symbol.to_string(), date))
                 .cloned()
                 .unwrap_or_default())
         }
+
+        /// Counts one fetch per range request.
+        async fn fetch_candles_range(
+            &self,
+            symbol: &str,
+            start: NaiveDate,
+            end: NaiveDate,
+        ) -> Result<Vec<(NaiveDate, Vec<Candle>)>, ProviderError> {
+            *self.fetch_count.lock().unwrap_or_else(|e| e.into_inner()) += 1;
+            let Some(data) = &self.data else {
+                return Err(ProviderError::Http("unavailable".to_string()));
+            };
+            Ok(start
+                .iter_days()
+                .take_while(|d| *d <= end)
+                .filter_map(|d| Some((d, data.get(&(symbol.to_string(), d))?.clone())))
+                .collect())
+        }
     }
 
     fn sample_candles() -> Vec<Candle> {
@@ -304,7 +506,13 @@ mod tests {
     #[test]
     fn candles_to_rows_produces_bars_and_quote() {
         let candles = sample_candles();
-        let rows = candles_to_cache_rows("AAPL", &candles, "market_data", 600);
+        let rows = candles_to_cache_rows(
+            "AAPL",
+            &candles,
+            "market_data",
+            600,
+            &[Timeframe::FiveMinutes],
+        );
         assert_eq!(rows.len(), 2); // bars + quote
 
         assert_eq!(rows[0].key, "bars:AAPL:5m");
@@ -317,28 +525,56 @@ mod tests {
 
     #[test]
     fn empty_candles_produce_no_rows() {
-        let rows = candles_to_cache_rows("AAPL", &[], "market_data", 600);
+        let rows = candles_to_cache_rows("AAPL", &[], "market_data", 600, &[Timeframe::OneDay]);
         assert!(rows.is_empty());
     }
 
     #[test]
     fn reference_symbol_uses_correct_category() {
         let candles = sample_candles();
-        let rows = candles_to_cache_rows("SPY", &candles, "reference_symbol", 600);
+        let rows = candles_to_cache_rows(
+            "SPY",
+            &candles,
+            "reference_symbol",
+            600,
+            &[Timeframe::FiveMinutes],
+        );
         assert_eq!(rows[0].category, "reference_symbol");
     }
 
     #[test]
-    fn create_provider_yahoo_default() {
-        let provider = create_provider(&ProviderKind::Yahoo).unwrap();
-        assert_eq!(provider.name(), "yahoo");
+    fn candles_to_rows_writes_each_timeframe() {
+        let candles = sample_candles();
+        let rows = candles_to_cache_rows(
+            "SPY",
+            &candles,
+            "reference_symbol",
+            600,
+            &[Timeframe::FiveMinutes, Timeframe::OneDay],
+        );
+        let keys: Vec<&str> = rows.iter().map(|r| r.key.as_str()).collect();
+        assert_eq!(keys, vec!["bars:SPY:5m", "bars:SPY:1d", "quote:SPY"]);
+
+        let daily: Vec<serde_json::Value> = serde_json::from_str(&rows[1].value_json).unwrap();
+        assert_eq!(daily.len(), 1);
+        assert_eq!(daily[0]["close"], "151.75");
+        assert_eq!(daily[0]["volume"], 185_000);
     }
 
     #[test]
-    fn create_provider_alpaca_fails_without_env() {
-        // Alpaca requires ALPACA_API_KEY_ID and ALPACA_API_SECRET_KEY
-        let result = create_provider(&ProviderKind::Alpaca);
-        assert!(result.is_err());
+    fn meta_row_uses_meta_key_and_category() {
+        let meta = SymbolMeta {
+            symbol: "AAPL".to_string(),
+            sector: "Information Technology".to_string(),
+            industry: None,
+            sector_etf: Some("XLK".to_string()),
+        };
+        let row = meta_to_cache_row(&meta, 600);
+        assert_eq!(row.key, "meta:AAPL");
+        assert_eq!(row.category, "meta");
+        assert_eq!(row.symbol.as_deref(), Some("AAPL"));
+        let value: serde_json::Value = serde_json::from_str(&row.value_json).unwrap();
+        assert_eq!(value["sector_etf"], "XLK");
     }
 
     fn sample_candles_for_date(date: NaiveDate) -> Vec<Candle> {
@@ -352,6 +588,24 @@ mod tests {
         }]
     }
 
+    /// Fill with the default calendar and 30-day chunks, returning the day log.
+    async fn fill(
+        store: &CandleStore,
+        providers: &ProviderChain,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<DayLog, LoaderError> {
+        let mut log = DayLog::default();
+        let backfill = Backfill {
+            store,
+            providers,
+            calendar: &ExchangeCalendar::default(),
+            chunk_days: 30,
+        };
+        fill_missing_data(&backfill, &mut log, "TEST", start, end).await?;
+        Ok(log)
+    }
+
     #[tokio::test]
     async fn fill_missing_data_fetches_and_writes() {
         let dir = tempfile::tempdir().unwrap();
@@ -364,14 +618,13 @@ mod tests {
         let mut data = HashMap::new();
         data.insert(("TEST".to_string(), date1), sample_candles_for_date(date1));
         data.insert(("TEST".to_string(), date2), sample_candles_for_date(date2));
-        let provider = MockProvider::new(data);
+        let (providers, fetches) = MockProvider::new(data).into_chain();
 
-        let days = fill_missing_data(&store, &provider, "TEST", date1, date2)
-            .await
-            .unwrap();
+        let log = fill(&store, &providers, date1, date2).await.unwrap();
 
-        assert_eq!(days, 2);
-        assert!(provider.fetch_count() > 0);
+        assert_eq!(log.sources.len(), 2);
+        assert!(log.sources.values().all(|p| p == "mock"));
+        assert!(count(&fetches) > 0);
 
         // Data should now be readable from the store
         let candles = store.read_range("TEST", date1, date2).unwrap();
@@ -390,13 +643,203 @@ mod tests {
             .write_day("TEST", date, &sample_candles_for_date(date))
             .unwrap();
 
-        let provider = MockProvider::new(HashMap::new());
+        let (providers, fetches) = MockProvider::new(HashMap::new()).into_chain();
+
+        let log = fill(&store, &providers, date, date).await.unwrap();
+
+        assert_eq!(log, DayLog::default());
+        assert_eq!(count(&fetches), 0);
+    }
+
+    #[tokio::test]
+    async fn fill_missing_data_falls_through_providers() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = CandleStore::new(dir.path().to_str().unwrap());
+        let date1 = NaiveDate::from_ymd_opt(2025, 1, 13).unwrap();
+        let date2 = NaiveDate::from_ymd_opt(2025, 1, 14).unwrap();
+
+        // "partial" only has the first day; "full" has both.
+        let mut partial = HashMap::new();
+        partial.insert(("TEST".to_string(), date1), sample_candles_for_date(date1));
+        let mut full = partial.clone();
+        full.insert(("TEST".to_string(), date2), sample_candles_for_date(date2));
+        let providers = chain(vec![
+            MockProvider::named("down", None),
+            MockProvider::named("partial", Some(partial)),
+            MockProvider::named("full", Some(full)),
+        ]);
+
+        let log = fill(&store, &providers, date1, date2).await.unwrap();
+
+        assert_eq!(log.sources[&date1], "partial");
+        assert_eq!(log.sources[&date2], "full");
+        // "down" failed, so nothing can be known to be empty.
+        assert!(log.empty.is_empty());
+    }
+
+    #[tokio::test]
+    async fn fill_missing_data_imports_local_files() {
+        let imports = tempfile::tempdir().unwrap();
+        std::fs::write(
+            imports.path().join("TEST.csv"),
+            "timestamp,open,high,low,close,volume\n2025-01-13T14:30:00Z,150,151.5,149.5,151,100000\n",
+        )
+        .unwrap();
+        let files = FilesProvider::new(&FilesProviderConfig::new(imports.path().to_str().unwrap()))
+            .unwrap();
+        let providers = ProviderChain::new(vec![(
+            Box::new(files),
+            ProviderSettings::new(ProviderKind::Files),
+        )]);
+
+        let dir = tempfile::tempdir().unwrap();
+        let store = CandleStore::new(dir.path().to_str().unwrap());
+        let date = NaiveDate::from_ymd_opt(2025, 1, 13).unwrap();
+        let log = fill(&store, &providers, date, date).await.unwrap();
+
+        assert_eq!(log.sources[&date], "files");
+        let candles = store.read_range("TEST", date, date).unwrap();
+        assert_eq!(candles[0].close, dec!(151));
+    }
+
+    #[tokio::test]
+    async fn files_dropped_after_an_empty_answer_are_imported() {
+        let imports = tempfile::tempdir().unwrap();
+        let files = FilesProvider::new(&FilesProviderConfig::new(imports.path().to_str().unwrap()))
+            .unwrap();
+        let providers = ProviderChain::new(vec![(
+            Box::new(files),
+            ProviderSettings::new(ProviderKind::Files),
+        )]);
+        let dir = tempfile::tempdir().unwrap();
+        let store = CandleStore::new(dir.path().to_str().unwrap());
+        let date = NaiveDate::from_ymd_opt(2025, 1, 13).unwrap();
+
+        let mut log = fill(&store, &providers, date, date).await.unwrap();
+        assert!(log.empty.is_empty());
+
+        std::fs::write(
+            imports.path().join("TEST.csv"),
+            "timestamp,open,high,low,close,volume\n2025-01-13T14:30:00Z,150,151.5,149.5,151,100000\n",
+        )
+        .unwrap();
+        let backfill = Backfill {
+            store: &store,
+            providers: &providers,
+            calendar: &ExchangeCalendar::default(),
+            chunk_days: 30,
+        };
+        let days = fill_missing_data(&backfill, &mut log, "TEST", date, date)
+            .await
+            .unwrap();
+        assert_eq!(days, 1);
+        assert_eq!(log.sources[&date], "files");
+    }
+
+    #[tokio::test]
+    async fn fill_missing_data_errors_when_every_provider_fails() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = CandleStore::new(dir.path().to_str().unwrap());
+        let date = NaiveDate::from_ymd_opt(2025, 1, 13).unwrap();
+        let (providers, _) = MockProvider::named("down", None).into_chain();
+
+        let result = fill(&store, &providers, date, date).await;
+        assert!(matches!(result, Err(LoaderError::Provider(_))));
+    }
+
+    #[tokio::test]
+    async fn fill_missing_data_fetches_only_missing_ranges() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = CandleStore::new(dir.path().to_str().unwrap());
+        let start = NaiveDate::from_ymd_opt(2024, 1, 2).unwrap();
+        let end = NaiveDate::from_ymd_opt(2024, 3, 28).unwrap();
+        let gap = NaiveDate::from_ymd_opt(2024, 2, 7).unwrap();
+
+        // Everything stored except one day in February.
+        for date in start.iter_days().take_while(|d| *d <= end) {
+            if date != gap {
+                store
+                    .write_day("TEST", date, &sample_candles_for_date(date))
+                    .unwrap();
+            }
+        }
+        let mut data = HashMap::new();
+        data.insert(("TEST".to_string(), gap), sample_candles_for_date(gap));
+        let (providers, fetches) = MockProvider::new(data).into_chain();
+
+        let log = fill(&store, &providers, start, end).await.unwrap();
 
-        let days = fill_missing_data(&store, &provider, "TEST", date, date)
+        assert_eq!(log.sources.len(), 1);
+        // One request for the missing day, not the whole quarter.
+        assert_eq!(count(&fetches), 1);
+    }
+
+    #[tokio::test]
+    async fn fill_missing_data_skips_holidays_and_records_empty_days() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = CandleStore::new(dir.path().to_str().unwrap());
+        // Fri Jan 12 2024 through Tue Jan 16 2024 (Mon 15th is MLK day).
+        let start = NaiveDate::from_ymd_opt(2024, 1, 12).unwrap();
+        let end = NaiveDate::from_ymd_opt(2024, 1, 16).unwrap();
+        let (providers, fetches) = MockProvider::new(HashMap::new()).into_chain();
+
+        let log = fill(&store, &providers, start, end).await.unwrap();
+        assert_eq!(count(&fetches), 1); // 12th through 16th in one range
+        assert_eq!(
+            log.empty.iter().copied().collect::<Vec<_>>(),
+            vec![start, end]
+        );
+
+        // Days recorded as empty are not requested again.
+        let mut log = log;
+        let backfill = Backfill {
+            store: &store,
+            providers: &providers,
+            calendar: &ExchangeCalendar::default(),
+            chunk_days: 30,
+        };
+        fill_missing_data(&backfill, &mut log, "TEST", start, end)
             .await
             .unwrap();
+        assert_eq!(count(&fetches), 1);
+    }
 
-        assert_eq!(days, 0);
-        assert_eq!(provider.fetch_count(), 0);
+    #[test]
+    fn day_log_persists_and_labels_rows() {
+        let dir = tempfile::tempdir().unwrap();
+        let data_path = dir.path().to_str().unwrap();
+        let date1 = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+
+        assert_eq!(DayLog::load(data_path, "AAPL"), DayLog::default());
+        let mut log = DayLog::default();
+        log.sources.insert(date1, "alpaca".to_string());
+        log.empty
+            .insert(NaiveDate::from_ymd_opt(2024, 1, 12).unwrap());
+        log.save(data_path, "AAPL").unwrap();
+        let loaded = DayLog::load(data_path, "AAPL");
+        assert_eq!(loaded, log);
+        let sources = loaded.sources;
+
+        let candles = sample_candles();
+        let mut rows = candles_to_cache_rows(
+            "AAPL",
+            &candles,
+            "market_data",
+            600,
+            &[Timeframe::FiveMinutes],
+        );
+        apply_day_sources(&mut rows, &candles, &sources);
+        assert_eq!(rows[0].source, "market-data:alpaca");
+        assert_eq!(rows[1].source, "market-data:alpaca");
+
+        let mut rows = candles_to_cache_rows(
+            "AAPL",
+            &candles,
+            "market_data",
+            600,
+            &[Timeframe::FiveMinutes],
+        );
+        apply_day_sources(&mut rows, &candles, &BTreeMap::new());
+        assert_eq!(rows[0].source, "market-data");
     }
 }
@@ -1,3 +1,12 @@
 pub mod calculations;
+pub mod calendar;
+pub mod derived;
+pub mod files;
+pub mod incremental;
+pub mod levels;
 pub mod market_data;
+pub mod providers;
+pub mod quality;
+pub mod regime;
+pub mod resample;
 pub mod stream;
@@ -0,0 +1,367 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use chrono::NaiveDate;
+use market_data_core::candle::Candle;
+use market_data_providers::provider::CandleProvider;
+
+use crate::config::{MarketDataConfig, ProviderKind, ProviderSettings};
+use crate::error::LoaderError;
+use crate::sources::files::FilesProvider;
+
+/// Create a market data provider based on the configured kind.
+pub fn create_provider(
+    kind: &ProviderKind,
+    config: &MarketDataConfig,
+) -> Result<Box<dyn CandleProvider>, LoaderError> {
+    match kind {
+        ProviderKind::Yahoo => Ok(Box::new(market_data_providers::yahoo::YahooProvider::new())),
+        ProviderKind::Alpaca => {
+            let provider = market_data_providers::alpaca::AlpacaProvider::from_env()
+                .map_err(|e| LoaderError::Provider(format!("Alpaca provider: {e}")))?;
+            Ok(Box::new(provider))
+        }
+        ProviderKind::Files => {
+            let files = config.files.as_ref().ok_or_else(|| {
+                LoaderError::Config("files provider requires [market_data.files]".to_string())
+            })?;
+            Ok(Box::new(FilesProvider::new(files)?))
+        }
+    }
+}
+
+/// Candles per trading day, as returned by a provider range fetch.
+pub type DayCandles = Vec<(NaiveDate, Vec<Candle>)>;
+
+/// Ordered market data providers, each with its own rate limit, retry/backoff
+/// and circuit breaker. Shared by every loop for the daemon's lifetime so the
+/// limits and breaker state carry across refresh cycles.
+pub struct ProviderChain {
+    providers: Vec<ManagedProvider>,
+    /// Serializes backfills so concurrent schedules don't fetch the same days
+    /// or race on a symbol's day log.
+    backfill: tokio::sync::Mutex<()>,
+}
+
+impl ProviderChain {
+    /// Build the chain from `market_data.provider`. Providers that can't be
+    /// created (e.g. Alpaca without credentials) are logged and left out.
+    pub fn from_config(config: &MarketDataConfig) -> Self {
+        let providers = config
+            .provider
+            .iter()
+            .filter_map(|s| match create_provider(&s.kind, config) {
+                Ok(provider) => Some((provider, s.clone())),
+                Err(e) => {
+                    tracing::warn!(provider = ?s.kind, error = %e, "Skipping provider");
+                    None
+                }
+            })
+            .collect();
+        Self::new(providers)
+    }
+
+    pub fn new(providers: Vec<(Box<dyn CandleProvider>, ProviderSettings)>) -> Self {
+        Self {
+            providers: providers
+                .into_iter()
+                .map(|(provider, settings)| ManagedProvider::new(provider, settings))
+                .collect(),
+            backfill: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.providers.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.providers.len()
+    }
+
+    /// Provider names in fallback order.
+    pub fn names(&self) -> Vec<&str> {
+        self.providers.iter().map(|p| p.provider.name()).collect()
+    }
+
+    /// Held while filling a symbol's missing data.
+    pub(crate) async fn backfill_guard(&self) -> tokio::sync::MutexGuard<'_, ()> {
+        self.backfill.lock().await
+    }
+
+    /// Providers whose circuit currently allows requests, in fallback order.
+    pub(crate) fn available(&self) -> impl Iterator<Item = &ManagedProvider> {
+        self.providers.iter().filter(|p| p.is_available())
+    }
+}
+
+pub(crate) struct ManagedProvider {
+    provider: Box<dyn CandleProvider>,
+    settings: ProviderSettings,
+    /// Earliest time the next request may start (rate limit).
+    next_request: Mutex<Option<Instant>>,
+    breaker: Mutex<Breaker>,
+}
+
+#[derive(Default)]
+struct Breaker {
+    consecutive_failures: u32,
+    open_until: Option<Instant>,
+}
+
+impl ManagedProvider {
+    fn new(provider: Box<dyn CandleProvider>, settings: ProviderSettings) -> Self {
+        Self {
+            provider,
+            settings,
+            next_request: Mutex::new(None),
+            breaker: Mutex::new(Breaker::default()),
+        }
+    }
+
+    pub(crate) fn name(&self) -> &str {
+        self.provider.name()
+    }
+
+    /// Whether a day this provider returned no data for will stay empty.
+    /// Not for the files provider: files can be dropped into its directory later.
+    pub(crate) fn empty_is_final(&self) -> bool {
+        self.settings.kind != ProviderKind::Files
+    }
+
+    /// False while the circuit is open. Once the cooldown has passed the
+    /// provider is tried again; one more failure reopens it.
+    fn is_available(&self) -> bool {
+        let breaker = self.breaker.lock().unwrap_or_else(|e| e.into_inner());
+        breaker
+            .open_until
+            .is_none_or(|until| Instant::now() >= until)
+    }
+
+    /// Fetch a date range, waiting for the rate limit and retrying with
+    /// exponential backoff. A final failure counts toward the circuit breaker.
+    pub(crate) async fn fetch_range(
+        &self,
+        symbol: &str,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<DayCandles, LoaderError> {
+        let mut last_error = String::new();
+        for attempt in 0..=self.settings.max_retries {
+            if attempt > 0 {
+                let backoff = self
+                    .settings
+                    .backoff_ms
+                    .saturating_mul(1 << (attempt - 1).min(16));
+                tokio::time::sleep(Duration::from_millis(backoff)).await;
+            }
+            self.throttle().await;
+            match self.provider.fetch_candles_range(symbol, start, end).await {
+                Ok(days) => {
+                    self.record_success();
+                    return Ok(days);
+                }
+                Err(e) => {
+                    tracing::debug!(provider = self.name(), symbol, attempt, error = %e, "Provider fetch failed");
+                    last_error = e.to_string();
+                }
+            }
+        }
+        self.record_failure();
+        Err(LoaderError::Provider(format!(
+            "{} {symbol}: {last_error}",
+            self.name()
+        )))
+    }
+
+    /// Wait until the next request slot under `requests_per_minute`.
+    async fn throttle(&self) {
+        let Some(rpm) = self.settings.requests_per_minute.filter(|r| *r > 0) else {
+            return;
+        };
+        let interval = Duration::from_secs(60) / rpm;
+        let wait = {
+            let mut next = self.next_request.lock().unwrap_or_else(|e| e.into_inner());
+            let now = Instant::now();
+            let start = next.map_or(now, |n| n.max(now));
+            *next = Some(start + interval);
+            start - now
+        };
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    fn record_success(&self) {
+        let mut breaker = self.breaker.lock().unwrap_or_else(|e| e.into_inner());
+        *breaker = Breaker::default();
+    }
+
+    fn record_failure(&self) {
+        let mut breaker = self.breaker.lock().unwrap_or_else(|e| e.into_inner());
+        breaker.consecutive_failures += 1;
+        if breaker.consecutive_failures >= self.settings.failure_threshold.max(1) {
+            let cooldown = Duration::from_secs(self.settings.cooldown_seconds);
+            breaker.open_until = Some(Instant::now() + cooldown);
+            tracing::warn!(
+                provider = self.name(),
+                failures = breaker.consecutive_failures,
+                cooldown_seconds = self.settings.cooldown_seconds,
+                "Provider circuit opened"
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::FilesProviderConfig;
+    use async_trait::async_trait;
+    use market_data_providers::error::ProviderError;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    /// Fails the first `failures` calls, then returns one empty day.
+    struct Flaky {
+        failures: usize,
+        calls: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl CandleProvider for Flaky {
+        fn name(&self) -> &str {
+            "flaky"
+        }
+
+        async fn fetch_candles(
+            &self,
+            _symbol: &str,
+            _date: NaiveDate,
+        ) -> Result<Vec<Candle>, ProviderError> {
+            let call = self.calls.fetch_add(1, Ordering::SeqCst);
+            if call < self.failures {
+                Err(ProviderError::Http("unavailable".to_string()))
+            } else {
+                Ok(vec![])
+            }
+        }
+    }
+
+    fn flaky(failures: usize, settings: ProviderSettings) -> (ProviderChain, Arc<AtomicUsize>) {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let provider = Flaky {
+            failures,
+            calls: calls.clone(),
+        };
+        (
+            ProviderChain::new(vec![(Box::new(provider), settings)]),
+            calls,
+        )
+    }
+
+    fn settings(max_retries: u32, failure_threshold: u32) -> ProviderSettings {
+        ProviderSettings {
+            max_retries,
+            backoff_ms: 1,
+            failure_threshold,
+            ..ProviderSettings::new(ProviderKind::Yahoo)
+        }
+    }
+
+    fn day() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 1, 13).unwrap()
+    }
+
+    #[tokio::test]
+    async fn retries_until_success() {
+        let (chain, calls) = flaky(2, settings(2, 3));
+        let provider = chain.available().next().unwrap();
+        assert!(provider.fetch_range("AAPL", day(), day()).await.is_ok());
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn circuit_opens_after_threshold() {
+        let (chain, calls) = flaky(usize::MAX, settings(0, 2));
+        for _ in 0..2 {
+            let provider = chain.available().next().unwrap();
+            assert!(provider.fetch_range("AAPL", day(), day()).await.is_err());
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert!(chain.available().next().is_none());
+    }
+
+    #[tokio::test]
+    async fn circuit_closes_after_cooldown() {
+        let (chain, _) = flaky(
+            1,
+            ProviderSettings {
+                cooldown_seconds: 0,
+                ..settings(0, 1)
+            },
+        );
+        let provider = chain.available().next().unwrap();
+        assert!(provider.fetch_range("AAPL", day(), day()).await.is_err());
+        let provider = chain.available().next().unwrap();
+        assert!(provider.fetch_range("AAPL", day(), day()).await.is_ok());
+        assert_eq!(provider.breaker.lock().unwrap().consecutive_failures, 0);
+    }
+
+    #[tokio::test]
+    async fn rate_limit_spaces_requests() {
+        let (chain, calls) = flaky(
+            0,
+            ProviderSettings {
+                requests_per_minute: Some(1200),
+                ..settings(0, 1)
+            },
+        );
+        let provider = chain.available().next().unwrap();
+        let started = Instant::now();
+        for _ in 0..3 {
+            provider.fetch_range("AAPL", day(), day()).await.unwrap();
+        }
+        // 1200/min = one request per 50ms; the first goes immediately.
+        assert!(started.elapsed() >= Duration::from_millis(100));
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    fn market_data(provider: Vec<ProviderSettings>) -> MarketDataConfig {
+        let mut config: MarketDataConfig =
+            toml::from_str("data_path = \"/data\"\nsymbols = []").unwrap();
+        config.provider = provider;
+        config
+    }
+
+    #[test]
+    fn create_provider_yahoo_default() {
+        let provider = create_provider(&ProviderKind::Yahoo, &market_data(vec![])).unwrap();
+        assert_eq!(provider.name(), "yahoo");
+    }
+
+    #[test]
+    fn create_provider_alpaca_fails_without_env() {
+        // Alpaca requires ALPACA_API_KEY_ID and ALPACA_API_SECRET_KEY
+        let result = create_provider(&ProviderKind::Alpaca, &market_data(vec![]));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn create_provider_files_requires_config() {
+        let mut config = market_data(vec![]);
+        assert!(create_provider(&ProviderKind::Files, &config).is_err());
+        config.files = Some(FilesProviderConfig::new("/imports"));
+        let provider = create_provider(&ProviderKind::Files, &config).unwrap();
+        assert_eq!(provider.name(), "files");
+    }
+
+    #[test]
+    fn chain_skips_providers_that_cannot_be_created() {
+        let chain = ProviderChain::from_config(&market_data(vec![
+            ProviderSettings::new(ProviderKind::Alpaca),
+            ProviderSettings::new(ProviderKind::Yahoo),
+        ]));
+        assert_eq!(chain.names(), vec!["yahoo"]);
+    }
+}
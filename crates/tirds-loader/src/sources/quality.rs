@@ -0,0 +1,693 @@
+use std::collections::{BTreeMap, HashSet};
+
+use chrono::{DateTime, Duration, NaiveTime, Utc};
+use chrono_tz::America::New_York;
+use market_data_core::candle::Candle as MdCandle;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use tirds_models::cache_schema::{key_patterns, CacheRow};
+use tirds_models::quality::{DataQuality, QualityIssue, QualityIssueKind, QualityResolution};
+
+use crate::config::{QualityAction, QualityConfig};
+
+/// Spacing of the loader's base candles.
+const BAR_MINUTES: i64 = 5;
+
+/// Forward split ratios checked against overnight moves (reverse splits use
+/// the reciprocals).
+const SPLIT_RATIOS: [u32; 6] = [2, 3, 4, 5, 10, 20];
+
+/// Minimum overnight move, as a fraction of price, before a split is considered.
+/// Keeps ordinary gaps from matching the 2:1 ratio's tolerance band.
+const MIN_SPLIT_MOVE: f64 = 0.4;
+
+/// Validate a symbol's 5-minute candles and apply `config.action`.
+///
+/// Checks run in order: ordering and duplicate timestamps, OHLC consistency,
+/// suspected splits between sessions, ATR spikes, zero volume, and intraday gaps.
+/// Returns the candles to cache and the quality summary, or None for no candles.
+pub fn check_candles(
+    candles: &[MdCandle],
+    config: &QualityConfig,
+) -> Option<(Vec<MdCandle>, DataQuality)> {
+    let as_of = candles.iter().map(|c| c.timestamp).max()?;
+    let mut report = Report::default();
+
+    let bars = check_order(candles.to_vec(), config.action, &mut report);
+    let bars = check_ohlc(bars, config.action, &mut report);
+    let bars = check_splits(bars, config, &mut report);
+    let bars = check_spikes(bars, config, &mut report);
+    let bars = check_volume(bars, config.action, &mut report);
+    check_gaps(&bars, &mut report);
+
+    let quality = report.finish(as_of, candles.len(), config.max_issues);
+    Some((bars, quality))
+}
+
+#[derive(Default)]
+struct Report {
+    issues: Vec<QualityIssue>,
+    dropped: usize,
+    repaired: usize,
+    missing: usize,
+}
+
+impl Report {
+    fn record(
+        &mut self,
+        kind: QualityIssueKind,
+        timestamp: DateTime<Utc>,
+        resolution: QualityResolution,
+        detail: String,
+    ) {
+        match resolution {
+            QualityResolution::Dropped => self.dropped += 1,
+            QualityResolution::Repaired => self.repaired += 1,
+            QualityResolution::Labeled => {}
+        }
+        self.issues.push(QualityIssue {
+            kind,
+            timestamp,
+            resolution,
+            detail,
+        });
+    }
+
+    fn finish(mut self, as_of: DateTime<Utc>, checked: usize, max_issues: usize) -> DataQuality {
+        let mut counts = BTreeMap::new();
+        for issue in &self.issues {
+            *counts.entry(issue.kind).or_insert(0) += 1;
+        }
+
+        let affected: HashSet<DateTime<Utc>> = self
+            .issues
+            .iter()
+            .filter(|i| i.kind != QualityIssueKind::Gap)
+            .map(|i| i.timestamp)
+            .collect();
+        let expected = checked + self.missing;
+        let clean = expected.saturating_sub(affected.len() + self.missing);
+        let mut score = if expected == 0 {
+            0.0
+        } else {
+            clean as f64 / expected as f64
+        };
+        let unadjusted_split = self.issues.iter().any(|i| {
+            i.kind == QualityIssueKind::SuspectedSplit && i.resolution == QualityResolution::Labeled
+        });
+        if unadjusted_split {
+            score = score.min(0.5);
+        }
+
+        self.issues.sort_by_key(|i| i.timestamp);
+        self.issues
+            .drain(..self.issues.len().saturating_sub(max_issues));
+
+        DataQuality {
+            as_of,
+            bars_checked: checked,
+            bars_dropped: self.dropped,
+            bars_repaired: self.repaired,
+            bars_missing: self.missing,
+            score: (score * 1000.0).round() / 1000.0,
+            counts,
+            issues: self.issues,
+        }
+    }
+}
+
+/// Out-of-order bars are sorted and duplicate timestamps keep the last bar
+/// read, unless only labeling.
+fn check_order(bars: Vec<MdCandle>, action: QualityAction, report: &mut Report) -> Vec<MdCandle> {
+    for pair in bars.windows(2) {
+        if pair[1].timestamp < pair[0].timestamp {
+            let resolution = match action {
+                QualityAction::Label => QualityResolution::Labeled,
+                _ => QualityResolution::Repaired,
+            };
+            report.record(
+                QualityIssueKind::OutOfOrder,
+                pair[1].timestamp,
+                resolution,
+                format!("follows bar at {}", pair[0].timestamp.to_rfc3339()),
+            );
+        }
+    }
+
+    if action == QualityAction::Label {
+        let mut seen = HashSet::new();
+        for bar in &bars {
+            if !seen.insert(bar.timestamp) {
+                report.record(
+                    QualityIssueKind::DuplicateTimestamp,
+                    bar.timestamp,
+                    QualityResolution::Labeled,
+                    "timestamp repeated".to_string(),
+                );
+            }
+        }
+        return bars;
+    }
+
+    let mut sorted = bars;
+    sorted.sort_by_key(|c| c.timestamp);
+    let mut out: Vec<MdCandle> = Vec::with_capacity(sorted.len());
+    for bar in sorted {
+        if let Some(last) = out.last_mut() {
+            if last.timestamp == bar.timestamp {
+                report.record(
+                    QualityIssueKind::DuplicateTimestamp,
+                    bar.timestamp,
+                    QualityResolution::Dropped,
+                    "earlier bar with the same timestamp replaced".to_string(),
+                );
+                *last = bar;
+                continue;
+            }
+        }
+        out.push(bar);
+    }
+    out
+}
+
+/// Non-positive prices can't be repaired and are dropped; a high/low that
+/// doesn't contain open and close is widened to do so.
+fn check_ohlc(bars: Vec<MdCandle>, action: QualityAction, report: &mut Report) -> Vec<MdCandle> {
+    let mut out = Vec::with_capacity(bars.len());
+    for mut bar in bars {
+        let prices = [bar.open, bar.high, bar.low, bar.close];
+        if prices.iter().any(|p| *p <= Decimal::ZERO) {
+            let resolution = match action {
+                QualityAction::Label => QualityResolution::Labeled,
+                _ => QualityResolution::Dropped,
+            };
+            report.record(
+                QualityIssueKind::InvalidOhlc,
+                bar.timestamp,
+                resolution,
+                "non-positive price".to_string(),
+            );
+            if resolution == QualityResolution::Dropped {
+                continue;
+            }
+        } else {
+            let high = prices.iter().copied().max().unwrap_or(bar.high);
+            let low = prices.iter().copied().min().unwrap_or(bar.low);
+            if high != bar.high || low != bar.low {
+                let detail = format!(
+                    "high {} / low {} do not contain open {} and close {}",
+                    bar.high, bar.low, bar.open, bar.close
+                );
+                let resolution = match action {
+                    QualityAction::Label => QualityResolution::Labeled,
+                    QualityAction::Repair => QualityResolution::Repaired,
+                    QualityAction::Drop => QualityResolution::Dropped,
+                };
+                report.record(
+                    QualityIssueKind::InvalidOhlc,
+                    bar.timestamp,
+                    resolution,
+                    detail,
+                );
+                match resolution {
+                    QualityResolution::Dropped => continue,
+                    QualityResolution::Repaired => {
+                        bar.high = high;
+                        bar.low = low;
+                    }
+                    QualityResolution::Labeled => {}
+                }
+            }
+        }
+        out.push(bar);
+    }
+    out
+}
+
+/// New York calendar date of a bar.
+fn session_date(timestamp: DateTime<Utc>) -> chrono::NaiveDate {
+    timestamp.with_timezone(&New_York).date_naive()
+}
+
+/// The split factor matching an overnight move from `prev_close` to `open`:
+/// `Some((ratio, true))` for an r:1 split, `Some((ratio, false))` for 1:r.
+/// Non-positive prices never match.
+fn split_ratio(prev_close: Decimal, open: Decimal, tolerance: f64) -> Option<(u32, bool)> {
+    if prev_close <= Decimal::ZERO || open <= Decimal::ZERO {
+        return None;
+    }
+    let change = prev_close.checked_div(open)?.to_f64()?;
+    if (change - 1.0).abs() < MIN_SPLIT_MOVE && (1.0 / change - 1.0).abs() < MIN_SPLIT_MOVE {
+        return None;
+    }
+    SPLIT_RATIOS.iter().find_map(|&r| {
+        let r_f = r as f64;
+        if (change / r_f - 1.0).abs() <= tolerance {
+            Some((r, true))
+        } else if (change * r_f - 1.0).abs() <= tolerance {
+            Some((r, false))
+        } else {
+            None
+        }
+    })
+}
+
+/// Repair back-adjusts bars before a suspected split to the post-split basis;
+/// drop discards them.
+fn check_splits(
+    mut bars: Vec<MdCandle>,
+    config: &QualityConfig,
+    report: &mut Report,
+) -> Vec<MdCandle> {
+    let mut i = 1;
+    while i < bars.len() {
+        let (prev, bar) = (&bars[i - 1], &bars[i]);
+        let new_session = session_date(prev.timestamp) != session_date(bar.timestamp);
+        let ratio = new_session
+            .then(|| split_ratio(prev.close, bar.open, config.split_tolerance))
+            .flatten();
+        let Some((ratio, forward)) = ratio else {
+            i += 1;
+            continue;
+        };
+
+        let detail = format!(
+            "close {} then open {} (~{})",
+            prev.close,
+            bar.open,
+            if forward {
+                format!("{ratio}:1 split")
+            } else {
+                format!("1:{ratio} reverse split")
+            }
+        );
+        let timestamp = bar.timestamp;
+        match config.action {
+            QualityAction::Label => {
+                report.record(
+                    QualityIssueKind::SuspectedSplit,
+                    timestamp,
+                    QualityResolution::Labeled,
+                    detail,
+                );
+                i += 1;
+            }
+            QualityAction::Repair => {
+                let r = Decimal::from(ratio);
+                for earlier in &mut bars[..i] {
+                    for price in [
+                        &mut earlier.open,
+                        &mut earlier.high,
+                        &mut earlier.low,
+                        &mut earlier.close,
+                    ] {
+                        *price = if forward { *price / r } else { *price * r };
+                    }
+                    earlier.volume = if forward {
+                        earlier.volume.saturating_mul(ratio as u64)
+                    } else {
+                        earlier.volume / ratio as u64
+                    };
+                }
+                report.record(
+                    QualityIssueKind::SuspectedSplit,
+                    timestamp,
+                    QualityResolution::Repaired,
+                    format!("{detail}; {i} earlier bars adjusted"),
+                );
+                i += 1;
+            }
+            QualityAction::Drop => {
+                report.record(
+                    QualityIssueKind::SuspectedSplit,
+                    timestamp,
+                    QualityResolution::Dropped,
+                    format!("{detail}; {i} earlier bars dropped"),
+                );
+                report.dropped += i - 1;
+                bars.drain(..i);
+                i = 1;
+            }
+        }
+    }
+    bars
+}
+
+/// Average true range of `bars` (each compared with the bar before it).
+fn atr(bars: &[MdCandle]) -> Option<Decimal> {
+    if bars.len() < 2 {
+        return None;
+    }
+    let sum: Decimal = bars
+        .windows(2)
+        .map(|w| {
+            let prev_close = w[0].close;
+            let bar = &w[1];
+            (bar.high - bar.low)
+                .max((bar.high - prev_close).abs())
+                .max((bar.low - prev_close).abs())
+        })
+        .sum();
+    Some(sum / Decimal::from(bars.len() - 1))
+}
+
+/// A close beyond `spike_atr_multiple` ATRs from the previous close is treated
+/// as a bad print and dropped; a wick that far out is clamped to the bar body.
+fn check_spikes(bars: Vec<MdCandle>, config: &QualityConfig, report: &mut Report) -> Vec<MdCandle> {
+    let Ok(multiple) = Decimal::try_from(config.spike_atr_multiple) else {
+        return bars;
+    };
+    let period = config.atr_period.max(1);
+    let mut out: Vec<MdCandle> = Vec::with_capacity(bars.len());
+
+    for mut bar in bars {
+        let window = &out[out.len().saturating_sub(period + 1)..];
+        let limit = (window.len() > period)
+            .then(|| atr(window))
+            .flatten()
+            .filter(|a| *a > Decimal::ZERO)
+            .map(|a| a * multiple);
+        let (Some(limit), Some(prev)) = (limit, out.last()) else {
+            out.push(bar);
+            continue;
+        };
+        let prev_close = prev.close;
+
+        if (bar.close - prev_close).abs() > limit {
+            let resolution = match config.action {
+                QualityAction::Label => QualityResolution::Labeled,
+                _ => QualityResolution::Dropped,
+            };
+            report.record(
+                QualityIssueKind::Spike,
+                bar.timestamp,
+                resolution,
+                format!("close {} vs previous close {prev_close}", bar.close),
+            );
+            if resolution == QualityResolution::Labeled {
+                out.push(bar);
+            }
+            continue;
+        }
+
+        if bar.high - prev_close > limit || prev_close - bar.low > limit {
+            let detail = format!(
+                "wick {}-{} vs previous close {prev_close}",
+                bar.low, bar.high
+            );
+            let resolution = match config.action {
+                QualityAction::Label => QualityResolution::Labeled,
+                QualityAction::Repair => QualityResolution::Repaired,
+                QualityAction::Drop => QualityResolution::Dropped,
+            };
+            report.record(QualityIssueKind::Spike, bar.timestamp, resolution, detail);
+            match resolution {
+                QualityResolution::Dropped => continue,
+                QualityResolution::Repaired => {
+                    bar.high = bar.open.max(bar.close);
+                    bar.low = bar.open.min(bar.close);
+                }
+                QualityResolution::Labeled => {}
+            }
+        }
+        out.push(bar);
+    }
+    out
+}
+
+/// Zero-volume bars can't be repaired; they are labeled, or dropped in drop mode.
+fn check_volume(bars: Vec<MdCandle>, action: QualityAction, report: &mut Report) -> Vec<MdCandle> {
+    let mut out = Vec::with_capacity(bars.len());
+    for bar in bars {
+        if bar.volume == 0 {
+            let resolution = match action {
+                QualityAction::Drop => QualityResolution::Dropped,
+                _ => QualityResolution::Labeled,
+            };
+            report.record(
+                QualityIssueKind::ZeroVolume,
+                bar.timestamp,
+                resolution,
+                "volume is 0".to_string(),
+            );
+            if resolution == QualityResolution::Dropped {
+                continue;
+            }
+        }
+        out.push(bar);
+    }
+    out
+}
+
+fn in_regular_session(timestamp: DateTime<Utc>) -> bool {
+    let time = timestamp.with_timezone(&New_York).time();
+    let open = NaiveTime::from_hms_opt(9, 30, 0).unwrap_or_default();
+    let close = NaiveTime::from_hms_opt(16, 0, 0).unwrap_or_default();
+    time >= open && time < close
+}
+
+/// Missing 5-minute bars between two regular-session bars on the same day.
+/// Gaps are only labeled; nothing is synthesized.
+fn check_gaps(bars: &[MdCandle], report: &mut Report) {
+    let step = Duration::minutes(BAR_MINUTES);
+    for pair in bars.windows(2) {
+        let (prev, bar) = (&pair[0], &pair[1]);
+        if session_date(prev.timestamp) != session_date(bar.timestamp)
+            || !in_regular_session(prev.timestamp)
+            || !in_regular_session(bar.timestamp)
+        {
+            continue;
+        }
+        let delta = bar.timestamp - prev.timestamp;
+        if delta > step {
+            let missing = (delta.num_minutes() / BAR_MINUTES - 1).max(0) as usize;
+            report.missing += missing;
+            report.record(
+                QualityIssueKind::Gap,
+                bar.timestamp,
+                QualityResolution::Labeled,
+                format!(
+                    "{missing} bars missing after {}",
+                    prev.timestamp.to_rfc3339()
+                ),
+            );
+        }
+    }
+}
+
+pub fn quality_to_cache_row(symbol: &str, quality: &DataQuality, ttl_seconds: u64) -> CacheRow {
+    let now = Utc::now();
+    CacheRow {
+        key: key_patterns::quality(symbol),
+        category: "indicator".to_string(),
+        value_json: serde_json::to_string(quality).unwrap_or_default(),
+        source: "tirds-loader:quality".to_string(),
+        symbol: Some(symbol.to_string()),
+        created_at: now.to_rfc3339(),
+        expires_at: (now + Duration::seconds(ttl_seconds as i64)).to_rfc3339(),
+        updated_at: now.to_rfc3339(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use rust_decimal_macros::dec;
+
+    /// 09:30 ET on 2024-01-16.
+    fn open_time() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 1, 16, 14, 30, 0).unwrap()
+    }
+
+    fn candle(timestamp: DateTime<Utc>, close: Decimal) -> MdCandle {
+        MdCandle {
+            timestamp,
+            open: close,
+            high: close + dec!(0.5),
+            low: close - dec!(0.5),
+            close,
+            volume: 1000,
+        }
+    }
+
+    /// Clean 5-minute bars starting at the open, closes alternating around 100.
+    fn clean_bars(count: usize) -> Vec<MdCandle> {
+        (0..count)
+            .map(|i| {
+                let close = if i % 2 == 0 { dec!(100) } else { dec!(100.4) };
+                candle(open_time() + Duration::minutes(i as i64 * 5), close)
+            })
+            .collect()
+    }
+
+    fn config(action: QualityAction) -> QualityConfig {
+        QualityConfig {
+            action,
+            ..QualityConfig::default()
+        }
+    }
+
+    #[test]
+    fn clean_data_scores_one() {
+        let bars = clean_bars(30);
+        let (out, quality) = check_candles(&bars, &QualityConfig::default()).unwrap();
+        assert_eq!(out, bars);
+        assert!(quality.is_clean());
+        assert_eq!(quality.score, 1.0);
+        assert_eq!(quality.bars_checked, 30);
+    }
+
+    #[test]
+    fn empty_input_has_no_report() {
+        assert!(check_candles(&[], &QualityConfig::default()).is_none());
+    }
+
+    #[test]
+    fn repair_sorts_and_dedupes() {
+        let mut bars = clean_bars(5);
+        bars.swap(1, 2);
+        let mut dup = bars[4].clone();
+        dup.close = dec!(100.2);
+        bars.push(dup);
+
+        let (out, quality) = check_candles(&bars, &config(QualityAction::Repair)).unwrap();
+        assert_eq!(out.len(), 5);
+        assert!(out.windows(2).all(|w| w[0].timestamp < w[1].timestamp));
+        assert_eq!(out[4].close, dec!(100.2));
+        assert_eq!(quality.counts[&QualityIssueKind::OutOfOrder], 1);
+        assert_eq!(quality.counts[&QualityIssueKind::DuplicateTimestamp], 1);
+        assert_eq!(quality.bars_dropped, 1);
+    }
+
+    #[test]
+    fn label_leaves_bars_untouched() {
+        let mut bars = clean_bars(5);
+        bars[2].high = dec!(99);
+        bars[3].volume = 0;
+        let (out, quality) = check_candles(&bars, &config(QualityAction::Label)).unwrap();
+        assert_eq!(out, bars);
+        assert_eq!(quality.counts[&QualityIssueKind::InvalidOhlc], 1);
+        assert_eq!(quality.counts[&QualityIssueKind::ZeroVolume], 1);
+        assert_eq!(quality.bars_repaired + quality.bars_dropped, 0);
+        assert_eq!(quality.score, 0.6);
+    }
+
+    #[test]
+    fn repair_widens_invalid_high_low_and_drops_bad_prices() {
+        let mut bars = clean_bars(5);
+        bars[1].high = dec!(99);
+        bars[3].low = dec!(0);
+        let (out, quality) = check_candles(&bars, &config(QualityAction::Repair)).unwrap();
+        assert_eq!(out.len(), 4);
+        assert_eq!(out[1].high, dec!(100.4));
+        assert_eq!(quality.bars_repaired, 1);
+        assert_eq!(quality.bars_dropped, 1);
+    }
+
+    #[test]
+    fn close_spike_dropped_and_wick_clamped() {
+        let mut bars = clean_bars(40);
+        bars[20].close = dec!(150);
+        bars[30].high = dec!(140);
+        let (out, quality) = check_candles(&bars, &config(QualityAction::Repair)).unwrap();
+        assert_eq!(out.len(), 39);
+        assert!(out.iter().all(|c| c.close < dec!(101)));
+        let clamped = out
+            .iter()
+            .find(|c| c.timestamp == bars[30].timestamp)
+            .unwrap();
+        assert_eq!(clamped.high, clamped.close.max(clamped.open));
+        assert_eq!(quality.counts[&QualityIssueKind::Spike], 2);
+    }
+
+    #[test]
+    fn forward_split_back_adjusted() {
+        let mut bars = clean_bars(3);
+        let next_day = open_time() + Duration::days(1);
+        bars.extend((0..3).map(|i| candle(next_day + Duration::minutes(i * 5), dec!(50))));
+
+        let (out, quality) = check_candles(&bars, &config(QualityAction::Repair)).unwrap();
+        assert_eq!(out[0].close, dec!(50));
+        assert_eq!(out[1].close, dec!(50.2));
+        assert_eq!(out[0].volume, 2000);
+        assert_eq!(quality.counts[&QualityIssueKind::SuspectedSplit], 1);
+        assert_eq!(quality.bars_repaired, 1);
+    }
+
+    #[test]
+    fn labeled_split_caps_score_and_drop_discards_history() {
+        let mut bars = clean_bars(3);
+        let next_day = open_time() + Duration::days(1);
+        bars.extend((0..3).map(|i| candle(next_day + Duration::minutes(i * 5), dec!(300))));
+
+        let (_, labeled) = check_candles(&bars, &config(QualityAction::Label)).unwrap();
+        assert_eq!(labeled.score, 0.5);
+        assert!(labeled.issues[0].detail.contains("1:3 reverse split"));
+
+        let (out, dropped) = check_candles(&bars, &config(QualityAction::Drop)).unwrap();
+        assert_eq!(out.len(), 3);
+        assert_eq!(dropped.bars_dropped, 3);
+        assert!(out.iter().all(|c| c.close == dec!(300)));
+    }
+
+    #[test]
+    fn ordinary_overnight_gap_is_not_a_split() {
+        assert_eq!(split_ratio(dec!(100), dec!(90), 0.03), None);
+        assert_eq!(split_ratio(dec!(100), dec!(49.5), 0.03), Some((2, true)));
+        assert_eq!(split_ratio(dec!(10), dec!(100), 0.03), Some((10, false)));
+        assert_eq!(split_ratio(dec!(100), dec!(0), 0.03), None);
+        assert_eq!(split_ratio(dec!(0), dec!(100), 0.03), None);
+    }
+
+    #[test]
+    fn label_tolerates_zero_open_after_overnight() {
+        let mut bars = clean_bars(3);
+        let next_day = open_time() + Duration::days(1);
+        bars.extend((0..3).map(|i| candle(next_day + Duration::minutes(i * 5), dec!(100))));
+        bars[3].open = dec!(0);
+
+        let (out, quality) = check_candles(&bars, &config(QualityAction::Label)).unwrap();
+        assert_eq!(out, bars);
+        assert!(!quality
+            .counts
+            .contains_key(&QualityIssueKind::SuspectedSplit));
+    }
+
+    #[test]
+    fn intraday_gaps_counted_as_missing() {
+        let mut bars = clean_bars(10);
+        bars.drain(3..6);
+        let (out, quality) = check_candles(&bars, &QualityConfig::default()).unwrap();
+        assert_eq!(out.len(), 7);
+        assert_eq!(quality.bars_missing, 3);
+        assert_eq!(quality.counts[&QualityIssueKind::Gap], 1);
+        assert_eq!(quality.score, 0.7);
+    }
+
+    #[test]
+    fn issues_capped_to_most_recent() {
+        let mut bars = clean_bars(10);
+        for bar in &mut bars {
+            bar.volume = 0;
+        }
+        let cfg = QualityConfig {
+            max_issues: 3,
+            ..QualityConfig::default()
+        };
+        let (_, quality) = check_candles(&bars, &cfg).unwrap();
+        assert_eq!(quality.counts[&QualityIssueKind::ZeroVolume], 10);
+        assert_eq!(quality.issues.len(), 3);
+        assert_eq!(quality.issues[2].timestamp, bars[9].timestamp);
+    }
+
+    #[test]
+    fn quality_cache_row() {
+        let (_, quality) = check_candles(&clean_bars(3), &QualityConfig::default()).unwrap();
+        let row = quality_to_cache_row("AAPL", &quality, 300);
+        assert_eq!(row.key, "quality:AAPL");
+        assert_eq!(row.symbol.as_deref(), Some("AAPL"));
+        let parsed: DataQuality = serde_json::from_str(&row.value_json).unwrap();
+        assert_eq!(parsed, quality);
+    }
+}
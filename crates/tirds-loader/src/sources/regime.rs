@@ -0,0 +1,427 @@
+use std::collections::HashMap;
+
+use chrono::{Duration, Utc};
+use market_data_core::candle::Candle as MdCandle;
+use rust_decimal::prelude::ToPrimitive;
+use serde::Serialize;
+use tirds_models::cache_schema::{key_patterns, CacheRow};
+use tirds_models::regime::{
+    BreadthInputs, BreadthRegime, MarketRegime, RiskRegime, SymbolRegime, TrendInputs, TrendRegime,
+    VolatilityInputs, VolatilityRegime, MARKET_SCOPE,
+};
+
+use crate::config::RegimeConfig;
+use crate::error::LoaderError;
+use crate::sources::resample::resample;
+use crate::write_queue::CacheWriter;
+
+/// Breadth is strong at or above this share of sector ETFs above trend, weak at or below its complement.
+const STRONG_BREADTH: f64 = 0.7;
+
+/// (close, high, low) per bar in the regime timeframe, oldest first.
+fn bars(candles: &[MdCandle], config: &RegimeConfig) -> Vec<(f64, f64, f64)> {
+    resample(candles, config.timeframe)
+        .iter()
+        .filter_map(|c| Some((c.close.to_f64()?, c.high.to_f64()?, c.low.to_f64()?)))
+        .collect()
+}
+
+fn sma(closes: &[f64], period: usize) -> Option<f64> {
+    if period == 0 || closes.len() < period {
+        return None;
+    }
+    Some(closes[closes.len() - period..].iter().sum::<f64>() / period as f64)
+}
+
+/// Classify the trend from fast/slow simple moving averages, treating choppy
+/// price action (low efficiency ratio) as a range regardless of the averages.
+pub fn classify_trend(closes: &[f64], config: &RegimeConfig) -> Option<(TrendRegime, TrendInputs)> {
+    let fast_sma = sma(closes, config.fast_period)?;
+    let slow_sma = sma(closes, config.slow_period)?;
+    let close = *closes.last()?;
+
+    let window = &closes[closes.len().checked_sub(config.slow_period + 1)?..];
+    let path: f64 = window.windows(2).map(|w| (w[1] - w[0]).abs()).sum();
+    let efficiency_ratio = if path > 0.0 {
+        (close - window[0]).abs() / path
+    } else {
+        0.0
+    };
+
+    let trend = if efficiency_ratio < config.range_efficiency {
+        TrendRegime::Range
+    } else if fast_sma > slow_sma && close > slow_sma {
+        TrendRegime::Uptrend
+    } else if fast_sma < slow_sma && close < slow_sma {
+        TrendRegime::Downtrend
+    } else {
+        TrendRegime::Range
+    };
+
+    Some((
+        trend,
+        TrendInputs {
+            close,
+            fast_sma,
+            slow_sma,
+            efficiency_ratio,
+        },
+    ))
+}
+
+/// Average true range over the last `period` bars as a percentage of the latest close.
+fn atr_percent(bars: &[(f64, f64, f64)], period: usize) -> Option<f64> {
+    if period == 0 || bars.len() < period + 1 {
+        return None;
+    }
+    let recent = &bars[bars.len() - period - 1..];
+    let atr = recent
+        .windows(2)
+        .map(|w| {
+            let (prev_close, _, _) = w[0];
+            let (_, high, low) = w[1];
+            (high - low)
+                .max((high - prev_close).abs())
+                .max((low - prev_close).abs())
+        })
+        .sum::<f64>()
+        / period as f64;
+    let close = recent.last()?.0;
+    (close > 0.0).then(|| atr / close * 100.0)
+}
+
+fn bucket(value: f64, thresholds: &[f64; 3]) -> VolatilityRegime {
+    if value < thresholds[0] {
+        VolatilityRegime::Low
+    } else if value < thresholds[1] {
+        VolatilityRegime::Normal
+    } else if value < thresholds[2] {
+        VolatilityRegime::Elevated
+    } else {
+        VolatilityRegime::Extreme
+    }
+}
+
+fn risk(
+    trend: TrendRegime,
+    volatility: VolatilityRegime,
+    breadth: Option<BreadthRegime>,
+) -> RiskRegime {
+    let weak_breadth = breadth == Some(BreadthRegime::Weak);
+    if trend == TrendRegime::Downtrend
+        || volatility == VolatilityRegime::Extreme
+        || (volatility == VolatilityRegime::Elevated && weak_breadth)
+    {
+        RiskRegime::RiskOff
+    } else if trend == TrendRegime::Uptrend
+        && volatility <= VolatilityRegime::Normal
+        && !weak_breadth
+    {
+        RiskRegime::RiskOn
+    } else {
+        RiskRegime::Neutral
+    }
+}
+
+fn breadth(
+    candle_data: &HashMap<String, Vec<MdCandle>>,
+    config: &RegimeConfig,
+) -> Option<(BreadthRegime, BreadthInputs)> {
+    let mut inputs = BreadthInputs {
+        above: Vec::new(),
+        below: Vec::new(),
+    };
+    for symbol in &config.breadth_symbols {
+        let Some(candles) = candle_data.get(symbol) else {
+            continue;
+        };
+        let closes: Vec<f64> = bars(candles, config).iter().map(|b| b.0).collect();
+        let (Some(slow), Some(close)) = (sma(&closes, config.slow_period), closes.last()) else {
+            continue;
+        };
+        if *close > slow {
+            inputs.above.push(symbol.clone());
+        } else {
+            inputs.below.push(symbol.clone());
+        }
+    }
+
+    let total = inputs.above.len() + inputs.below.len();
+    if total == 0 {
+        return None;
+    }
+    let share = inputs.above.len() as f64 / total as f64;
+    let regime = if share >= STRONG_BREADTH {
+        BreadthRegime::Strong
+    } else if share <= 1.0 - STRONG_BREADTH {
+        BreadthRegime::Weak
+    } else {
+        BreadthRegime::Mixed
+    };
+    Some((regime, inputs))
+}
+
+/// Classify the market from the benchmark's trend, the volatility index (or
+/// benchmark ATR when the index isn't loaded) and sector ETF breadth.
+pub fn classify_market(
+    candle_data: &HashMap<String, Vec<MdCandle>>,
+    config: &RegimeConfig,
+) -> Option<MarketRegime> {
+    let candles = candle_data.get(&config.benchmark)?;
+    let as_of = candles.last()?.timestamp;
+    let benchmark_bars = bars(candles, config);
+    let closes: Vec<f64> = benchmark_bars.iter().map(|b| b.0).collect();
+    let (trend, trend_inputs) = classify_trend(&closes, config)?;
+
+    let vix = candle_data
+        .get(&config.volatility_index)
+        .and_then(|c| c.last())
+        .and_then(|c| c.close.to_f64());
+    let atr_percent = atr_percent(&benchmark_bars, config.atr_period);
+    let volatility = match (vix, atr_percent) {
+        (Some(vix), _) => bucket(vix, &config.vix_thresholds),
+        (None, Some(atr)) => bucket(atr, &config.atr_percent_thresholds),
+        (None, None) => VolatilityRegime::Normal,
+    };
+
+    let (breadth, breadth_inputs) = match breadth(candle_data, config) {
+        Some((regime, inputs)) => (Some(regime), Some(inputs)),
+        None => (None, None),
+    };
+
+    Some(MarketRegime {
+        as_of,
+        benchmark: config.benchmark.clone(),
+        trend,
+        trend_inputs,
+        volatility,
+        volatility_inputs: VolatilityInputs { vix, atr_percent },
+        breadth,
+        breadth_inputs,
+        risk: risk(trend, volatility, breadth),
+    })
+}
+
+/// Classify one symbol's own trend and ATR volatility.
+pub fn classify_symbol(
+    symbol: &str,
+    candles: &[MdCandle],
+    config: &RegimeConfig,
+) -> Option<SymbolRegime> {
+    let as_of = candles.last()?.timestamp;
+    let symbol_bars = bars(candles, config);
+    let closes: Vec<f64> = symbol_bars.iter().map(|b| b.0).collect();
+    let (trend, trend_inputs) = classify_trend(&closes, config)?;
+    let atr_percent = atr_percent(&symbol_bars, config.atr_period)?;
+
+    Some(SymbolRegime {
+        as_of,
+        symbol: symbol.to_string(),
+        trend,
+        trend_inputs,
+        volatility: bucket(atr_percent, &config.atr_percent_thresholds),
+        volatility_inputs: VolatilityInputs {
+            vix: None,
+            atr_percent: Some(atr_percent),
+        },
+    })
+}
+
+fn regime_row(
+    scope: &str,
+    symbol: Option<&str>,
+    value: &impl Serialize,
+    ttl_seconds: u64,
+) -> CacheRow {
+    let now = Utc::now();
+    CacheRow {
+        key: key_patterns::regime(scope),
+        category: "indicator".to_string(),
+        value_json: serde_json::to_string(value).unwrap_or_default(),
+        source: "tirds-loader:regime".to_string(),
+        symbol: symbol.map(str::to_string),
+        created_at: now.to_rfc3339(),
+        expires_at: (now + Duration::seconds(ttl_seconds as i64)).to_rfc3339(),
+        updated_at: now.to_rfc3339(),
+    }
+}
+
+/// Classify and write `regime:market` (which has no symbol) and `regime:{symbol}`
+/// for each symbol. The market row is only written when `include_market` is set,
+/// so partial candle sets (e.g., on-demand requests) don't overwrite it.
+pub async fn refresh_regimes(
+    symbols: &[String],
+    candle_data: &HashMap<String, Vec<MdCandle>>,
+    config: &RegimeConfig,
+    ttl_seconds: u64,
+    include_market: bool,
+    writer: &CacheWriter,
+) -> Result<usize, LoaderError> {
+    if !config.enabled {
+        return Ok(0);
+    }
+
+    let mut rows = Vec::new();
+    if include_market {
+        match classify_market(candle_data, config) {
+            Some(market) => rows.push(regime_row(MARKET_SCOPE, None, &market, ttl_seconds)),
+            None => {
+                tracing::warn!(benchmark = %config.benchmark, "Not enough benchmark bars to classify market regime");
+            }
+        }
+    }
+    for symbol in symbols {
+        if let Some(regime) = candle_data
+            .get(symbol)
+            .and_then(|candles| classify_symbol(symbol, candles, config))
+        {
+            rows.push(regime_row(symbol, Some(symbol), &regime, ttl_seconds));
+        }
+    }
+
+    let count = rows.len();
+    writer.upsert_batch(rows).await?;
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use rust_decimal::Decimal;
+
+    fn config() -> RegimeConfig {
+        RegimeConfig {
+            timeframe: crate::config::Timeframe::FiveMinutes,
+            fast_period: 3,
+            slow_period: 6,
+            atr_period: 3,
+            ..RegimeConfig::default()
+        }
+    }
+
+    /// 5-minute candles with the given closes and a high/low `spread` around them.
+    fn candles(closes: &[f64], spread: f64) -> Vec<MdCandle> {
+        let base = Utc.with_ymd_and_hms(2024, 1, 16, 14, 30, 0).unwrap();
+        closes
+            .iter()
+            .enumerate()
+            .map(|(i, close)| MdCandle {
+                timestamp: base + Duration::minutes(i as i64 * 5),
+                open: Decimal::try_from(*close).unwrap(),
+                high: Decimal::try_from(close + spread).unwrap(),
+                low: Decimal::try_from(close - spread).unwrap(),
+                close: Decimal::try_from(*close).unwrap(),
+                volume: 100,
+            })
+            .collect()
+    }
+
+    fn rising() -> Vec<f64> {
+        (0..10).map(|i| 100.0 + i as f64).collect()
+    }
+
+    fn falling() -> Vec<f64> {
+        (0..10).map(|i| 100.0 - i as f64).collect()
+    }
+
+    #[test]
+    fn classifies_trends() {
+        let (trend, inputs) = classify_trend(&rising(), &config()).unwrap();
+        assert_eq!(trend, TrendRegime::Uptrend);
+        assert!((inputs.efficiency_ratio - 1.0).abs() < 1e-9);
+
+        let (trend, _) = classify_trend(&falling(), &config()).unwrap();
+        assert_eq!(trend, TrendRegime::Downtrend);
+
+        let chop = [100.0, 102.0, 100.0, 102.0, 100.0, 102.0, 100.0, 102.0];
+        let (trend, inputs) = classify_trend(&chop, &config()).unwrap();
+        assert_eq!(trend, TrendRegime::Range);
+        assert!(inputs.efficiency_ratio < 0.3);
+
+        assert!(classify_trend(&[1.0, 2.0], &config()).is_none());
+    }
+
+    #[test]
+    fn volatility_buckets() {
+        let t = [15.0, 20.0, 30.0];
+        assert_eq!(bucket(12.0, &t), VolatilityRegime::Low);
+        assert_eq!(bucket(15.0, &t), VolatilityRegime::Normal);
+        assert_eq!(bucket(25.0, &t), VolatilityRegime::Elevated);
+        assert_eq!(bucket(45.0, &t), VolatilityRegime::Extreme);
+    }
+
+    #[test]
+    fn risk_rules() {
+        use BreadthRegime::*;
+        use TrendRegime::*;
+        use VolatilityRegime::*;
+        assert_eq!(risk(Uptrend, Normal, Some(Strong)), RiskRegime::RiskOn);
+        assert_eq!(risk(Uptrend, Normal, Some(Weak)), RiskRegime::Neutral);
+        assert_eq!(risk(Uptrend, Extreme, None), RiskRegime::RiskOff);
+        assert_eq!(risk(Range, Elevated, Some(Weak)), RiskRegime::RiskOff);
+        assert_eq!(risk(Range, Low, Some(Mixed)), RiskRegime::Neutral);
+        assert_eq!(risk(Downtrend, Low, Some(Strong)), RiskRegime::RiskOff);
+    }
+
+    #[test]
+    fn market_regime_uses_vix_and_breadth() {
+        let mut data = HashMap::new();
+        data.insert("SPY".to_string(), candles(&rising(), 0.5));
+        data.insert("VIX".to_string(), candles(&[14.0], 0.0));
+        data.insert("XLK".to_string(), candles(&rising(), 0.5));
+        data.insert("XLF".to_string(), candles(&rising(), 0.5));
+        data.insert("XLE".to_string(), candles(&falling(), 0.5));
+
+        let market = classify_market(&data, &config()).unwrap();
+        assert_eq!(market.trend, TrendRegime::Uptrend);
+        assert_eq!(market.volatility, VolatilityRegime::Low);
+        assert_eq!(market.volatility_inputs.vix, Some(14.0));
+        assert_eq!(market.breadth, Some(BreadthRegime::Mixed));
+        let inputs = market.breadth_inputs.unwrap();
+        assert_eq!(inputs.above, vec!["XLF", "XLK"]);
+        assert_eq!(inputs.below, vec!["XLE"]);
+        assert_eq!(market.risk, RiskRegime::RiskOn);
+    }
+
+    #[test]
+    fn market_volatility_falls_back_to_atr() {
+        let mut data = HashMap::new();
+        // True range of 6 on a ~100 price: ATR ≈ 6% → extreme
+        data.insert("SPY".to_string(), candles(&rising(), 3.0));
+
+        let market = classify_market(&data, &config()).unwrap();
+        assert_eq!(market.volatility_inputs.vix, None);
+        assert!(market.volatility_inputs.atr_percent.unwrap() > 5.0);
+        assert_eq!(market.volatility, VolatilityRegime::Extreme);
+        assert_eq!(market.breadth, None);
+        assert_eq!(market.risk, RiskRegime::RiskOff);
+    }
+
+    #[test]
+    fn symbol_regime_uses_own_atr() {
+        let regime = classify_symbol("AAPL", &candles(&falling(), 0.2), &config()).unwrap();
+        assert_eq!(regime.trend, TrendRegime::Downtrend);
+        assert_eq!(regime.volatility, VolatilityRegime::Elevated);
+        assert!(classify_symbol("AAPL", &candles(&[100.0], 0.2), &config()).is_none());
+    }
+
+    #[tokio::test]
+    async fn refresh_writes_market_only_when_requested() {
+        let writer = CacheWriter::open_in_memory().unwrap();
+        let mut data = HashMap::new();
+        data.insert("SPY".to_string(), candles(&rising(), 0.5));
+        data.insert("AAPL".to_string(), candles(&falling(), 0.5));
+        let symbols = vec!["AAPL".to_string()];
+
+        let count = refresh_regimes(&symbols, &data, &config(), 600, false, &writer)
+            .await
+            .unwrap();
+        assert_eq!(count, 1);
+        let count = refresh_regimes(&symbols, &data, &config(), 600, true, &writer)
+            .await
+            .unwrap();
+        assert_eq!(count, 2);
+        assert_eq!(writer.call(|w| w.count()).unwrap(), 2);
+    }
+}
@@ -0,0 +1,184 @@
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, TimeZone, Utc};
+use chrono_tz::America::New_York;
+use market_data_core::candle::Candle;
+
+use crate::config::Timeframe;
+
+/// Regular session open for US equities, in exchange (New York) time.
+fn session_open() -> NaiveTime {
+    NaiveTime::from_hms_opt(9, 30, 0).expect("valid time")
+}
+
+/// Resample ascending 5-minute candles into the given timeframe.
+///
+/// Buckets never cross a session day (New York calendar date). Intraday buckets
+/// are aligned to the 09:30 session open, so pre/post-market candles form their
+/// own buckets. Weekly bars group by ISO week. Each output candle is stamped with
+/// its bucket start.
+pub fn resample(candles: &[Candle], timeframe: Timeframe) -> Vec<Candle> {
+    if timeframe == Timeframe::FiveMinutes {
+        return candles.to_vec();
+    }
+
+    let mut out: Vec<Candle> = Vec::new();
+    let mut current: Option<(NaiveDate, i64)> = None;
+
+    for candle in candles {
+        let bucket = bucket_of(candle.timestamp, timeframe);
+        match out.last_mut() {
+            Some(last) if current == Some(bucket) => {
+                last.high = last.high.max(candle.high);
+                last.low = last.low.min(candle.low);
+                last.close = candle.close;
+                last.volume += candle.volume;
+            }
+            _ => {
+                out.push(Candle {
+                    timestamp: bucket_start(bucket, timeframe).unwrap_or(candle.timestamp),
+                    ..candle.clone()
+                });
+                current = Some(bucket);
+            }
+        }
+    }
+
+    out
+}
+
+fn bucket_minutes(timeframe: Timeframe) -> Option<i64> {
+    match timeframe {
+        Timeframe::FiveMinutes => Some(5),
+        Timeframe::FifteenMinutes => Some(15),
+        Timeframe::OneHour => Some(60),
+        Timeframe::OneDay | Timeframe::OneWeek => None,
+    }
+}
+
+/// (session date, intraday bucket index). Weekly buckets use the ISO week's Monday.
+fn bucket_of(timestamp: DateTime<Utc>, timeframe: Timeframe) -> (NaiveDate, i64) {
+    let local = timestamp.with_timezone(&New_York);
+    let date = local.date_naive();
+    match bucket_minutes(timeframe) {
+        Some(minutes) => {
+            let from_open = (local.time() - session_open()).num_minutes();
+            (date, from_open.div_euclid(minutes))
+        }
+        None if timeframe == Timeframe::OneWeek => {
+            let monday = date - Duration::days(date.weekday().num_days_from_monday() as i64);
+            (monday, 0)
+        }
+        None => (date, 0),
+    }
+}
+
+fn bucket_start((date, index): (NaiveDate, i64), timeframe: Timeframe) -> Option<DateTime<Utc>> {
+    let offset = Duration::minutes(index * bucket_minutes(timeframe).unwrap_or(0));
+    let local = date.and_time(session_open()) + offset;
+    New_York
+        .from_local_datetime(&local)
+        .earliest()
+        .map(|t| t.with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::Decimal;
+    use rust_decimal_macros::dec;
+
+    /// 5-minute candles starting at the given UTC time, with close = open + 1.
+    fn candles_from(start: DateTime<Utc>, count: usize) -> Vec<Candle> {
+        (0..count)
+            .map(|i| {
+                let open = dec!(100) + Decimal::from(i as u32);
+                Candle {
+                    timestamp: start + Duration::minutes(i as i64 * 5),
+                    open,
+                    high: open + dec!(2),
+                    low: open - dec!(1),
+                    close: open + dec!(1),
+                    volume: 10,
+                }
+            })
+            .collect()
+    }
+
+    fn utc(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, m, d, h, min, 0).unwrap()
+    }
+
+    #[test]
+    fn five_minute_is_identity() {
+        let candles = candles_from(utc(2024, 1, 16, 14, 30), 3);
+        let bars = resample(&candles, Timeframe::FiveMinutes);
+        assert_eq!(bars.len(), 3);
+        assert_eq!(bars[2].timestamp, candles[2].timestamp);
+    }
+
+    #[test]
+    fn fifteen_minute_aggregates_ohlcv() {
+        // 09:30 ET in January is 14:30 UTC
+        let candles = candles_from(utc(2024, 1, 16, 14, 30), 6);
+        let bars = resample(&candles, Timeframe::FifteenMinutes);
+
+        assert_eq!(bars.len(), 2);
+        assert_eq!(bars[0].timestamp, utc(2024, 1, 16, 14, 30));
+        assert_eq!(bars[0].open, dec!(100));
+        assert_eq!(bars[0].high, dec!(104));
+        assert_eq!(bars[0].low, dec!(99));
+        assert_eq!(bars[0].close, dec!(103));
+        assert_eq!(bars[0].volume, 30);
+        assert_eq!(bars[1].timestamp, utc(2024, 1, 16, 14, 45));
+    }
+
+    #[test]
+    fn hourly_buckets_align_to_session_open() {
+        // 10:00-11:00 ET: buckets are 09:30-10:30 and 10:30-11:30
+        let candles = candles_from(utc(2024, 1, 16, 15, 0), 12);
+        let bars = resample(&candles, Timeframe::OneHour);
+
+        assert_eq!(bars.len(), 2);
+        assert_eq!(bars[0].timestamp, utc(2024, 1, 16, 14, 30));
+        assert_eq!(bars[0].volume, 60);
+        assert_eq!(bars[1].timestamp, utc(2024, 1, 16, 15, 30));
+    }
+
+    #[test]
+    fn hourly_alignment_follows_daylight_saving() {
+        // 09:30 ET in July is 13:30 UTC
+        let candles = candles_from(utc(2024, 7, 16, 13, 30), 12);
+        let bars = resample(&candles, Timeframe::OneHour);
+        assert_eq!(bars.len(), 1);
+        assert_eq!(bars[0].timestamp, utc(2024, 7, 16, 13, 30));
+    }
+
+    #[test]
+    fn daily_bars_split_on_session_day() {
+        let mut candles = candles_from(utc(2024, 1, 16, 20, 50), 2); // last 10 minutes of Tue
+        candles.extend(candles_from(utc(2024, 1, 17, 14, 30), 2)); // Wed open
+        let bars = resample(&candles, Timeframe::OneDay);
+
+        assert_eq!(bars.len(), 2);
+        assert_eq!(bars[0].timestamp, utc(2024, 1, 16, 14, 30));
+        assert_eq!(bars[0].close, candles[1].close);
+        assert_eq!(bars[1].open, candles[2].open);
+    }
+
+    #[test]
+    fn weekly_bars_group_by_iso_week() {
+        let mut candles = candles_from(utc(2024, 1, 16, 14, 30), 1); // Tue
+        candles.extend(candles_from(utc(2024, 1, 19, 14, 30), 1)); // Fri
+        candles.extend(candles_from(utc(2024, 1, 22, 14, 30), 1)); // next Mon
+        let bars = resample(&candles, Timeframe::OneWeek);
+
+        assert_eq!(bars.len(), 2);
+        assert_eq!(bars[0].timestamp, utc(2024, 1, 15, 14, 30));
+        assert_eq!(bars[0].volume, 20);
+        assert_eq!(bars[1].timestamp, utc(2024, 1, 22, 14, 30));
+    }
+
+    #[test]
+    fn empty_input() {
+        assert!(resample(&[], Timeframe::OneDay).is_empty());
+    }
+}
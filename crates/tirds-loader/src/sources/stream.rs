@@ -1,4 +1,4 @@
-use std::sync::{Arc, Mutex};
+use std::sync::Arc;
 
 use chrono::{Duration, Utc};
 use tds::prelude::*;
@@ -8,7 +8,8 @@ use tokio_util::sync::CancellationToken;
 use tracing;
 
 use crate::config::StreamConfig;
-use crate::writer::SqliteWriter;
+use crate::status::LoaderStatusTracker;
+use crate::write_queue::CacheWriter;
 
 /// Convert a StreamMessage into cache rows.
 /// One row per ticker mentioned in the message metadata.
@@ -138,9 +139,10 @@ pub fn stream_message_to_cache_rows(msg: &StreamMessage, ttl_seconds: u64) -> Ve
 /// Run the real-time stream loop.
 pub async fn stream_loop(
     _config: StreamConfig,
-    writer: Arc<Mutex<SqliteWriter>>,
+    writer: CacheWriter,
     mut rx: broadcast::Receiver<Arc<StreamMessage>>,
     ttl_seconds: u64,
+    status: LoaderStatusTracker,
     cancel: CancellationToken,
 ) {
     tracing::info!("Stream loop started");
@@ -156,22 +158,18 @@ pub async fn stream_loop(
                     Ok(msg) => {
                         let rows = stream_message_to_cache_rows(&msg, ttl_seconds);
                         if !rows.is_empty() {
-                            match writer.lock() {
-                                Ok(mut w) => {
-                                    if let Err(e) = w.upsert_batch(&rows) {
-                                        tracing::error!(error = %e, "Failed to write stream data");
-                                    } else {
-                                        tracing::debug!(count = rows.len(), "Wrote stream entries");
-                                    }
-                                }
-                                Err(e) => {
-                                    tracing::error!(error = %e, "Writer lock poisoned");
-                                }
+                            let count = rows.len();
+                            if let Err(e) = writer.upsert_batch(rows).await {
+                                tracing::error!(error = %e, "Failed to write stream data");
+                            } else {
+                                tracing::debug!(count, "Queued stream entries");
+                                status.record_refresh("stream");
                             }
                         }
                     }
                     Err(broadcast::error::RecvError::Lagged(n)) => {
                         tracing::warn!(skipped = n, "Stream receiver lagged");
+                        status.add_stream_lag(n);
                     }
                     Err(broadcast::error::RecvError::Closed) => {
                         tracing::info!("Stream broadcast channel closed");
@@ -0,0 +1,126 @@
+use std::sync::{Arc, Mutex};
+
+use chrono::Utc;
+use tirds_models::loader_status::{CacheSize, LoaderStatus};
+
+use crate::error::LoaderError;
+use crate::write_queue::CacheWriter;
+
+/// Shared, cloneable handle to the loader's in-memory status.
+///
+/// Each task records its progress here; the status is persisted to the
+/// cache's `loader_status` table via [`LoaderStatusTracker::write_heartbeat`].
+#[derive(Clone)]
+pub struct LoaderStatusTracker {
+    inner: Arc<Mutex<LoaderStatus>>,
+}
+
+impl LoaderStatusTracker {
+    pub fn new(version: &str, heartbeat_interval_seconds: u64) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(LoaderStatus::new(
+                version,
+                heartbeat_interval_seconds,
+            ))),
+        }
+    }
+
+    /// Record a successful run of the named task.
+    pub fn record_refresh(&self, task: &str) {
+        if let Ok(mut status) = self.inner.lock() {
+            status.last_refresh.insert(task.to_string(), Utc::now());
+        }
+    }
+
+    /// Record the cache database size measured by the last maintenance run.
+    pub fn record_cache_size(&self, size: CacheSize) {
+        if let Ok(mut status) = self.inner.lock() {
+            status.cache_size = size;
+        }
+    }
+
+    /// Record the most recent error for a symbol.
+    pub fn record_symbol_error(&self, symbol: &str, error: &str) {
+        if let Ok(mut status) = self.inner.lock() {
+            status
+                .symbol_errors
+                .insert(symbol.to_string(), error.to_string());
+        }
+    }
+
+    /// Clear a symbol's error after it loads successfully.
+    pub fn clear_symbol_error(&self, symbol: &str) {
+        if let Ok(mut status) = self.inner.lock() {
+            status.symbol_errors.remove(symbol);
+        }
+    }
+
+    /// Add to the count of stream messages dropped due to receiver lag.
+    pub fn add_stream_lag(&self, skipped: u64) {
+        if let Ok(mut status) = self.inner.lock() {
+            status.stream_lagged_messages += skipped;
+        }
+    }
+
+    /// Current status (a copy).
+    pub fn snapshot(&self) -> LoaderStatus {
+        match self.inner.lock() {
+            Ok(status) => status.clone(),
+            Err(poisoned) => poisoned.into_inner().clone(),
+        }
+    }
+
+    /// Stamp the heartbeat, attach the writer's queue statistics and queue the
+    /// status for the cache.
+    pub async fn write_heartbeat(&self, writer: &CacheWriter) -> Result<(), LoaderError> {
+        let status = {
+            let mut status = self
+                .inner
+                .lock()
+                .map_err(|e| LoaderError::Config(format!("Status lock poisoned: {e}")))?;
+            status.heartbeat_at = Utc::now();
+            status.writer = writer.stats();
+            status.clone()
+        };
+        writer.submit(move |w| w.write_status(&status)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_errors_and_clears_them() {
+        let tracker = LoaderStatusTracker::new("0.1.0", 60);
+        tracker.record_symbol_error("AAPL", "provider timeout");
+        tracker.record_symbol_error("MSFT", "no data");
+        tracker.clear_symbol_error("AAPL");
+
+        let status = tracker.snapshot();
+        assert_eq!(status.symbol_errors.len(), 1);
+        assert_eq!(status.symbol_errors["MSFT"], "no data");
+    }
+
+    #[test]
+    fn accumulates_stream_lag() {
+        let tracker = LoaderStatusTracker::new("0.1.0", 60);
+        tracker.add_stream_lag(5);
+        tracker.clone().add_stream_lag(7);
+        assert_eq!(tracker.snapshot().stream_lagged_messages, 12);
+    }
+
+    #[tokio::test]
+    async fn write_heartbeat_persists_status() {
+        let writer = CacheWriter::open_in_memory().unwrap();
+        let tracker = LoaderStatusTracker::new("0.1.0", 60);
+        tracker.record_refresh("market_data");
+        let before = tracker.snapshot().heartbeat_at;
+
+        tracker.write_heartbeat(&writer).await.unwrap();
+
+        let status = tracker.snapshot();
+        assert!(status.heartbeat_at >= before);
+        assert!(status.last_refresh.contains_key("market_data"));
+    }
+}
@@ -0,0 +1,519 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use tirds_models::cache_schema::CacheRow;
+use tirds_models::loader_status::WriterStats;
+
+use crate::config::WriterConfig;
+use crate::error::LoaderError;
+use crate::writer::SqliteWriter;
+
+type Job = Box<dyn FnOnce(&mut SqliteWriter) + Send>;
+
+enum Command {
+    Upsert(Vec<CacheRow>),
+    /// Runs after pending rows are committed, so it sees every earlier write.
+    Run(Job),
+    Shutdown,
+}
+
+/// Cloneable handle to the dedicated thread that owns the cache's SQLite
+/// connection.
+///
+/// Rows sent with [`CacheWriter::upsert_batch`] are queued on a bounded
+/// channel, coalesced by key and committed in one transaction per batch
+/// window, so async tasks never block on SQLite. When the queue is full,
+/// async senders wait on the blocking thread pool (backpressure); waits are
+/// counted in [`WriterStats`].
+/// Upsert errors happen on the writer thread: they are logged, counted and
+/// reported by the next [`CacheWriter::flush_since`].
+#[derive(Clone)]
+pub struct CacheWriter {
+    tx: SyncSender<Command>,
+    shared: Arc<Shared>,
+}
+
+#[derive(Default)]
+struct Shared {
+    counters: Counters,
+    /// Most recent failed commit or job.
+    last_error: Mutex<Option<String>>,
+    thread: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl Shared {
+    fn record_error(&self, error: &LoaderError) {
+        self.counters.write_errors.fetch_add(1, Ordering::Relaxed);
+        *self.last_error.lock().unwrap_or_else(|e| e.into_inner()) = Some(error.to_string());
+    }
+}
+
+#[derive(Default)]
+struct Counters {
+    queue_depth: AtomicU64,
+    rows_queued: AtomicU64,
+    rows_written: AtomicU64,
+    rows_unchanged: AtomicU64,
+    rows_coalesced: AtomicU64,
+    batches_committed: AtomicU64,
+    write_errors: AtomicU64,
+    backpressure_waits: AtomicU64,
+    backpressure_wait_ms: AtomicU64,
+}
+
+fn closed() -> LoaderError {
+    LoaderError::Writer("cache writer has shut down".to_string())
+}
+
+impl CacheWriter {
+    /// Move `writer` onto its own thread and return a handle to it.
+    pub fn spawn(writer: SqliteWriter, config: &WriterConfig) -> Self {
+        let (tx, rx) = mpsc::sync_channel(config.queue_capacity.max(1));
+        let shared = Arc::new(Shared::default());
+        let thread = {
+            let shared = shared.clone();
+            let config = config.clone();
+            std::thread::Builder::new()
+                .name("tirds-cache-writer".to_string())
+                .spawn(move || writer_thread(writer, rx, &config, &shared))
+                .expect("failed to spawn cache writer thread")
+        };
+        *shared.thread.lock().unwrap_or_else(|e| e.into_inner()) = Some(thread);
+        Self { tx, shared }
+    }
+
+    /// Writer thread over an in-memory database, for testing.
+    pub fn open_in_memory() -> Result<Self, LoaderError> {
+        Ok(Self::spawn(
+            SqliteWriter::open_in_memory()?,
+            &WriterConfig::default(),
+        ))
+    }
+
+    /// Queue rows for writing, waiting while the queue is full.
+    pub async fn upsert_batch(&self, rows: Vec<CacheRow>) -> Result<(), LoaderError> {
+        if rows.is_empty() {
+            return Ok(());
+        }
+        let count = rows.len() as u64;
+        self.send(Command::Upsert(rows)).await?;
+        self.shared
+            .counters
+            .rows_queued
+            .fetch_add(count, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Queue a job on the writer thread without waiting for it to run. Errors
+    /// are logged there.
+    pub async fn submit<F>(&self, job: F) -> Result<(), LoaderError>
+    where
+        F: FnOnce(&mut SqliteWriter) -> Result<(), LoaderError> + Send + 'static,
+    {
+        let shared = self.shared.clone();
+        self.send(Command::Run(Box::new(move |writer| {
+            if let Err(e) = job(writer) {
+                tracing::error!(error = %e, "Cache writer job failed");
+                shared.record_error(&e);
+            }
+        })))
+        .await
+    }
+
+    /// Run `job` on the writer thread after everything queued before it is
+    /// committed, blocking the current thread for the result. For callers
+    /// outside the async runtime; async code uses [`CacheWriter::call_async`].
+    pub fn call<T, F>(&self, job: F) -> Result<T, LoaderError>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut SqliteWriter) -> Result<T, LoaderError> + Send + 'static,
+    {
+        let (reply, result) = mpsc::sync_channel(1);
+        self.send_blocking(Command::Run(Box::new(move |writer| {
+            let _ = reply.send(job(writer));
+        })))?;
+        result.recv().map_err(|_| closed())?
+    }
+
+    /// Like [`CacheWriter::call`], but awaits the result instead of blocking.
+    pub async fn call_async<T, F>(&self, job: F) -> Result<T, LoaderError>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut SqliteWriter) -> Result<T, LoaderError> + Send + 'static,
+    {
+        let (reply, result) = tokio::sync::oneshot::channel();
+        self.send(Command::Run(Box::new(move |writer| {
+            let _ = reply.send(job(writer));
+        })))
+        .await?;
+        result.await.map_err(|_| closed())?
+    }
+
+    /// Wait until everything queued so far is committed.
+    pub fn flush(&self) -> Result<(), LoaderError> {
+        self.call(|_| Ok(()))
+    }
+
+    /// Failed commits and jobs so far; pass to [`CacheWriter::flush_since`].
+    pub fn error_mark(&self) -> u64 {
+        self.shared.counters.write_errors.load(Ordering::Relaxed)
+    }
+
+    /// Wait until everything queued so far is committed, failing if any
+    /// commit or job failed after `mark` was taken from
+    /// [`CacheWriter::error_mark`]. Failures of rows queued through other
+    /// handles in the meantime count too.
+    pub async fn flush_since(&self, mark: u64) -> Result<(), LoaderError> {
+        self.call_async(|_| Ok(())).await?;
+        let failed = self.error_mark().saturating_sub(mark);
+        if failed == 0 {
+            return Ok(());
+        }
+        let last = self
+            .shared
+            .last_error
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+            .unwrap_or_default();
+        Err(LoaderError::Writer(format!(
+            "{failed} cache write(s) failed to commit: {last}"
+        )))
+    }
+
+    /// Commit everything queued and stop the writer thread. Later writes
+    /// through any clone of this handle fail.
+    pub fn shutdown(&self) -> Result<(), LoaderError> {
+        let thread = self
+            .shared
+            .thread
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take();
+        let Some(thread) = thread else {
+            return Ok(());
+        };
+        self.send_blocking(Command::Shutdown)?;
+        thread
+            .join()
+            .map_err(|_| LoaderError::Writer("cache writer thread panicked".to_string()))
+    }
+
+    pub fn stats(&self) -> WriterStats {
+        let c = &self.shared.counters;
+        WriterStats {
+            queue_depth: c.queue_depth.load(Ordering::Relaxed),
+            rows_queued: c.rows_queued.load(Ordering::Relaxed),
+            rows_written: c.rows_written.load(Ordering::Relaxed),
+            rows_unchanged: c.rows_unchanged.load(Ordering::Relaxed),
+            rows_coalesced: c.rows_coalesced.load(Ordering::Relaxed),
+            batches_committed: c.batches_committed.load(Ordering::Relaxed),
+            write_errors: c.write_errors.load(Ordering::Relaxed),
+            backpressure_waits: c.backpressure_waits.load(Ordering::Relaxed),
+            backpressure_wait_ms: c.backpressure_wait_ms.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Queue `command`; when the queue is full, wait for room on the blocking
+    /// thread pool so the async worker stays free.
+    async fn send(&self, command: Command) -> Result<(), LoaderError> {
+        let counters = &self.shared.counters;
+        counters.queue_depth.fetch_add(1, Ordering::Relaxed);
+        let sent = match self.tx.try_send(command) {
+            Ok(()) => Ok(()),
+            Err(TrySendError::Full(command)) => {
+                counters.backpressure_waits.fetch_add(1, Ordering::Relaxed);
+                let started = Instant::now();
+                let tx = self.tx.clone();
+                let sent = tokio::task::spawn_blocking(move || tx.send(command))
+                    .await
+                    .map_err(|e| LoaderError::Writer(format!("cache writer send panicked: {e}")))
+                    .and_then(|sent| sent.map_err(|_| closed()));
+                counters
+                    .backpressure_wait_ms
+                    .fetch_add(started.elapsed().as_millis() as u64, Ordering::Relaxed);
+                sent
+            }
+            Err(TrySendError::Disconnected(_)) => Err(closed()),
+        };
+        if sent.is_err() {
+            counters.queue_depth.fetch_sub(1, Ordering::Relaxed);
+        }
+        sent
+    }
+
+    /// Queue `command`, blocking the current thread while the queue is full.
+    fn send_blocking(&self, command: Command) -> Result<(), LoaderError> {
+        let counters = &self.shared.counters;
+        counters.queue_depth.fetch_add(1, Ordering::Relaxed);
+        let sent = self.tx.send(command).map_err(|_| closed());
+        if sent.is_err() {
+            counters.queue_depth.fetch_sub(1, Ordering::Relaxed);
+        }
+        sent
+    }
+}
+
+/// Collect upserts for up to `batch_window_ms` (or `max_batch_rows` distinct
+/// keys), keeping only the latest row per key, then commit them together.
+/// Jobs and shutdown commit pending rows first. When every handle is dropped
+/// the pending rows are committed and the thread exits.
+fn writer_thread(
+    mut writer: SqliteWriter,
+    rx: mpsc::Receiver<Command>,
+    config: &WriterConfig,
+    shared: &Shared,
+) {
+    let counters = &shared.counters;
+    let window = Duration::from_millis(config.batch_window_ms);
+    let mut pending: HashMap<String, CacheRow> = HashMap::new();
+    let mut deadline = Instant::now();
+
+    loop {
+        let command = if pending.is_empty() {
+            rx.recv().map_err(|_| RecvTimeoutError::Disconnected)
+        } else {
+            rx.recv_timeout(deadline.saturating_duration_since(Instant::now()))
+        };
+        if command.is_ok() {
+            counters.queue_depth.fetch_sub(1, Ordering::Relaxed);
+        }
+        match command {
+            Ok(Command::Upsert(rows)) => {
+                if pending.is_empty() {
+                    deadline = Instant::now() + window;
+                }
+                for row in rows {
+                    if pending.insert(row.key.clone(), row).is_some() {
+                        counters.rows_coalesced.fetch_add(1, Ordering::Relaxed);
+                    }
+                }
+                if pending.len() >= config.max_batch_rows.max(1) {
+                    commit(&mut writer, &mut pending, shared);
+                }
+            }
+            Ok(Command::Run(job)) => {
+                commit(&mut writer, &mut pending, shared);
+                job(&mut writer);
+            }
+            Err(RecvTimeoutError::Timeout) => commit(&mut writer, &mut pending, shared),
+            Ok(Command::Shutdown) | Err(RecvTimeoutError::Disconnected) => {
+                commit(&mut writer, &mut pending, shared);
+                tracing::info!("Cache writer stopped");
+                return;
+            }
+        }
+    }
+}
+
+fn commit(writer: &mut SqliteWriter, pending: &mut HashMap<String, CacheRow>, shared: &Shared) {
+    if pending.is_empty() {
+        return;
+    }
+    let counters = &shared.counters;
+    let rows: Vec<CacheRow> = pending.drain().map(|(_, row)| row).collect();
+    match writer.upsert_batch(&rows) {
+        Ok(written) => {
+            counters
+                .rows_written
+                .fetch_add(rows.len() as u64, Ordering::Relaxed);
+            counters
+                .rows_unchanged
+                .fetch_add((rows.len() - written) as u64, Ordering::Relaxed);
+            counters.batches_committed.fetch_add(1, Ordering::Relaxed);
+            tracing::debug!(count = rows.len(), "Committed cache batch");
+        }
+        Err(e) => {
+            tracing::error!(count = rows.len(), error = %e, "Failed to commit cache batch");
+            shared.record_error(&e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn row(key: &str, value: &str) -> CacheRow {
+        let now = Utc::now();
+        CacheRow {
+            key: key.to_string(),
+            category: "indicator".to_string(),
+            value_json: value.to_string(),
+            source: "test".to_string(),
+            symbol: None,
+            created_at: now.to_rfc3339(),
+            expires_at: (now + chrono::Duration::minutes(10)).to_rfc3339(),
+            updated_at: now.to_rfc3339(),
+        }
+    }
+
+    fn spawn(config: WriterConfig) -> CacheWriter {
+        CacheWriter::spawn(SqliteWriter::open_in_memory().unwrap(), &config)
+    }
+
+    #[tokio::test]
+    async fn coalesces_rows_within_a_window() {
+        let writer = spawn(WriterConfig {
+            batch_window_ms: 10_000,
+            ..WriterConfig::default()
+        });
+        writer
+            .upsert_batch(vec![row("a", "1"), row("b", "1")])
+            .await
+            .unwrap();
+        writer.upsert_batch(vec![row("a", "2")]).await.unwrap();
+        assert_eq!(writer.call(|w| w.count()).unwrap(), 2);
+
+        let stats = writer.stats();
+        assert_eq!(stats.rows_queued, 3);
+        assert_eq!(stats.rows_written, 2);
+        assert_eq!(stats.rows_coalesced, 1);
+        assert_eq!(stats.batches_committed, 1);
+        assert_eq!(stats.queue_depth, 0);
+    }
+
+    #[tokio::test]
+    async fn counts_unchanged_rows() {
+        let writer = spawn(WriterConfig::default());
+        writer.upsert_batch(vec![row("a", "1")]).await.unwrap();
+        writer.flush().unwrap();
+        writer
+            .upsert_batch(vec![row("a", "1"), row("b", "1")])
+            .await
+            .unwrap();
+        writer.flush().unwrap();
+
+        let stats = writer.stats();
+        assert_eq!(stats.rows_written, 3);
+        assert_eq!(stats.rows_unchanged, 1);
+    }
+
+    #[tokio::test]
+    async fn commits_when_the_window_closes() {
+        let writer = spawn(WriterConfig {
+            batch_window_ms: 10,
+            ..WriterConfig::default()
+        });
+        writer.upsert_batch(vec![row("a", "1")]).await.unwrap();
+        std::thread::sleep(Duration::from_millis(200));
+        assert_eq!(writer.stats().batches_committed, 1);
+    }
+
+    #[tokio::test]
+    async fn commits_early_at_max_batch_rows() {
+        let writer = spawn(WriterConfig {
+            batch_window_ms: 10_000,
+            max_batch_rows: 2,
+            ..WriterConfig::default()
+        });
+        writer
+            .upsert_batch(vec![row("a", "1"), row("b", "1"), row("c", "1")])
+            .await
+            .unwrap();
+        writer.flush().unwrap();
+        assert_eq!(writer.stats().batches_committed, 1);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn full_queue_applies_backpressure() {
+        let writer = spawn(WriterConfig {
+            queue_capacity: 1,
+            ..WriterConfig::default()
+        });
+        let (started, release) = (
+            Arc::new(std::sync::Barrier::new(2)),
+            Arc::new(std::sync::Barrier::new(2)),
+        );
+        {
+            let (started, release) = (started.clone(), release.clone());
+            writer
+                .submit(move |_| {
+                    started.wait();
+                    release.wait();
+                    Ok(())
+                })
+                .await
+                .unwrap();
+        }
+        tokio::task::spawn_blocking(move || started.wait())
+            .await
+            .unwrap();
+        // The job holds the thread: one command fits in the queue, the next waits.
+        writer.upsert_batch(vec![row("a", "1")]).await.unwrap();
+        let sender = {
+            let writer = writer.clone();
+            tokio::spawn(async move { writer.upsert_batch(vec![row("b", "1")]).await })
+        };
+        // The waiting sender leaves the runtime free to run other tasks.
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!sender.is_finished());
+        tokio::task::spawn_blocking(move || release.wait())
+            .await
+            .unwrap();
+        sender.await.unwrap().unwrap();
+        writer.call_async(|_| Ok(())).await.unwrap();
+
+        let stats = writer.stats();
+        assert!(stats.backpressure_waits >= 1);
+        assert!(stats.backpressure_wait_ms >= 40);
+        assert_eq!(stats.rows_written, 2);
+    }
+
+    #[tokio::test]
+    async fn shutdown_flushes_pending_rows() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cache.db");
+        let path = path.to_str().unwrap();
+        let writer = CacheWriter::spawn(
+            SqliteWriter::open(path).unwrap(),
+            &WriterConfig {
+                batch_window_ms: 60_000,
+                ..WriterConfig::default()
+            },
+        );
+        writer.upsert_batch(vec![row("a", "1")]).await.unwrap();
+        writer.shutdown().unwrap();
+
+        assert_eq!(SqliteWriter::open(path).unwrap().count().unwrap(), 1);
+        assert!(writer.upsert_batch(vec![row("b", "1")]).await.is_err());
+        assert!(writer.shutdown().is_ok());
+    }
+
+    #[tokio::test]
+    async fn flush_since_reports_failed_commits() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cache.db");
+        let path = path.to_str().unwrap();
+        let writer =
+            CacheWriter::spawn(SqliteWriter::open(path).unwrap(), &WriterConfig::default());
+        rusqlite::Connection::open(path)
+            .unwrap()
+            .execute_batch("DROP TABLE cache_entries")
+            .unwrap();
+        let mark = writer.error_mark();
+        writer.upsert_batch(vec![row("a", "1")]).await.unwrap();
+
+        let err = writer.flush_since(mark).await.unwrap_err();
+        assert!(err.to_string().contains("1 cache write(s) failed"), "{err}");
+        assert!(writer.flush_since(writer.error_mark()).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn call_async_sees_earlier_writes() {
+        let writer = spawn(WriterConfig::default());
+        writer.upsert_batch(vec![row("a", "1")]).await.unwrap();
+        assert_eq!(writer.call_async(|w| w.count()).await.unwrap(), 1);
+        writer
+            .submit(|_| Err(LoaderError::Writer("boom".to_string())))
+            .await
+            .unwrap();
+        writer.flush().unwrap();
+        assert_eq!(writer.stats().write_errors, 1);
+    }
+}
@@ -1,6 +1,11 @@
-use chrono::Utc;
-use rusqlite::Connection;
-use tirds_models::cache_schema::CacheRow;
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use rusqlite::{Connection, OptionalExtension, Transaction, TransactionBehavior};
+use tirds_models::cache_schema::{history_timestamp, CacheRow};
+use tirds_models::loader_status::{CacheSize, LoaderStatus};
+use tirds_models::migrations::{self, CURRENT_SCHEMA_VERSION};
+use tirds_models::symbol_request::SymbolRequestStatus;
 
 use crate::error::LoaderError;
 
@@ -8,58 +13,135 @@ use crate::error::LoaderError;
 ///
 /// Opens the shared cache database in read-write mode with WAL journal
 /// for concurrent read/write access (TIRDS reader can read while loader writes).
+///
+/// Keeps a hash of each entry's content so that rewriting an unchanged value
+/// only moves its `expires_at` and `updated_at` forward instead of replacing
+/// the whole row. With
+/// [`SqliteWriter::with_history`], every new value is also appended to
+/// `cache_history` for point-in-time reads.
 pub struct SqliteWriter {
     conn: Connection,
+    hashes: HashMap<String, u64>,
+    history: bool,
 }
 
 impl SqliteWriter {
     /// Open a read-write connection to the cache database.
-    /// Creates the schema if it doesn't exist. Enables WAL mode.
+    /// Migrates the schema to the current version. Enables WAL mode.
     pub fn open(path: &str) -> Result<Self, LoaderError> {
-        let conn = Connection::open(path)?;
-        conn.execute_batch(tirds_models::cache_schema::CACHE_TABLE_DDL)?;
+        let mut conn = Connection::open(path)?;
         conn.pragma_update(None, "journal_mode", "WAL")?;
-        Ok(Self { conn })
+        migrate(&mut conn)?;
+        Self::with_hashes(conn)
     }
 
     /// Open an in-memory database for testing.
     pub fn open_in_memory() -> Result<Self, LoaderError> {
-        let conn = Connection::open_in_memory()?;
-        conn.execute_batch(tirds_models::cache_schema::CACHE_TABLE_DDL)?;
-        Ok(Self { conn })
+        let mut conn = Connection::open_in_memory()?;
+        migrate(&mut conn)?;
+        Self::with_hashes(conn)
+    }
+
+    /// Seed the content hashes from the entries already in the cache, so a
+    /// restarted loader doesn't rewrite every unchanged row.
+    fn with_hashes(conn: Connection) -> Result<Self, LoaderError> {
+        let hashes = {
+            let mut stmt = conn
+                .prepare("SELECT key, category, value_json, source, symbol FROM cache_entries")?;
+            let hashes = stmt
+                .query_map([], |row| {
+                    let key: String = row.get(0)?;
+                    let hash = content_hash(
+                        row.get_ref(1)?.as_str()?,
+                        row.get_ref(2)?.as_str()?,
+                        row.get_ref(3)?.as_str()?,
+                        row.get_ref(4)?.as_str_or_null()?,
+                    );
+                    Ok((key, hash))
+                })?
+                .collect::<Result<HashMap<_, _>, _>>()?;
+            hashes
+        };
+        Ok(Self {
+            conn,
+            hashes,
+            history: false,
+        })
+    }
+
+    /// Record every new value in `cache_history`. Current entries that have
+    /// no open history interval yet are recorded as valid from their
+    /// `updated_at`.
+    pub fn with_history(mut self) -> Result<Self, LoaderError> {
+        let tx = self.conn.transaction()?;
+        let rows = {
+            let mut stmt = tx.prepare(
+                "SELECT key, category, value_json, source, symbol, created_at, expires_at, updated_at \
+                 FROM cache_entries e WHERE NOT EXISTS \
+                 (SELECT 1 FROM cache_history h WHERE h.key = e.key AND h.valid_to IS NULL)",
+            )?;
+            let rows = stmt
+                .query_map([], |row| {
+                    Ok(CacheRow {
+                        key: row.get(0)?,
+                        category: row.get(1)?,
+                        value_json: row.get(2)?,
+                        source: row.get(3)?,
+                        symbol: row.get(4)?,
+                        created_at: row.get(5)?,
+                        expires_at: row.get(6)?,
+                        updated_at: row.get(7)?,
+                    })
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            rows
+        };
+        for row in &rows {
+            append_history(&tx, row, row_hash(row))?;
+        }
+        tx.commit()?;
+        if !rows.is_empty() {
+            tracing::info!(
+                count = rows.len(),
+                "Recorded current cache entries in history"
+            );
+        }
+        self.history = true;
+        Ok(self)
     }
 
     /// Upsert a single cache entry.
-    pub fn upsert(&self, row: &CacheRow) -> Result<(), LoaderError> {
-        self.conn.execute(
-            "INSERT OR REPLACE INTO cache_entries \
-             (key, category, value_json, source, symbol, created_at, expires_at, updated_at) \
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
-            rusqlite::params![
-                row.key,
-                row.category,
-                row.value_json,
-                row.source,
-                row.symbol,
-                row.created_at,
-                row.expires_at,
-                row.updated_at,
-            ],
-        )?;
+    pub fn upsert(&mut self, row: &CacheRow) -> Result<(), LoaderError> {
+        self.upsert_batch(std::slice::from_ref(row))?;
         Ok(())
     }
 
-    /// Batch upsert within a transaction for efficiency.
-    pub fn upsert_batch(&mut self, rows: &[CacheRow]) -> Result<(), LoaderError> {
+    /// Batch upsert within a transaction for efficiency. Rows whose content
+    /// matches what was last written only have their `expires_at` and
+    /// `updated_at` moved forward; no history is recorded for them.
+    /// Returns the number of rows that were written in full.
+    pub fn upsert_batch(&mut self, rows: &[CacheRow]) -> Result<usize, LoaderError> {
+        let mut changed = Vec::new();
         let tx = self.conn.transaction()?;
         {
-            let mut stmt = tx.prepare_cached(
+            let mut replace = tx.prepare_cached(
                 "INSERT OR REPLACE INTO cache_entries \
                  (key, category, value_json, source, symbol, created_at, expires_at, updated_at) \
                  VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
             )?;
+            let mut extend = tx.prepare_cached(
+                "UPDATE cache_entries SET expires_at = ?2, updated_at = ?3 WHERE key = ?1",
+            )?;
             for row in rows {
-                stmt.execute(rusqlite::params![
+                let hash = row_hash(row);
+                // An entry deleted since it was hashed (expired) is written again.
+                if self.hashes.get(&row.key) == Some(&hash)
+                    && extend.execute(rusqlite::params![row.key, row.expires_at, row.updated_at])?
+                        > 0
+                {
+                    continue;
+                }
+                replace.execute(rusqlite::params![
                     row.key,
                     row.category,
                     row.value_json,
@@ -69,22 +151,140 @@ impl SqliteWriter {
                     row.expires_at,
                     row.updated_at,
                 ])?;
+                if self.history {
+                    append_history(&tx, row, hash)?;
+                }
+                changed.push((row.key.clone(), hash));
             }
         }
         tx.commit()?;
-        Ok(())
+        let written = changed.len();
+        self.hashes.extend(changed);
+        Ok(written)
     }
 
-    /// Delete all expired entries. Returns the number of rows deleted.
-    pub fn expire_stale(&self) -> Result<usize, LoaderError> {
+    /// Delete all expired entries, closing their history intervals at their
+    /// expiry. Returns the number of rows deleted.
+    pub fn expire_stale(&mut self) -> Result<usize, LoaderError> {
         let now = Utc::now().to_rfc3339();
+        let tx = self.conn.transaction()?;
+        let deleted = {
+            let mut stmt = tx.prepare_cached(
+                "DELETE FROM cache_entries WHERE expires_at < ?1 RETURNING key, expires_at",
+            )?;
+            let deleted = stmt
+                .query_map(rusqlite::params![now], |row| {
+                    Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            deleted
+        };
+        if self.history {
+            for (key, expires_at) in &deleted {
+                close_history(&tx, key, &normalize_timestamp(expires_at))?;
+            }
+        }
+        tx.commit()?;
+        for (key, _) in &deleted {
+            self.hashes.remove(key);
+        }
+        Ok(deleted.len())
+    }
+
+    /// Delete history values that stopped being current before `cutoff`.
+    /// Returns the number of rows deleted.
+    pub fn prune_history(&self, cutoff: DateTime<Utc>) -> Result<usize, LoaderError> {
+        if !self.history {
+            return Ok(0);
+        }
         let deleted = self.conn.execute(
-            "DELETE FROM cache_entries WHERE expires_at < ?1",
-            rusqlite::params![now],
+            "DELETE FROM cache_history WHERE valid_to IS NOT NULL AND valid_to < ?1",
+            rusqlite::params![history_timestamp(cutoff)],
         )?;
         Ok(deleted)
     }
 
+    /// Copy the write-ahead log into the database and truncate it. Returns
+    /// false when readers kept the checkpoint from completing; it is retried
+    /// on the next call.
+    pub fn checkpoint(&self) -> Result<bool, LoaderError> {
+        let busy: i64 = self
+            .conn
+            .query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |row| row.get(0))?;
+        Ok(busy == 0)
+    }
+
+    /// Rebuild the database file, returning free pages to the filesystem.
+    pub fn vacuum(&self) -> Result<(), LoaderError> {
+        self.conn.execute_batch("VACUUM")?;
+        Ok(())
+    }
+
+    /// Current size of the database file, its WAL and its free pages.
+    pub fn size(&self) -> Result<CacheSize, LoaderError> {
+        let pragma = |name: &str| -> Result<u64, LoaderError> {
+            let value: i64 = self
+                .conn
+                .query_row(&format!("PRAGMA {name}"), [], |row| row.get(0))?;
+            Ok(value as u64)
+        };
+        let page_size = pragma("page_size")?;
+        let wal_bytes = match self.conn.path().filter(|path| !path.is_empty()) {
+            Some(path) => std::fs::metadata(format!("{path}-wal")).map_or(0, |m| m.len()),
+            None => 0,
+        };
+        Ok(CacheSize {
+            db_bytes: pragma("page_count")? * page_size,
+            wal_bytes,
+            free_bytes: pragma("freelist_count")? * page_size,
+        })
+    }
+
+    /// Write the loader heartbeat/status record (single row, replaced each time).
+    pub fn write_status(&self, status: &LoaderStatus) -> Result<(), LoaderError> {
+        let json = serde_json::to_string(status)?;
+        self.conn.execute(
+            "INSERT OR REPLACE INTO loader_status (id, status_json, updated_at) \
+             VALUES (1, ?1, ?2)",
+            rusqlite::params![json, status.heartbeat_at.to_rfc3339()],
+        )?;
+        Ok(())
+    }
+
+    /// Symbols the evaluator has requested that are still waiting to be loaded,
+    /// oldest first.
+    pub fn pending_symbol_requests(&self) -> Result<Vec<String>, LoaderError> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT symbol FROM symbol_requests WHERE status = ?1 ORDER BY requested_at",
+        )?;
+        let symbols = stmt
+            .query_map(
+                rusqlite::params![SymbolRequestStatus::Pending.as_str()],
+                |row| row.get(0),
+            )?
+            .collect::<Result<Vec<String>, _>>()?;
+        Ok(symbols)
+    }
+
+    /// Mark a requested symbol as loaded, or failed with the given error.
+    pub fn complete_symbol_request(
+        &self,
+        symbol: &str,
+        error: Option<&str>,
+    ) -> Result<(), LoaderError> {
+        let status = if error.is_some() {
+            SymbolRequestStatus::Failed
+        } else {
+            SymbolRequestStatus::Loaded
+        };
+        self.conn.execute(
+            "UPDATE symbol_requests SET status = ?2, completed_at = ?3, error = ?4 \
+             WHERE symbol = ?1",
+            rusqlite::params![symbol, status.as_str(), Utc::now().to_rfc3339(), error],
+        )?;
+        Ok(())
+    }
+
     /// Count all entries in the cache.
     pub fn count(&self) -> Result<usize, LoaderError> {
         let count: usize =
@@ -94,6 +294,128 @@ impl SqliteWriter {
     }
 }
 
+/// Run the schema migrations this database hasn't had yet, each in its own
+/// transaction, and record the new version in `schema_meta`. Refuses a
+/// database migrated by a newer loader. Returns the schema version.
+fn migrate(conn: &mut Connection) -> Result<u32, LoaderError> {
+    conn.execute_batch(migrations::SCHEMA_META_DDL)?;
+    loop {
+        // Immediate: a second loader opening the same file waits, then sees the new version
+        let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+        let version = read_meta(&tx, migrations::SCHEMA_VERSION_KEY)?;
+        if version > CURRENT_SCHEMA_VERSION {
+            return Err(LoaderError::Schema(format!(
+                "cache schema version {version} is newer than this loader supports \
+                 ({CURRENT_SCHEMA_VERSION})"
+            )));
+        }
+        let Some(migration) = migrations::pending(version).next() else {
+            return Ok(version);
+        };
+        for statement in migration.statements {
+            tx.execute_batch(statement)?;
+        }
+        let mut set =
+            tx.prepare_cached("INSERT OR REPLACE INTO schema_meta (key, value) VALUES (?1, ?2)")?;
+        set.execute(rusqlite::params![
+            migrations::SCHEMA_VERSION_KEY,
+            migration.version.to_string()
+        ])?;
+        set.execute(rusqlite::params![
+            migrations::MIN_READER_VERSION_KEY,
+            migrations::min_reader_version(migration.version).to_string()
+        ])?;
+        drop(set);
+        tx.commit()?;
+        tracing::info!(
+            version = migration.version,
+            description = migration.description,
+            "Applied cache schema migration"
+        );
+    }
+}
+
+/// A numeric `schema_meta` value; 0 when unset.
+fn read_meta(conn: &Connection, key: &str) -> Result<u32, LoaderError> {
+    let value: Option<String> = conn
+        .query_row(
+            "SELECT value FROM schema_meta WHERE key = ?1",
+            [key],
+            |row| row.get(0),
+        )
+        .optional()?;
+    match value {
+        None => Ok(0),
+        Some(value) => value
+            .parse()
+            .map_err(|_| LoaderError::Schema(format!("invalid {key} in schema_meta: {value}"))),
+    }
+}
+
+/// Hash of everything in a row except its timestamps.
+fn row_hash(row: &CacheRow) -> u64 {
+    content_hash(
+        &row.category,
+        &row.value_json,
+        &row.source,
+        row.symbol.as_deref(),
+    )
+}
+
+/// 64-bit FNV-1a over the row's content fields. Stable across builds, since
+/// it is stored in `cache_history`.
+fn content_hash(category: &str, value_json: &str, source: &str, symbol: Option<&str>) -> u64 {
+    let fields = [Some(category), Some(value_json), Some(source), symbol];
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for field in fields {
+        // Separate fields (and None from "") so different rows can't collide by shifting bytes
+        let bytes = field.map_or(&[0xff][..], str::as_bytes);
+        for &byte in bytes.iter().chain(&[0]) {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    hash
+}
+
+/// Close the key's current history interval and open one for `row`, both at
+/// the row's `updated_at`.
+fn append_history(tx: &Transaction, row: &CacheRow, hash: u64) -> Result<(), LoaderError> {
+    let valid_from = normalize_timestamp(&row.updated_at);
+    close_history(tx, &row.key, &valid_from)?;
+    tx.prepare_cached(
+        "INSERT OR REPLACE INTO cache_history \
+         (key, value_hash, value_json, category, source, symbol, valid_from, valid_to) \
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, NULL)",
+    )?
+    .execute(rusqlite::params![
+        row.key,
+        hash as i64,
+        row.value_json,
+        row.category,
+        row.source,
+        row.symbol,
+        valid_from,
+    ])?;
+    Ok(())
+}
+
+fn close_history(tx: &Transaction, key: &str, at: &str) -> Result<(), LoaderError> {
+    tx.prepare_cached(
+        "UPDATE cache_history SET valid_to = ?2 WHERE key = ?1 AND valid_to IS NULL",
+    )?
+    .execute(rusqlite::params![key, at])?;
+    Ok(())
+}
+
+/// Rewrite an RFC3339 timestamp in the fixed-width history form; unparseable
+/// timestamps are kept as they are.
+fn normalize_timestamp(ts: &str) -> String {
+    DateTime::parse_from_rfc3339(ts)
+        .map(|t| history_timestamp(t.with_timezone(&Utc)))
+        .unwrap_or_else(|_| ts.to_string())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -115,7 +437,7 @@ mod tests {
 
     #[test]
     fn upsert_and_count() {
-        let writer = SqliteWriter::open_in_memory().unwrap();
+        let mut writer = SqliteWriter::open_in_memory().unwrap();
         writer
             .upsert(&make_row("indicator:rsi_14:AAPL", "AAPL", 300))
             .unwrap();
@@ -124,7 +446,7 @@ mod tests {
 
     #[test]
     fn upsert_replaces_existing() {
-        let writer = SqliteWriter::open_in_memory().unwrap();
+        let mut writer = SqliteWriter::open_in_memory().unwrap();
         let mut row = make_row("indicator:rsi_14:AAPL", "AAPL", 300);
         writer.upsert(&row).unwrap();
 
@@ -162,6 +484,106 @@ mod tests {
         assert_eq!(writer.count().unwrap(), 1);
     }
 
+    #[test]
+    fn write_status_replaces_single_row() {
+        let writer = SqliteWriter::open_in_memory().unwrap();
+        let mut status = LoaderStatus::new("0.1.0", 60);
+        writer.write_status(&status).unwrap();
+        status.stream_lagged_messages = 3;
+        writer.write_status(&status).unwrap();
+
+        let (rows, json): (i64, String) = writer
+            .conn
+            .query_row(
+                "SELECT COUNT(*), MAX(status_json) FROM loader_status",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!(rows, 1);
+        let stored: LoaderStatus = serde_json::from_str(&json).unwrap();
+        assert_eq!(stored.stream_lagged_messages, 3);
+    }
+
+    #[test]
+    fn symbol_requests_pending_and_complete() {
+        let writer = SqliteWriter::open_in_memory().unwrap();
+        for (symbol, requested_at) in [
+            ("NVDA", "2026-01-02T00:00:00Z"),
+            ("AMD", "2026-01-01T00:00:00Z"),
+        ] {
+            writer
+                .conn
+                .execute(
+                    "INSERT INTO symbol_requests (symbol, status, requested_at) VALUES (?1, 'pending', ?2)",
+                    rusqlite::params![symbol, requested_at],
+                )
+                .unwrap();
+        }
+        assert_eq!(
+            writer.pending_symbol_requests().unwrap(),
+            vec!["AMD", "NVDA"]
+        );
+
+        writer.complete_symbol_request("AMD", None).unwrap();
+        writer
+            .complete_symbol_request("NVDA", Some("no data"))
+            .unwrap();
+        assert!(writer.pending_symbol_requests().unwrap().is_empty());
+
+        let status: String = writer
+            .conn
+            .query_row(
+                "SELECT status FROM symbol_requests WHERE symbol = 'NVDA'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(status, "failed");
+    }
+
+    #[test]
+    fn migrates_legacy_database() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("legacy.db");
+        let path = path.to_str().unwrap();
+        {
+            // A database created before schema versioning
+            let conn = Connection::open(path).unwrap();
+            conn.execute_batch(tirds_models::cache_schema::CACHE_TABLE_DDL)
+                .unwrap();
+        }
+
+        let writer = SqliteWriter::open(path).unwrap();
+        assert_eq!(
+            read_meta(&writer.conn, migrations::SCHEMA_VERSION_KEY).unwrap(),
+            CURRENT_SCHEMA_VERSION
+        );
+        assert_eq!(
+            read_meta(&writer.conn, migrations::MIN_READER_VERSION_KEY).unwrap(),
+            1
+        );
+        drop(writer);
+        // Reopening finds nothing to do
+        let mut conn = Connection::open(path).unwrap();
+        assert_eq!(migrate(&mut conn).unwrap(), CURRENT_SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn refuses_newer_schema() {
+        let writer = SqliteWriter::open_in_memory().unwrap();
+        let mut conn = writer.conn;
+        conn.execute(
+            "UPDATE schema_meta SET value = ?1 WHERE key = ?2",
+            rusqlite::params![
+                (CURRENT_SCHEMA_VERSION + 1).to_string(),
+                migrations::SCHEMA_VERSION_KEY
+            ],
+        )
+        .unwrap();
+        assert!(matches!(migrate(&mut conn), Err(LoaderError::Schema(_))));
+    }
+
     #[test]
     fn wal_mode_on_file() {
         let dir = tempfile::tempdir().unwrap();
@@ -169,4 +591,184 @@ mod tests {
         let _writer = SqliteWriter::open(path.to_str().unwrap()).unwrap();
         // WAL mode is set during open - if we get here without error, it worked
     }
+
+    /// `(expires_at, updated_at, value_json)` of the stored entry.
+    fn stored(writer: &SqliteWriter, key: &str) -> (String, String, String) {
+        writer
+            .conn
+            .query_row(
+                "SELECT expires_at, updated_at, value_json FROM cache_entries WHERE key = ?1",
+                [key],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .unwrap()
+    }
+
+    fn history_count(writer: &SqliteWriter, key: &str) -> usize {
+        writer
+            .conn
+            .query_row(
+                "SELECT COUNT(*) FROM cache_history WHERE key = ?1",
+                [key],
+                |row| row.get::<_, i64>(0),
+            )
+            .unwrap() as usize
+    }
+
+    #[test]
+    fn unchanged_rows_only_extend_expiry() {
+        let mut writer = SqliteWriter::open_in_memory()
+            .unwrap()
+            .with_history()
+            .unwrap();
+        let first = make_row("indicator:rsi_14:AAPL", "AAPL", 300);
+        assert_eq!(
+            writer.upsert_batch(std::slice::from_ref(&first)).unwrap(),
+            1
+        );
+
+        let mut again = make_row("indicator:rsi_14:AAPL", "AAPL", 900);
+        again.updated_at = (Utc::now() + Duration::seconds(60)).to_rfc3339();
+        assert_eq!(writer.upsert_batch(&[again.clone()]).unwrap(), 0);
+        assert_eq!(
+            stored(&writer, &again.key),
+            (
+                again.expires_at.clone(),
+                again.updated_at.clone(),
+                first.value_json.clone()
+            )
+        );
+        assert_eq!(history_count(&writer, &again.key), 1);
+
+        again.value_json = r#"{"value": 43.0}"#.to_string();
+        again.updated_at = (Utc::now() + Duration::seconds(120)).to_rfc3339();
+        assert_eq!(writer.upsert_batch(&[again.clone()]).unwrap(), 1);
+        assert_eq!(stored(&writer, &again.key).1, again.updated_at);
+        assert_eq!(history_count(&writer, &again.key), 2);
+    }
+
+    #[test]
+    fn expired_unchanged_row_is_written_again() {
+        let mut writer = SqliteWriter::open_in_memory().unwrap();
+        let row = make_row("quote:AAPL", "AAPL", -10);
+        writer.upsert(&row).unwrap();
+        assert_eq!(writer.expire_stale().unwrap(), 1);
+
+        assert_eq!(writer.upsert_batch(&[row]).unwrap(), 1);
+        assert_eq!(writer.count().unwrap(), 1);
+    }
+
+    #[test]
+    fn reopened_writer_skips_unchanged_rows() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("test_cache.db");
+        let path = path.to_str().unwrap();
+        let row = make_row("indicator:rsi_14:AAPL", "AAPL", 300);
+        SqliteWriter::open(path).unwrap().upsert(&row).unwrap();
+
+        let mut writer = SqliteWriter::open(path).unwrap();
+        assert_eq!(writer.upsert_batch(&[row]).unwrap(), 0);
+    }
+
+    fn history(writer: &SqliteWriter, key: &str) -> Vec<(String, Option<String>)> {
+        let mut stmt = writer
+            .conn
+            .prepare(
+                "SELECT value_json, valid_to FROM cache_history WHERE key = ?1 ORDER BY valid_from",
+            )
+            .unwrap();
+        stmt.query_map([key], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn history_records_changed_values() {
+        let mut writer = SqliteWriter::open_in_memory()
+            .unwrap()
+            .with_history()
+            .unwrap();
+        let mut row = make_row("indicator:rsi_14:AAPL", "AAPL", 300);
+        writer.upsert(&row).unwrap();
+        writer.upsert(&row).unwrap();
+
+        let first_updated = row.updated_at.clone();
+        row.value_json = r#"{"value": 50.0}"#.to_string();
+        row.updated_at = (Utc::now() + Duration::seconds(60)).to_rfc3339();
+        writer.upsert(&row).unwrap();
+
+        let history = history(&writer, &row.key);
+        assert_eq!(history.len(), 2);
+        assert_eq!(
+            history[0].1.as_deref(),
+            Some(normalize_timestamp(&row.updated_at).as_str())
+        );
+        assert!(normalize_timestamp(&first_updated) < normalize_timestamp(&row.updated_at));
+        assert_eq!(history[1], (row.value_json.clone(), None));
+    }
+
+    #[test]
+    fn history_closes_expired_values_and_prunes() {
+        let mut writer = SqliteWriter::open_in_memory()
+            .unwrap()
+            .with_history()
+            .unwrap();
+        let row = make_row("quote:AAPL", "AAPL", -10);
+        writer.upsert(&row).unwrap();
+        writer.expire_stale().unwrap();
+        assert_eq!(
+            history(&writer, &row.key)[0].1,
+            Some(normalize_timestamp(&row.expires_at))
+        );
+
+        assert_eq!(
+            writer
+                .prune_history(Utc::now() - Duration::days(1))
+                .unwrap(),
+            0
+        );
+        assert_eq!(writer.prune_history(Utc::now()).unwrap(), 1);
+        assert!(history(&writer, &row.key).is_empty());
+    }
+
+    #[test]
+    fn with_history_records_existing_entries() {
+        let mut writer = SqliteWriter::open_in_memory().unwrap();
+        writer.upsert(&make_row("quote:AAPL", "AAPL", 300)).unwrap();
+        let writer = writer.with_history().unwrap().with_history().unwrap();
+        assert_eq!(history(&writer, "quote:AAPL").len(), 1);
+    }
+
+    #[test]
+    fn content_hash_separates_fields() {
+        assert_ne!(
+            content_hash("a", "bc", "d", None),
+            content_hash("ab", "c", "d", None)
+        );
+        assert_ne!(
+            content_hash("a", "b", "c", None),
+            content_hash("a", "b", "c", Some(""))
+        );
+    }
+
+    #[test]
+    fn checkpoint_truncates_wal() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("test_cache.db");
+        let mut writer = SqliteWriter::open(path.to_str().unwrap()).unwrap();
+        let rows: Vec<CacheRow> = (0..100)
+            .map(|i| make_row(&format!("indicator:sma_{i}:AAPL"), "AAPL", 300))
+            .collect();
+        writer.upsert_batch(&rows).unwrap();
+        let before = writer.size().unwrap();
+        assert!(before.wal_bytes > 0);
+        assert!(before.db_bytes > 0);
+
+        assert!(writer.checkpoint().unwrap());
+        assert_eq!(writer.size().unwrap().wal_bytes, 0);
+
+        writer.vacuum().unwrap();
+        assert_eq!(writer.size().unwrap().free_bytes, 0);
+    }
 }
@@ -9,13 +9,15 @@
 //! cargo test -p tirds-loader --test daemon_lifecycle
 //! ```
 
-use std::sync::{Arc, Mutex};
+use std::sync::Arc;
 use std::time::Duration;
 
 use chrono::Utc;
 use tds::prelude::*;
-use tirds_loader::config::StreamConfig;
+use tirds_loader::config::{StreamConfig, WriterConfig};
 use tirds_loader::sources::stream::stream_loop;
+use tirds_loader::status::LoaderStatusTracker;
+use tirds_loader::write_queue::CacheWriter;
 use tirds_loader::writer::SqliteWriter;
 use tirds_models::cache_schema::CacheRow;
 use tokio::sync::broadcast;
@@ -41,7 +43,7 @@ async fn stream_loop_ingests_messages_and_shuts_down() {
     let dir = tempfile::tempdir().unwrap();
     let db_path = dir.path().join("stream_test.db");
     let writer = SqliteWriter::open(db_path.to_str().unwrap()).unwrap();
-    let writer = Arc::new(Mutex::new(writer));
+    let writer = CacheWriter::spawn(writer, &WriterConfig::default());
 
     let (tx, rx) = broadcast::channel::<Arc<StreamMessage>>(16);
     let cancel = CancellationToken::new();
@@ -54,7 +56,15 @@ async fn stream_loop_ingests_messages_and_shuts_down() {
     let cancel_clone = cancel.clone();
     let writer_clone = writer.clone();
     let handle = tokio::spawn(async move {
-        stream_loop(stream_config, writer_clone, rx, 600, cancel_clone).await;
+        stream_loop(
+            stream_config,
+            writer_clone,
+            rx,
+            600,
+            LoaderStatusTracker::new("test", 60),
+            cancel_clone,
+        )
+        .await;
     });
 
     // Send a few messages
@@ -98,8 +108,7 @@ async fn stream_loop_ingests_messages_and_shuts_down() {
         .expect("stream loop panicked");
 
     // Verify both messages were written
-    let w = writer.lock().unwrap();
-    let count = w.count().unwrap();
+    let count = writer.call(|w| w.count()).unwrap();
     assert_eq!(count, 2, "Expected 2 stream entries in the database");
 }
 
@@ -109,7 +118,7 @@ async fn stream_loop_exits_on_channel_close() {
     let dir = tempfile::tempdir().unwrap();
     let db_path = dir.path().join("channel_close.db");
     let writer = SqliteWriter::open(db_path.to_str().unwrap()).unwrap();
-    let writer = Arc::new(Mutex::new(writer));
+    let writer = CacheWriter::spawn(writer, &WriterConfig::default());
 
     let (tx, rx) = broadcast::channel::<Arc<StreamMessage>>(16);
     let cancel = CancellationToken::new();
@@ -120,7 +129,15 @@ async fn stream_loop_exits_on_channel_close() {
     };
 
     let handle = tokio::spawn(async move {
-        stream_loop(stream_config, writer, rx, 600, cancel).await;
+        stream_loop(
+            stream_config,
+            writer,
+            rx,
+            600,
+            LoaderStatusTracker::new("test", 60),
+            cancel,
+        )
+        .await;
     });
 
     // Drop the sender — this closes the channel
@@ -151,21 +168,19 @@ async fn cleanup_loop_expires_stale_entries() {
     writer.upsert_batch(&rows).unwrap();
     assert_eq!(writer.count().unwrap(), 5);
 
-    let writer = Arc::new(Mutex::new(writer));
+    let writer = CacheWriter::spawn(writer, &WriterConfig::default());
     let cancel = CancellationToken::new();
 
     // Run a quick expire
-    {
-        let w = writer.lock().unwrap();
-        let deleted = w.expire_stale().unwrap();
-        assert_eq!(deleted, 3, "Should delete 3 stale entries");
-    }
+    let deleted = writer.call(|w| w.expire_stale()).unwrap();
+    assert_eq!(deleted, 3, "Should delete 3 stale entries");
 
     // Verify only fresh entries remain
-    {
-        let w = writer.lock().unwrap();
-        assert_eq!(w.count().unwrap(), 2, "Only 2 fresh entries should remain");
-    }
+    assert_eq!(
+        writer.call(|w| w.count()).unwrap(),
+        2,
+        "Only 2 fresh entries should remain"
+    );
 
     cancel.cancel();
 }
@@ -176,7 +191,7 @@ async fn cancellation_token_stops_stream_loop_promptly() {
     let dir = tempfile::tempdir().unwrap();
     let db_path = dir.path().join("cancel_test.db");
     let writer = SqliteWriter::open(db_path.to_str().unwrap()).unwrap();
-    let writer = Arc::new(Mutex::new(writer));
+    let writer = CacheWriter::spawn(writer, &WriterConfig::default());
 
     let (_tx, rx) = broadcast::channel::<Arc<StreamMessage>>(16);
     let cancel = CancellationToken::new();
@@ -188,7 +203,15 @@ async fn cancellation_token_stops_stream_loop_promptly() {
 
     let cancel_clone = cancel.clone();
     let handle = tokio::spawn(async move {
-        stream_loop(stream_config, writer, rx, 600, cancel_clone).await;
+        stream_loop(
+            stream_config,
+            writer,
+            rx,
+            600,
+            LoaderStatusTracker::new("test", 60),
+            cancel_clone,
+        )
+        .await;
     });
 
     // Cancel immediately
@@ -208,7 +231,7 @@ async fn stream_loop_upserts_duplicate_keys() {
     let dir = tempfile::tempdir().unwrap();
     let db_path = dir.path().join("upsert_test.db");
     let writer = SqliteWriter::open(db_path.to_str().unwrap()).unwrap();
-    let writer = Arc::new(Mutex::new(writer));
+    let writer = CacheWriter::spawn(writer, &WriterConfig::default());
 
     let (tx, rx) = broadcast::channel::<Arc<StreamMessage>>(16);
     let cancel = CancellationToken::new();
@@ -221,7 +244,15 @@ async fn stream_loop_upserts_duplicate_keys() {
     let cancel_clone = cancel.clone();
     let writer_clone = writer.clone();
     let handle = tokio::spawn(async move {
-        stream_loop(stream_config, writer_clone, rx, 600, cancel_clone).await;
+        stream_loop(
+            stream_config,
+            writer_clone,
+            rx,
+            600,
+            LoaderStatusTracker::new("test", 60),
+            cancel_clone,
+        )
+        .await;
     });
 
     // Send two news messages for AAPL — they'll have the same key (sentiment:news:AAPL)
@@ -246,10 +277,54 @@ async fn stream_loop_upserts_duplicate_keys() {
     handle.await.unwrap();
 
     // Same key gets upserted, so count should be 1
-    let w = writer.lock().unwrap();
     assert_eq!(
-        w.count().unwrap(),
+        writer.call(|w| w.count()).unwrap(),
         1,
         "Duplicate keys should be upserted, not duplicated"
     );
 }
+
+/// A lagging receiver is counted in the loader status.
+#[tokio::test]
+async fn stream_loop_records_lag_in_status() {
+    let writer = CacheWriter::open_in_memory().unwrap();
+
+    let (tx, rx) = broadcast::channel::<Arc<StreamMessage>>(1);
+    let cancel = CancellationToken::new();
+    let status = LoaderStatusTracker::new("test", 60);
+
+    // Overflow the channel before the loop starts receiving
+    for headline in &["one", "two", "three"] {
+        let msg = Arc::new(StreamMessage::new(
+            SourceId::Finnhub,
+            Utc::now(),
+            StreamPayload::News(NewsPayload {
+                headline: (*headline).into(),
+                summary: None,
+                url: None,
+                author: None,
+                category: None,
+            }),
+            MessageMetadata::default().with_tickers(vec![Ticker::equity("AAPL")]),
+        ));
+        tx.send(msg).unwrap();
+    }
+
+    let stream_config = StreamConfig {
+        enabled: true,
+        ttl_seconds: 600,
+    };
+    let cancel_clone = cancel.clone();
+    let status_clone = status.clone();
+    let handle = tokio::spawn(async move {
+        stream_loop(stream_config, writer, rx, 600, status_clone, cancel_clone).await;
+    });
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    cancel.cancel();
+    handle.await.unwrap();
+
+    let snapshot = status.snapshot();
+    assert_eq!(snapshot.stream_lagged_messages, 2);
+    assert!(snapshot.last_refresh.contains_key("stream"));
+}
@@ -0,0 +1,77 @@
+//! Point-in-time reads: the loader records every new cache value in
+//! `cache_history` and the reader rebuilds snapshots as of a past time.
+//!
+//! Run with:
+//! ```bash
+//! cargo test -p tirds-loader --test history
+//! ```
+
+use chrono::{DateTime, Duration, Utc};
+use tirds_cache::{CacheReader, SqliteReader};
+use tirds_loader::writer::SqliteWriter;
+use tirds_models::cache_schema::CacheRow;
+
+fn make_row(key: &str, value_json: &str, updated_at: DateTime<Utc>) -> CacheRow {
+    CacheRow {
+        key: key.to_string(),
+        category: "indicator".to_string(),
+        value_json: value_json.to_string(),
+        source: "history_test".to_string(),
+        symbol: Some("AAPL".to_string()),
+        created_at: updated_at.to_rfc3339(),
+        expires_at: (updated_at + Duration::hours(1)).to_rfc3339(),
+        updated_at: updated_at.to_rfc3339(),
+    }
+}
+
+#[tokio::test]
+async fn snapshot_as_of_replays_earlier_values() {
+    let dir = tempfile::tempdir().unwrap();
+    let db_path = dir.path().join("history.db");
+    let path_str = db_path.to_str().unwrap();
+    let mut writer = SqliteWriter::open(path_str)
+        .unwrap()
+        .with_history()
+        .unwrap();
+
+    let t0 = Utc::now() - Duration::minutes(30);
+    let t1 = t0 + Duration::minutes(10);
+    writer
+        .upsert_batch(&[
+            make_row("indicator:rsi_14:AAPL", r#"{"value": 30.0}"#, t0),
+            make_row("quote:AAPL", r#"{"price": 150.0}"#, t0),
+        ])
+        .unwrap();
+    // The quote is refreshed unchanged; only the RSI moves.
+    writer
+        .upsert_batch(&[
+            make_row("indicator:rsi_14:AAPL", r#"{"value": 45.0}"#, t1),
+            make_row("quote:AAPL", r#"{"price": 150.0}"#, t1),
+        ])
+        .unwrap();
+
+    let reader = CacheReader::new(
+        SqliteReader::open(path_str).unwrap(),
+        100,
+        std::time::Duration::from_secs(60),
+    );
+    let before = reader
+        .build_domain_snapshot_as_of("AAPL", t0 + Duration::minutes(5))
+        .await
+        .unwrap();
+    assert_eq!(before["indicator:rsi_14:AAPL"]["value"], 30.0);
+    assert_eq!(before["quote:AAPL"]["price"], 150.0);
+
+    let after = reader
+        .build_snapshot_as_of("AAPL", Utc::now())
+        .await
+        .unwrap();
+    assert_eq!(after.data["indicator:rsi_14:AAPL"]["value"], 45.0);
+    assert_eq!(after.sources.len(), 2);
+
+    let earliest = reader
+        .get_as_of("quote:AAPL", t0 - Duration::seconds(1))
+        .await
+        .unwrap();
+    assert!(earliest.is_none());
+}
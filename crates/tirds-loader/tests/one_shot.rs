@@ -0,0 +1,205 @@
+//! Integration tests for the one-shot loader commands (`once`, `backfill`,
+//! `recompute`) against a files provider and a file-backed SQLite cache.
+//!
+//! Run with:
+//! ```bash
+//! cargo test -p tirds-loader --test one_shot
+//! ```
+
+use std::fmt::Write as _;
+use std::path::Path;
+
+use chrono::{Datelike, Duration, Utc, Weekday};
+use tirds_loader::config::LoaderConfig;
+use tirds_loader::daemon::{backfill, Daemon};
+use tirds_loader::sources::providers::ProviderChain;
+use tirds_loader::writer::SqliteWriter;
+
+/// One daily bar per weekday over the last `days` calendar days.
+fn write_import(dir: &Path, symbol: &str, days: i64) {
+    let today = Utc::now().date_naive();
+    let mut csv = String::from("timestamp,open,high,low,close,volume\n");
+    for offset in (1..=days).rev() {
+        let date = today - Duration::days(offset);
+        if matches!(date.weekday(), Weekday::Sat | Weekday::Sun) {
+            continue;
+        }
+        let close = 100.0 + (offset % 7) as f64;
+        writeln!(
+            csv,
+            "{date}T15:00:00Z,{close},{},{},{close},100000",
+            close + 1.0,
+            close - 1.0
+        )
+        .unwrap();
+    }
+    std::fs::write(dir.join(format!("{symbol}.csv")), csv).unwrap();
+}
+
+fn daemon(root: &Path) -> (Daemon, String) {
+    let imports = root.join("imports");
+    std::fs::create_dir_all(&imports).unwrap();
+    write_import(&imports, "AAPL", 60);
+    let db_path = root.join("cache.db").to_str().unwrap().to_string();
+
+    let toml_str = format!(
+        r#"
+[cache]
+sqlite_path = "{db_path}"
+
+[market_data]
+data_path = "{data}"
+symbols = ["AAPL"]
+reference_symbols = []
+provider = "files"
+lookback_days = 60
+ttl_seconds = 600
+
+[market_data.files]
+path = "{imports}"
+
+[calculations]
+indicators = ["sma"]
+ttl_seconds = 600
+
+[stream]
+enabled = false
+ttl_seconds = 600
+"#,
+        data = root.join("data").display(),
+        imports = imports.display(),
+    );
+    let config: LoaderConfig = toml::from_str(&toml_str).unwrap();
+    let writer = SqliteWriter::open(&db_path).unwrap();
+    (Daemon::new(config, writer), db_path)
+}
+
+fn category_count(db_path: &str, category: &str) -> usize {
+    let conn = rusqlite::Connection::open(db_path).unwrap();
+    conn.query_row(
+        "SELECT COUNT(*) FROM cache_entries WHERE category = ?1",
+        [category],
+        |row| row.get::<_, i64>(0),
+    )
+    .unwrap() as usize
+}
+
+/// Backfill fills the local store without writing cache rows; recompute then
+/// builds indicator rows from the store alone.
+#[tokio::test]
+async fn backfill_then_recompute_from_local_data() {
+    let dir = tempfile::tempdir().unwrap();
+    let (daemon, db_path) = daemon(dir.path());
+    let today = Utc::now().date_naive();
+
+    let summary = daemon
+        .backfill(&["AAPL".to_string()], today - Duration::days(60), today)
+        .await
+        .unwrap();
+    assert!(summary.errors.is_empty());
+    assert!(summary.days_written["AAPL"] > 20);
+    assert_eq!(SqliteWriter::open(&db_path).unwrap().count().unwrap(), 0);
+
+    // Imports are gone: recompute must not need the provider.
+    std::fs::remove_dir_all(dir.path().join("imports")).unwrap();
+    let summary = daemon.recompute().await;
+    assert!(summary.is_ok(), "{summary:?}");
+    assert_eq!(summary.symbols_loaded, 1);
+    assert_eq!(summary.market_data_rows, 0);
+    assert!(summary.indicator_rows > 0);
+    assert_eq!(category_count(&db_path, "market_data"), 0);
+    assert!(category_count(&db_path, "indicator") > 0);
+}
+
+/// A single cycle fills, caches and computes everything, then returns.
+#[tokio::test]
+async fn run_once_reports_rows_written() {
+    let dir = tempfile::tempdir().unwrap();
+    let (daemon, db_path) = daemon(dir.path());
+
+    let summary = daemon.run_once().await;
+    assert!(summary.is_ok(), "{summary:?}");
+    assert_eq!(summary.symbols, 1);
+    assert_eq!(summary.symbols_loaded, 1);
+    assert!(summary.market_data_rows > 0);
+    assert!(summary.indicator_rows > 0);
+    assert!(summary.symbol_errors.is_empty());
+    assert!(category_count(&db_path, "market_data") > 0);
+    assert!(daemon
+        .status()
+        .snapshot()
+        .last_refresh
+        .contains_key("calculations"));
+    assert!(daemon
+        .status()
+        .snapshot()
+        .last_refresh
+        .contains_key("market_data"));
+}
+
+/// Backfill needs no cache writer, so the cache file is never created.
+#[tokio::test]
+async fn backfill_without_a_cache() {
+    let dir = tempfile::tempdir().unwrap();
+    let (daemon, _) = daemon(dir.path());
+    let mut config = daemon.config().clone();
+    let db_path = dir.path().join("untouched.db");
+    config.cache.sqlite_path = db_path.to_str().unwrap().to_string();
+    let providers = ProviderChain::from_config(&config.market_data);
+
+    let today = Utc::now().date_naive();
+    let summary = backfill(
+        &config,
+        &providers,
+        &["AAPL".to_string()],
+        today - Duration::days(30),
+        today,
+    )
+    .await
+    .unwrap();
+    assert!(summary.days_written["AAPL"] > 0);
+    assert!(!db_path.exists());
+}
+
+#[tokio::test]
+async fn backfill_requires_a_provider() {
+    let dir = tempfile::tempdir().unwrap();
+    let (daemon, _) = daemon(dir.path());
+    let mut config = daemon.config().clone();
+    config.market_data.provider.clear();
+    let db_path = dir.path().join("other.db");
+    let daemon = Daemon::new(
+        config,
+        SqliteWriter::open(db_path.to_str().unwrap()).unwrap(),
+    );
+
+    let today = Utc::now().date_naive();
+    assert!(daemon.backfill(&[], today, today).await.is_err());
+}
+
+/// Rows that fail to commit make the cycle fail instead of being dropped quietly.
+#[tokio::test]
+async fn run_once_reports_failed_cache_writes() {
+    let dir = tempfile::tempdir().unwrap();
+    let (daemon, db_path) = daemon(dir.path());
+    rusqlite::Connection::open(&db_path)
+        .unwrap()
+        .execute_batch("DROP TABLE cache_entries")
+        .unwrap();
+
+    let summary = daemon.run_once().await;
+    assert!(!summary.is_ok());
+    assert!(
+        summary.step_errors.contains_key("cache_write"),
+        "{summary:?}"
+    );
+    assert!(summary.write_errors > 0);
+    assert_eq!(summary.market_data_rows, 0);
+    assert!(summary.symbol_errors.contains_key("AAPL"));
+    assert!(summary.step_errors.contains_key("market_data"));
+    let last_refresh = daemon.status().snapshot().last_refresh;
+    assert!(
+        !last_refresh.contains_key("market_data"),
+        "{last_refresh:?}"
+    );
+}
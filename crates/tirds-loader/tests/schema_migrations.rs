@@ -0,0 +1,60 @@
+//! Schema versioning across the shared cache: the loader migrates database
+//! files forward and the read-only evaluator reader checks the version.
+//!
+//! Run with:
+//! ```bash
+//! cargo test -p tirds-loader --test schema_migrations
+//! ```
+
+use tirds_cache::{CacheError, SqliteReader};
+use tirds_loader::error::LoaderError;
+use tirds_loader::writer::SqliteWriter;
+use tirds_models::migrations::CURRENT_SCHEMA_VERSION;
+
+/// A database written before versioning is migrated by the loader and then
+/// read at the current version.
+#[test]
+fn loader_migrates_legacy_file_for_reader() {
+    let dir = tempfile::tempdir().unwrap();
+    let db_path = dir.path().join("legacy.db");
+    let path_str = db_path.to_str().unwrap();
+    rusqlite::Connection::open(path_str)
+        .unwrap()
+        .execute_batch(tirds_models::cache_schema::CACHE_TABLE_DDL)
+        .unwrap();
+    assert_eq!(SqliteReader::open(path_str).unwrap().schema_version(), 0);
+
+    drop(SqliteWriter::open(path_str).unwrap());
+
+    let reader = SqliteReader::open(path_str).unwrap();
+    assert_eq!(reader.schema_version(), CURRENT_SCHEMA_VERSION);
+    assert!(reader.get("quote:AAPL").unwrap().is_none());
+}
+
+/// A database migrated past what this build understands (with a change that
+/// breaks older readers) is refused by both sides.
+#[test]
+fn newer_breaking_schema_is_refused() {
+    let dir = tempfile::tempdir().unwrap();
+    let db_path = dir.path().join("newer.db");
+    let path_str = db_path.to_str().unwrap();
+    drop(SqliteWriter::open(path_str).unwrap());
+    let future = (CURRENT_SCHEMA_VERSION + 1).to_string();
+    rusqlite::Connection::open(path_str)
+        .unwrap()
+        .execute(
+            "UPDATE schema_meta SET value = ?1 \
+             WHERE key IN ('schema_version', 'min_reader_version')",
+            [&future],
+        )
+        .unwrap();
+
+    assert!(matches!(
+        SqliteReader::open(path_str),
+        Err(CacheError::UnsupportedSchema { .. })
+    ));
+    assert!(matches!(
+        SqliteWriter::open(path_str),
+        Err(LoaderError::Schema(_))
+    ));
+}
@@ -0,0 +1,73 @@
+//! Integration tests for on-demand symbol requests: the evaluator enqueues via
+//! `tirds_cache::SymbolDemand` and the loader picks them up via `SqliteWriter`
+//! on the same file-backed database.
+//!
+//! Run with:
+//! ```bash
+//! cargo test -p tirds-loader --test symbol_requests
+//! ```
+
+use tirds_cache::{CacheError, SymbolDemand};
+use tirds_loader::writer::SqliteWriter;
+use tirds_models::migrations::CURRENT_SCHEMA_VERSION;
+use tirds_models::symbol_request::SymbolRequestStatus;
+
+#[tokio::test]
+async fn evaluator_request_is_seen_and_completed_by_loader() {
+    let dir = tempfile::tempdir().unwrap();
+    let db_path = dir.path().join("requests.db");
+    let path_str = db_path.to_str().unwrap();
+
+    let writer = SqliteWriter::open(path_str).unwrap();
+    let demand = SymbolDemand::open(path_str).unwrap();
+
+    demand.request("NVDA").await.unwrap();
+    demand.request("AMD").await.unwrap();
+    assert_eq!(writer.pending_symbol_requests().unwrap().len(), 2);
+
+    writer.complete_symbol_request("NVDA", None).unwrap();
+    writer
+        .complete_symbol_request("AMD", Some("No candles available"))
+        .unwrap();
+    assert!(writer.pending_symbol_requests().unwrap().is_empty());
+
+    let nvda = demand.get("NVDA").await.unwrap().unwrap();
+    assert_eq!(nvda.status, SymbolRequestStatus::Loaded);
+    assert!(nvda.completed_at.is_some());
+
+    let amd = demand.get("AMD").await.unwrap().unwrap();
+    assert_eq!(amd.status, SymbolRequestStatus::Failed);
+    assert_eq!(amd.error.as_deref(), Some("No candles available"));
+}
+
+#[test]
+fn demand_cannot_create_missing_database() {
+    let dir = tempfile::tempdir().unwrap();
+    let db_path = dir.path().join("missing.db");
+    assert!(SymbolDemand::open(db_path.to_str().unwrap()).is_err());
+}
+
+#[test]
+fn demand_rejects_unsupported_schema() {
+    let dir = tempfile::tempdir().unwrap();
+    let db_path = dir.path().join("newer.db");
+    let path_str = db_path.to_str().unwrap();
+    SqliteWriter::open(path_str).unwrap();
+
+    let conn = rusqlite::Connection::open(path_str).unwrap();
+    conn.execute(
+        "INSERT OR REPLACE INTO schema_meta (key, value) VALUES ('min_reader_version', ?1)",
+        [(CURRENT_SCHEMA_VERSION + 1).to_string()],
+    )
+    .unwrap();
+    conn.execute(
+        "UPDATE schema_meta SET value = ?1 WHERE key = 'schema_version'",
+        [(CURRENT_SCHEMA_VERSION + 1).to_string()],
+    )
+    .unwrap();
+
+    assert!(matches!(
+        SymbolDemand::open(path_str),
+        Err(CacheError::UnsupportedSchema { .. })
+    ));
+}
@@ -225,3 +225,40 @@ fn expire_stale_during_concurrent_reads() {
         "Fresh entries should survive cleanup"
     );
 }
+
+/// Rewriting unchanged values only updates their timestamps, so a refresh that
+/// changes nothing grows the WAL far less than one that changes every value.
+#[test]
+fn unchanged_refresh_writes_less_wal() {
+    let dir = tempfile::tempdir().unwrap();
+    let db_path = dir.path().join("churn.db");
+    let mut writer = SqliteWriter::open(db_path.to_str().unwrap()).unwrap();
+
+    // Bar-sized values that spill into overflow pages
+    let bars = |value: f64| -> Vec<CacheRow> {
+        (0..100)
+            .map(|i| {
+                let mut row = make_row(&format!("bars:1d:SYM{i}"), "AAPL", value, 600);
+                row.value_json = format!("[{}]", vec![value.to_string(); 2000].join(","));
+                // Fixed-width timestamps, so moving them forward rewrites the row in place
+                row.expires_at = (Utc::now() + Duration::seconds(600))
+                    .to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+                row.updated_at = Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+                row
+            })
+            .collect()
+    };
+    let wal_growth = |writer: &mut SqliteWriter, rows: &[CacheRow]| {
+        assert!(writer.checkpoint().unwrap());
+        writer.upsert_batch(rows).unwrap();
+        writer.size().unwrap().wal_bytes
+    };
+
+    writer.upsert_batch(&bars(1.0)).unwrap();
+    let unchanged = wal_growth(&mut writer, &bars(1.0));
+    let changed = wal_growth(&mut writer, &bars(2.5));
+    assert!(
+        unchanged * 2 < changed,
+        "unchanged refresh wrote {unchanged} WAL bytes, changed refresh {changed}"
+    );
+}
@@ -1,3 +1,4 @@
+use chrono::{DateTime, SecondsFormat, Utc};
 use serde::{Deserialize, Serialize};
 
 /// Categories for organizing cache keys.
@@ -10,6 +11,7 @@ pub enum CacheCategory {
     ReferenceSymbol,
     Subscription,
     Sentiment,
+    Meta,
 }
 
 /// The expected SQLite table schema that the data pipeline must write to
@@ -47,6 +49,90 @@ CREATE INDEX IF NOT EXISTS idx_cache_symbol ON cache_entries(symbol);
 CREATE INDEX IF NOT EXISTS idx_cache_expires ON cache_entries(expires_at);
 ";
 
+/// Single-row table holding the loader's heartbeat as a JSON-serialized
+/// [`LoaderStatus`](crate::loader_status::LoaderStatus).
+///
+/// ```sql
+/// CREATE TABLE IF NOT EXISTS loader_status (
+///     id          INTEGER PRIMARY KEY CHECK (id = 1),
+///     status_json TEXT NOT NULL,
+///     updated_at  TEXT NOT NULL
+/// );
+/// ```
+pub const LOADER_STATUS_DDL: &str = "\
+CREATE TABLE IF NOT EXISTS loader_status (
+    id          INTEGER PRIMARY KEY CHECK (id = 1),
+    status_json TEXT NOT NULL,
+    updated_at  TEXT NOT NULL
+);
+";
+
+/// Queue of symbols the evaluator wants loaded that are outside the loader's
+/// configured symbol list. This is the only table the evaluator writes to;
+/// the loader polls it for `pending` rows and marks them `loaded` or `failed`.
+///
+/// ```sql
+/// CREATE TABLE IF NOT EXISTS symbol_requests (
+///     symbol       TEXT PRIMARY KEY,
+///     status       TEXT NOT NULL,
+///     requested_at TEXT NOT NULL,
+///     completed_at TEXT,
+///     error        TEXT
+/// );
+/// ```
+pub const SYMBOL_REQUESTS_DDL: &str = "\
+CREATE TABLE IF NOT EXISTS symbol_requests (
+    symbol       TEXT PRIMARY KEY,
+    status       TEXT NOT NULL,
+    requested_at TEXT NOT NULL,
+    completed_at TEXT,
+    error        TEXT
+);
+";
+
+/// Append-only history of cache values, maintained by the loader. Each row
+/// is one value of a key and the interval it was current for: `valid_to` is
+/// set when the value is replaced or expires, and is NULL while it is current.
+/// Timestamps are written with [`history_timestamp`] so they sort as text.
+///
+/// ```sql
+/// CREATE TABLE IF NOT EXISTS cache_history (
+///     key         TEXT NOT NULL,
+///     value_hash  INTEGER NOT NULL,
+///     value_json  TEXT NOT NULL,
+///     category    TEXT NOT NULL,
+///     source      TEXT NOT NULL,
+///     symbol      TEXT,
+///     valid_from  TEXT NOT NULL,
+///     valid_to    TEXT,
+///     PRIMARY KEY (key, valid_from)
+/// );
+///
+/// CREATE INDEX IF NOT EXISTS idx_history_symbol ON cache_history(symbol, valid_from);
+/// CREATE INDEX IF NOT EXISTS idx_history_valid_to ON cache_history(valid_to);
+/// ```
+pub const CACHE_HISTORY_DDL: &str = "\
+CREATE TABLE IF NOT EXISTS cache_history (
+    key         TEXT NOT NULL,
+    value_hash  INTEGER NOT NULL,
+    value_json  TEXT NOT NULL,
+    category    TEXT NOT NULL,
+    source      TEXT NOT NULL,
+    symbol      TEXT,
+    valid_from  TEXT NOT NULL,
+    valid_to    TEXT,
+    PRIMARY KEY (key, valid_from)
+);
+CREATE INDEX IF NOT EXISTS idx_history_symbol ON cache_history(symbol, valid_from);
+CREATE INDEX IF NOT EXISTS idx_history_valid_to ON cache_history(valid_to);
+";
+
+/// Fixed-width RFC3339 form (microseconds, `Z`) used for `cache_history`
+/// timestamps, so that text comparison matches time order.
+pub fn history_timestamp(ts: DateTime<Utc>) -> String {
+    ts.to_rfc3339_opts(SecondsFormat::Micros, true)
+}
+
 /// Key pattern conventions for the cache.
 ///
 /// Data pipelines should use these patterns when writing cache entries
@@ -57,6 +143,10 @@ CREATE INDEX IF NOT EXISTS idx_cache_expires ON cache_entries(expires_at);
 /// - Indicators: `indicator:{name}:{symbol}` (e.g., `indicator:rsi_14:AAPL`)
 /// - Reference symbols: `ref:{symbol}` (e.g., `ref:SPY`, `ref:VIX`)
 /// - Sentiment: `sentiment:{source}:{symbol}` (e.g., `sentiment:twitter:AAPL`)
+/// - Symbol metadata: `meta:{symbol}` (e.g., `meta:AAPL`, sector and sector ETF)
+/// - Support/resistance: `levels:{symbol}` (e.g., `levels:AAPL`)
+/// - Data quality: `quality:{symbol}` (e.g., `quality:AAPL`)
+/// - Regime: `regime:{scope}` where scope is `market` or a symbol (e.g., `regime:market`, `regime:AAPL`)
 pub mod key_patterns {
     pub fn bars(symbol: &str, timeframe: &str) -> String {
         format!("bars:{symbol}:{timeframe}")
@@ -77,10 +167,26 @@ pub mod key_patterns {
     pub fn sentiment(source: &str, symbol: &str) -> String {
         format!("sentiment:{source}:{symbol}")
     }
+
+    pub fn meta(symbol: &str) -> String {
+        format!("meta:{symbol}")
+    }
+
+    pub fn levels(symbol: &str) -> String {
+        format!("levels:{symbol}")
+    }
+
+    pub fn quality(symbol: &str) -> String {
+        format!("quality:{symbol}")
+    }
+
+    pub fn regime(scope: &str) -> String {
+        format!("regime:{scope}")
+    }
 }
 
 /// A raw cache row as read from SQLite.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CacheRow {
     pub key: String,
     pub category: String,
@@ -96,6 +202,16 @@ pub struct CacheRow {
 mod tests {
     use super::*;
 
+    #[test]
+    fn history_timestamps_sort_as_text() {
+        let whole = DateTime::parse_from_rfc3339("2026-03-02T10:32:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let later = whole + chrono::Duration::milliseconds(500);
+        assert_eq!(history_timestamp(whole), "2026-03-02T10:32:00.000000Z");
+        assert!(history_timestamp(whole) < history_timestamp(later));
+    }
+
     #[test]
     fn key_pattern_bars() {
         assert_eq!(key_patterns::bars("AAPL", "1d"), "bars:AAPL:1d");
@@ -127,6 +243,27 @@ mod tests {
         );
     }
 
+    #[test]
+    fn key_pattern_meta() {
+        assert_eq!(key_patterns::meta("AAPL"), "meta:AAPL");
+    }
+
+    #[test]
+    fn key_pattern_levels() {
+        assert_eq!(key_patterns::levels("AAPL"), "levels:AAPL");
+    }
+
+    #[test]
+    fn key_pattern_quality() {
+        assert_eq!(key_patterns::quality("AAPL"), "quality:AAPL");
+    }
+
+    #[test]
+    fn key_pattern_regime() {
+        assert_eq!(key_patterns::regime("market"), "regime:market");
+        assert_eq!(key_patterns::regime("AAPL"), "regime:AAPL");
+    }
+
     #[test]
     fn cache_category_roundtrip() {
         let categories = vec![
@@ -135,6 +272,7 @@ mod tests {
             CacheCategory::ReferenceSymbol,
             CacheCategory::Subscription,
             CacheCategory::Sentiment,
+            CacheCategory::Meta,
         ];
         for cat in categories {
             let json = serde_json::to_string(&cat).unwrap();
@@ -1,3 +1,6 @@
+use std::collections::BTreeMap;
+
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
 /// Top-level configuration for TIRDS.
@@ -15,7 +18,15 @@ pub struct CacheConfig {
     /// Maximum number of entries in the in-memory moka cache.
     pub memory_max_capacity: u64,
     /// Default TTL in seconds for moka entries (how long to keep a read in memory).
+    /// Entries whose row expires sooner are kept only until then.
     pub memory_ttl_seconds: u64,
+    /// Read-only SQLite connections shared by concurrent cache reads.
+    #[serde(default = "default_read_connections")]
+    pub read_connections: usize,
+}
+
+fn default_read_connections() -> usize {
+    4
 }
 
 impl Default for CacheConfig {
@@ -24,6 +35,7 @@ impl Default for CacheConfig {
             sqlite_path: "data/tirds_cache.db".to_string(),
             memory_max_capacity: 10_000,
             memory_ttl_seconds: 60,
+            read_connections: default_read_connections(),
         }
     }
 }
@@ -41,6 +53,12 @@ pub struct AgentsConfig {
     pub specialist_model: String,
     /// List of specialist agent configurations.
     pub specialists: Vec<SpecialistConfig>,
+    /// Maximum data ages and what to do when the snapshot exceeds them.
+    #[serde(default)]
+    pub staleness: StalenessConfig,
+    /// Requesting data from the loader for symbols missing from the cache.
+    #[serde(default)]
+    pub on_demand: OnDemandConfig,
 }
 
 impl Default for AgentsConfig {
@@ -76,6 +94,8 @@ impl Default for AgentsConfig {
                     enabled: true,
                 },
             ],
+            staleness: StalenessConfig::default(),
+            on_demand: OnDemandConfig::default(),
         }
     }
 }
@@ -90,6 +110,100 @@ pub struct SpecialistConfig {
     pub enabled: bool,
 }
 
+/// What the orchestrator does when snapshot data is older than allowed.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum StalenessPolicy {
+    /// Log and note stale entries in the decision's trade intelligence.
+    #[default]
+    Warn,
+    /// Same as `Warn`, and multiply confidence scores by `downgrade_factor`.
+    Downgrade,
+    /// Refuse to evaluate; returns `AgentError::StaleData`.
+    Fail,
+}
+
+/// Staleness guard applied to the domain snapshot before evaluation.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StalenessConfig {
+    #[serde(default)]
+    pub policy: StalenessPolicy,
+    /// Multiplier applied to confidence scores under `StalenessPolicy::Downgrade`.
+    /// Must be between 0 and 1.
+    #[serde(
+        default = "default_downgrade_factor",
+        deserialize_with = "deserialize_downgrade_factor"
+    )]
+    pub downgrade_factor: Decimal,
+    /// Maximum age in seconds per key family, measured from the row's `updated_at`.
+    /// A family matches keys that start with `{family}:`; the longest match wins
+    /// (e.g., `quote`, `indicator`, `sentiment:news`). Keys with no match are not checked.
+    #[serde(default)]
+    pub max_age_seconds: BTreeMap<String, u64>,
+}
+
+impl Default for StalenessConfig {
+    fn default() -> Self {
+        Self {
+            policy: StalenessPolicy::default(),
+            downgrade_factor: default_downgrade_factor(),
+            max_age_seconds: BTreeMap::new(),
+        }
+    }
+}
+
+fn default_downgrade_factor() -> Decimal {
+    Decimal::new(5, 1)
+}
+
+fn deserialize_downgrade_factor<'de, D>(deserializer: D) -> Result<Decimal, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let factor = <Decimal as Deserialize>::deserialize(deserializer)?;
+    if factor < Decimal::ZERO || factor > Decimal::ONE {
+        return Err(serde::de::Error::custom(format!(
+            "downgrade_factor must be between 0 and 1, got {factor}"
+        )));
+    }
+    Ok(factor)
+}
+
+/// On-demand loading for symbols the loader does not already cover.
+///
+/// When enabled, a snapshot with no market data enqueues the symbol in the
+/// cache's `symbol_requests` table, and the orchestrator waits up to
+/// `wait_seconds` for the loader to fill it. Requires write access to the
+/// cache file.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct OnDemandConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Maximum time to wait for the loader. Zero enqueues without waiting.
+    #[serde(default = "default_on_demand_wait")]
+    pub wait_seconds: u64,
+    /// How often to check the request's status while waiting.
+    #[serde(default = "default_on_demand_poll")]
+    pub poll_interval_millis: u64,
+}
+
+impl Default for OnDemandConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            wait_seconds: default_on_demand_wait(),
+            poll_interval_millis: default_on_demand_poll(),
+        }
+    }
+}
+
+fn default_on_demand_wait() -> u64 {
+    30
+}
+fn default_on_demand_poll() -> u64 {
+    500
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -142,5 +256,46 @@ enabled = false
         assert_eq!(config.cache.sqlite_path, "/tmp/test_cache.db");
         assert_eq!(config.agents.specialists.len(), 2);
         assert!(!config.agents.specialists[1].enabled);
+        // Staleness guard defaults to warn with no limits when omitted
+        assert_eq!(config.agents.staleness, StalenessConfig::default());
+    }
+
+    #[test]
+    fn staleness_config_from_toml() {
+        let toml_str = r#"
+policy = "downgrade"
+downgrade_factor = "0.75"
+
+[max_age_seconds]
+quote = 60
+indicator = 600
+"sentiment:news" = 1800
+"#;
+
+        let config: StalenessConfig = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.policy, StalenessPolicy::Downgrade);
+        assert_eq!(config.downgrade_factor, Decimal::new(75, 2));
+        assert_eq!(config.max_age_seconds.get("quote"), Some(&60));
+        assert_eq!(config.max_age_seconds.get("sentiment:news"), Some(&1800));
+    }
+
+    #[test]
+    fn staleness_rejects_downgrade_factor_out_of_range() {
+        for factor in ["1.5", "-0.1"] {
+            let toml_str = format!("downgrade_factor = \"{factor}\"");
+            let err = toml::from_str::<StalenessConfig>(&toml_str).unwrap_err();
+            assert!(err.to_string().contains("between 0 and 1"), "{err}");
+        }
+        let edge: StalenessConfig = toml::from_str(r#"downgrade_factor = "1""#).unwrap();
+        assert_eq!(edge.downgrade_factor, Decimal::ONE);
+    }
+
+    #[test]
+    fn on_demand_defaults_when_partial() {
+        let config: OnDemandConfig = toml::from_str("enabled = true").unwrap();
+        assert!(config.enabled);
+        assert_eq!(config.wait_seconds, 30);
+        assert_eq!(config.poll_interval_millis, 500);
+        assert!(!AgentsConfig::default().on_demand.enabled);
     }
 }
@@ -0,0 +1,129 @@
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+/// Support and resistance levels for a symbol, written by `tirds-loader` as
+/// `levels:{symbol}`. Prices are decimal strings in JSON.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PriceLevels {
+    /// Timestamp of the most recent bar the levels were computed from.
+    pub as_of: DateTime<Utc>,
+    /// High/low/close of the session before the latest one.
+    pub prior_day: Option<PriorDay>,
+    /// Classic floor-trader pivots derived from `prior_day`.
+    pub pivots: Option<PivotPoints>,
+    /// Confirmed swing highs, oldest first.
+    pub swing_highs: Vec<SwingPoint>,
+    /// Confirmed swing lows, oldest first.
+    pub swing_lows: Vec<SwingPoint>,
+    pub volume_profile: Option<VolumeProfile>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PriorDay {
+    /// Session start of the prior day.
+    pub date: DateTime<Utc>,
+    pub high: Decimal,
+    pub low: Decimal,
+    pub close: Decimal,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PivotPoints {
+    pub pivot: Decimal,
+    pub r1: Decimal,
+    pub r2: Decimal,
+    pub r3: Decimal,
+    pub s1: Decimal,
+    pub s2: Decimal,
+    pub s3: Decimal,
+}
+
+impl PivotPoints {
+    /// Classic pivots: P = (H + L + C) / 3, R1 = 2P − L, S1 = 2P − H,
+    /// R2 = P + (H − L), S2 = P − (H − L), R3 = H + 2(P − L), S3 = L − 2(H − P).
+    pub fn classic(high: Decimal, low: Decimal, close: Decimal) -> Self {
+        let pivot = (high + low + close) / Decimal::from(3);
+        let range = high - low;
+        let two = Decimal::from(2);
+        let round = |d: Decimal| d.round_dp(4);
+        Self {
+            pivot: round(pivot),
+            r1: round(two * pivot - low),
+            r2: round(pivot + range),
+            r3: round(high + two * (pivot - low)),
+            s1: round(two * pivot - high),
+            s2: round(pivot - range),
+            s3: round(low - two * (high - pivot)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SwingPoint {
+    pub price: Decimal,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Volume traded per price bucket over the loader's lookback window.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VolumeProfile {
+    /// Point of control: midpoint of the highest-volume bucket.
+    pub poc: Decimal,
+    /// Upper and lower bounds of the value area around the POC.
+    pub value_area_high: Decimal,
+    pub value_area_low: Decimal,
+    /// Highest-volume buckets, sorted by price.
+    pub nodes: Vec<VolumeNode>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VolumeNode {
+    pub price: Decimal,
+    pub volume: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn classic_pivots() {
+        let p = PivotPoints::classic(dec!(110), dec!(100), dec!(105));
+        assert_eq!(p.pivot, dec!(105));
+        assert_eq!(p.r1, dec!(110));
+        assert_eq!(p.s1, dec!(100));
+        assert_eq!(p.r2, dec!(115));
+        assert_eq!(p.s2, dec!(95));
+        assert_eq!(p.r3, dec!(120));
+        assert_eq!(p.s3, dec!(90));
+    }
+
+    #[test]
+    fn pivots_are_rounded() {
+        let p = PivotPoints::classic(dec!(10), dec!(9), dec!(9.5));
+        assert_eq!(p.pivot, dec!(9.5));
+        let p = PivotPoints::classic(dec!(10), dec!(9), dec!(9));
+        assert_eq!(p.pivot, dec!(9.3333));
+    }
+
+    #[test]
+    fn levels_roundtrip_with_string_prices() {
+        let levels = PriceLevels {
+            as_of: Utc::now(),
+            prior_day: None,
+            pivots: None,
+            swing_highs: vec![SwingPoint {
+                price: dec!(151.25),
+                timestamp: Utc::now(),
+            }],
+            swing_lows: vec![],
+            volume_profile: None,
+        };
+        let json = serde_json::to_value(&levels).unwrap();
+        assert_eq!(json["swing_highs"][0]["price"], "151.25");
+        let parsed: PriceLevels = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, levels);
+    }
+}
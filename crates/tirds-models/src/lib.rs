@@ -1,14 +1,33 @@
 pub mod agent_message;
 pub mod cache_schema;
 pub mod config;
+pub mod levels;
+pub mod loader_status;
+pub mod migrations;
+pub mod quality;
+pub mod regime;
+pub mod sector;
+pub mod symbol_request;
 pub mod trade_decision;
 pub mod trade_input;
 
 pub use agent_message::{AgentRequest, AgentResponse};
 pub use cache_schema::{CacheCategory, CacheRow};
-pub use config::{AgentsConfig, CacheConfig, SpecialistConfig, TirdsConfig};
+pub use config::{
+    AgentsConfig, CacheConfig, OnDemandConfig, SpecialistConfig, StalenessConfig, StalenessPolicy,
+    TirdsConfig,
+};
+pub use levels::{PivotPoints, PriceLevels, PriorDay, SwingPoint, VolumeNode, VolumeProfile};
+pub use loader_status::LoaderStatus;
+pub use quality::{DataQuality, QualityIssue, QualityIssueKind, QualityResolution};
+pub use regime::{
+    BreadthRegime, MarketRegime, RiskRegime, SymbolRegime, TrendRegime, VolatilityRegime,
+};
+pub use sector::SymbolMeta;
+pub use symbol_request::{SymbolRequest, SymbolRequestStatus};
 pub use trade_decision::{
-    AgentReport, ConfidenceScore, DecayModel, DecayProfile, InformationRelevance, LegAssessment,
-    PriceAssessment, SourceContribution, TimelinePoint, TradeDecision, TradeIntelligence,
+    AgentProvenance, AgentReport, ConfidenceScore, DataSourceRef, DecayModel, DecayProfile,
+    DecisionProvenance, InformationRelevance, LegAssessment, PriceAssessment, SourceContribution,
+    TimelinePoint, TradeDecision, TradeIntelligence,
 };
 pub use trade_input::{LegSide, TradeContext, TradeLeg, TradeProposal};
@@ -0,0 +1,121 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Heartbeat record written by `tirds-loader` into the shared cache each cycle.
+///
+/// Stored as a single row in the `loader_status` table (see
+/// [`LOADER_STATUS_DDL`](crate::cache_schema::LOADER_STATUS_DDL)).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LoaderStatus {
+    /// When the loader process started.
+    pub started_at: DateTime<Utc>,
+    /// Loader crate version.
+    pub version: String,
+    /// When this record was last written.
+    pub heartbeat_at: DateTime<Utc>,
+    /// Expected seconds between heartbeats (the loader's refresh interval).
+    pub heartbeat_interval_seconds: u64,
+    /// Task name (e.g., "market_data", "calculations") → last successful run.
+    pub last_refresh: BTreeMap<String, DateTime<Utc>>,
+    /// Symbol → most recent error. Cleared when the symbol next loads successfully.
+    pub symbol_errors: BTreeMap<String, String>,
+    /// Total stream messages dropped because the receiver lagged.
+    pub stream_lagged_messages: u64,
+    /// Cache writer queue and batching counters.
+    #[serde(default)]
+    pub writer: WriterStats,
+    /// Size of the cache database as of the last maintenance run.
+    #[serde(default)]
+    pub cache_size: CacheSize,
+}
+
+/// Counters from the loader's dedicated cache writer thread.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct WriterStats {
+    /// Write requests waiting in the queue.
+    pub queue_depth: u64,
+    /// Rows submitted for writing.
+    pub rows_queued: u64,
+    /// Rows committed to SQLite.
+    pub rows_written: u64,
+    /// Committed rows whose value was unchanged, so only `expires_at` and `updated_at` were moved.
+    #[serde(default)]
+    pub rows_unchanged: u64,
+    /// Rows superseded by a later write to the same key before being committed.
+    pub rows_coalesced: u64,
+    /// Transactions committed.
+    pub batches_committed: u64,
+    /// Writes that failed (logged by the writer thread).
+    pub write_errors: u64,
+    /// Times a sender found the queue full and had to wait.
+    pub backpressure_waits: u64,
+    /// Total milliseconds senders spent waiting on a full queue.
+    pub backpressure_wait_ms: u64,
+}
+
+/// On-disk size of the cache database.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CacheSize {
+    /// Main database file (page count × page size).
+    pub db_bytes: u64,
+    /// Write-ahead log not yet checkpointed into the database.
+    pub wal_bytes: u64,
+    /// Free pages inside the database file, reclaimable by `VACUUM`.
+    pub free_bytes: u64,
+}
+
+impl LoaderStatus {
+    pub fn new(version: &str, heartbeat_interval_seconds: u64) -> Self {
+        let now = Utc::now();
+        Self {
+            started_at: now,
+            version: version.to_string(),
+            heartbeat_at: now,
+            heartbeat_interval_seconds,
+            last_refresh: BTreeMap::new(),
+            symbol_errors: BTreeMap::new(),
+            stream_lagged_messages: 0,
+            writer: WriterStats::default(),
+            cache_size: CacheSize::default(),
+        }
+    }
+
+    /// The loader is considered alive if it has written a heartbeat within
+    /// two heartbeat intervals.
+    pub fn is_alive(&self, now: DateTime<Utc>) -> bool {
+        let allowed = Duration::seconds(self.heartbeat_interval_seconds as i64 * 2);
+        now - self.heartbeat_at <= allowed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrip_loader_status() {
+        let mut status = LoaderStatus::new("0.1.0", 300);
+        status
+            .last_refresh
+            .insert("market_data".to_string(), Utc::now());
+        status
+            .symbol_errors
+            .insert("XYZ".to_string(), "Provider error: not found".to_string());
+        status.stream_lagged_messages = 12;
+        status.writer.rows_written = 40;
+        status.cache_size.wal_bytes = 4096;
+
+        let json = serde_json::to_string(&status).unwrap();
+        let parsed: LoaderStatus = serde_json::from_str(&json).unwrap();
+        assert_eq!(status, parsed);
+    }
+
+    #[test]
+    fn liveness_allows_two_intervals() {
+        let status = LoaderStatus::new("0.1.0", 60);
+        assert!(status.is_alive(status.heartbeat_at + Duration::seconds(120)));
+        assert!(!status.is_alive(status.heartbeat_at + Duration::seconds(121)));
+    }
+}
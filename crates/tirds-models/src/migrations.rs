@@ -0,0 +1,135 @@
+//! Versioned schema of the shared SQLite cache.
+//!
+//! The loader applies [`MIGRATIONS`] in order and records the result in the
+//! `schema_meta` table; readers compare that version with
+//! [`CURRENT_SCHEMA_VERSION`] via [`reader_compatibility`]. Migrations must be
+//! safe to run on databases created before versioning (version 0), so their
+//! DDL uses `IF NOT EXISTS`.
+
+use crate::cache_schema::{
+    CACHE_HISTORY_DDL, CACHE_TABLE_DDL, LOADER_STATUS_DDL, SYMBOL_REQUESTS_DDL,
+};
+
+/// Key/value table holding the schema version.
+///
+/// ```sql
+/// CREATE TABLE IF NOT EXISTS schema_meta (
+///     key   TEXT PRIMARY KEY,
+///     value TEXT NOT NULL
+/// );
+/// ```
+pub const SCHEMA_META_DDL: &str = "\
+CREATE TABLE IF NOT EXISTS schema_meta (
+    key   TEXT PRIMARY KEY,
+    value TEXT NOT NULL
+);
+";
+
+/// `schema_meta` key of the version the database was migrated to.
+pub const SCHEMA_VERSION_KEY: &str = "schema_version";
+/// `schema_meta` key of the oldest schema version a reader must understand
+/// to read the database.
+pub const MIN_READER_VERSION_KEY: &str = "min_reader_version";
+
+/// One forward schema change.
+#[derive(Debug)]
+pub struct Migration {
+    pub version: u32,
+    pub description: &'static str,
+    /// Statements run in order, in one transaction.
+    pub statements: &'static [&'static str],
+    /// True when readers built for earlier versions can no longer read the
+    /// database (e.g. a column they select was renamed). Additive changes
+    /// such as new tables or nullable columns leave this false.
+    pub breaks_readers: bool,
+}
+
+/// Every migration, in version order.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "cache entries, loader status and symbol requests",
+        statements: &[CACHE_TABLE_DDL, LOADER_STATUS_DDL, SYMBOL_REQUESTS_DDL],
+        breaks_readers: false,
+    },
+    Migration {
+        version: 2,
+        description: "cache value history",
+        statements: &[CACHE_HISTORY_DDL],
+        breaks_readers: false,
+    },
+];
+
+/// Schema version this build reads and writes.
+pub const CURRENT_SCHEMA_VERSION: u32 = MIGRATIONS[MIGRATIONS.len() - 1].version;
+
+/// Migrations still to run on a database at `version`.
+pub fn pending(version: u32) -> impl Iterator<Item = &'static Migration> {
+    MIGRATIONS.iter().filter(move |m| m.version > version)
+}
+
+/// Oldest reader version that can read a database migrated to `version`.
+pub fn min_reader_version(version: u32) -> u32 {
+    MIGRATIONS
+        .iter()
+        .filter(|m| m.breaks_readers && m.version <= version)
+        .map(|m| m.version)
+        .max()
+        .unwrap_or(1)
+}
+
+/// How a reader built for [`CURRENT_SCHEMA_VERSION`] relates to a database.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compatibility {
+    /// The database is at the reader's version.
+    Current,
+    /// The database predates the reader; features added since are missing.
+    Older,
+    /// The database is newer, but only with changes this reader can ignore.
+    NewerReadable,
+    /// The database is newer with changes this reader cannot read.
+    Unsupported,
+}
+
+/// Compare a database's recorded versions with this build's.
+pub fn reader_compatibility(version: u32, min_reader_version: u32) -> Compatibility {
+    if version == CURRENT_SCHEMA_VERSION {
+        Compatibility::Current
+    } else if version < CURRENT_SCHEMA_VERSION {
+        Compatibility::Older
+    } else if min_reader_version <= CURRENT_SCHEMA_VERSION {
+        Compatibility::NewerReadable
+    } else {
+        Compatibility::Unsupported
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn migrations_are_numbered_in_order() {
+        for (i, migration) in MIGRATIONS.iter().enumerate() {
+            assert_eq!(migration.version, i as u32 + 1, "{}", migration.description);
+        }
+        assert_eq!(pending(0).count(), MIGRATIONS.len());
+        assert_eq!(pending(CURRENT_SCHEMA_VERSION).count(), 0);
+    }
+
+    #[test]
+    fn compatibility_by_version() {
+        let current = CURRENT_SCHEMA_VERSION;
+        assert_eq!(reader_compatibility(current, 1), Compatibility::Current);
+        assert_eq!(reader_compatibility(0, 1), Compatibility::Older);
+        assert_eq!(
+            reader_compatibility(current + 1, current),
+            Compatibility::NewerReadable
+        );
+        assert_eq!(
+            reader_compatibility(current + 1, current + 1),
+            Compatibility::Unsupported
+        );
+        assert_eq!(min_reader_version(current), 1);
+    }
+}
@@ -0,0 +1,102 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Kinds of candle data problems detected by the loader.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QualityIssueKind {
+    /// More than one bar with the same timestamp.
+    DuplicateTimestamp,
+    /// Bars not in ascending timestamp order.
+    OutOfOrder,
+    /// High/low inconsistent with open/close, or a non-positive price.
+    InvalidOhlc,
+    /// Overnight price change matching a common split ratio.
+    SuspectedSplit,
+    /// Price far outside the recent average true range.
+    Spike,
+    ZeroVolume,
+    /// Missing bars within a session.
+    Gap,
+}
+
+/// What the loader did about an issue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QualityResolution {
+    /// Left in place.
+    Labeled,
+    /// Corrected in the cached data.
+    Repaired,
+    /// Removed from the cached data.
+    Dropped,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QualityIssue {
+    pub kind: QualityIssueKind,
+    /// Timestamp of the affected bar (for gaps, the bar after the gap).
+    pub timestamp: DateTime<Utc>,
+    pub resolution: QualityResolution,
+    pub detail: String,
+}
+
+/// Result of validating a symbol's candles, written by `tirds-loader` as
+/// `quality:{symbol}`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DataQuality {
+    /// Timestamp of the most recent bar checked.
+    pub as_of: DateTime<Utc>,
+    pub bars_checked: usize,
+    pub bars_dropped: usize,
+    pub bars_repaired: usize,
+    /// Expected bars missing inside sessions.
+    pub bars_missing: usize,
+    /// Share of expected bars that are present and had no issue, from 0.0 to 1.0.
+    /// Capped at 0.5 while an unadjusted split is suspected.
+    pub score: f64,
+    /// Issue count per kind, including issues beyond the `issues` cap.
+    pub counts: BTreeMap<QualityIssueKind, usize>,
+    /// Most recent issues, oldest first.
+    pub issues: Vec<QualityIssue>,
+}
+
+impl DataQuality {
+    pub fn is_clean(&self) -> bool {
+        self.counts.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quality_roundtrip() {
+        let mut counts = BTreeMap::new();
+        counts.insert(QualityIssueKind::ZeroVolume, 2);
+        let quality = DataQuality {
+            as_of: Utc::now(),
+            bars_checked: 78,
+            bars_dropped: 0,
+            bars_repaired: 0,
+            bars_missing: 0,
+            score: 0.97,
+            counts,
+            issues: vec![QualityIssue {
+                kind: QualityIssueKind::ZeroVolume,
+                timestamp: Utc::now(),
+                resolution: QualityResolution::Labeled,
+                detail: "volume is 0".to_string(),
+            }],
+        };
+        let json = serde_json::to_value(&quality).unwrap();
+        assert_eq!(json["counts"]["zero_volume"], 2);
+        assert_eq!(json["issues"][0]["resolution"], "labeled");
+        let parsed: DataQuality = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, quality);
+        assert!(!parsed.is_clean());
+    }
+}
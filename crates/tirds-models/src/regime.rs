@@ -0,0 +1,145 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Cache key scope of the market-wide regime (`regime:market`).
+pub const MARKET_SCOPE: &str = "market";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrendRegime {
+    Uptrend,
+    Downtrend,
+    Range,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VolatilityRegime {
+    Low,
+    Normal,
+    Elevated,
+    Extreme,
+}
+
+/// Share of sector ETFs trading above their trend average.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BreadthRegime {
+    Strong,
+    Mixed,
+    Weak,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RiskRegime {
+    RiskOn,
+    Neutral,
+    RiskOff,
+}
+
+/// Values the trend classification was based on.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrendInputs {
+    pub close: f64,
+    pub fast_sma: f64,
+    pub slow_sma: f64,
+    /// Net move over the slow window divided by the sum of absolute bar moves
+    /// (0 = pure chop, 1 = straight line).
+    pub efficiency_ratio: f64,
+}
+
+/// Values the volatility classification was based on.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VolatilityInputs {
+    /// Latest volatility index close, when used.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vix: Option<f64>,
+    /// ATR as a percentage of the latest close.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub atr_percent: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BreadthInputs {
+    /// Sector ETFs closing above their slow average.
+    pub above: Vec<String>,
+    /// Sector ETFs closing at or below it.
+    pub below: Vec<String>,
+}
+
+/// Market-wide regime written by `tirds-loader` as `regime:market`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MarketRegime {
+    pub as_of: DateTime<Utc>,
+    /// Symbol the trend is measured on (e.g., SPY).
+    pub benchmark: String,
+    pub trend: TrendRegime,
+    pub trend_inputs: TrendInputs,
+    pub volatility: VolatilityRegime,
+    pub volatility_inputs: VolatilityInputs,
+    /// None when no sector ETFs are loaded.
+    pub breadth: Option<BreadthRegime>,
+    pub breadth_inputs: Option<BreadthInputs>,
+    pub risk: RiskRegime,
+}
+
+/// Per-symbol regime written as `regime:{symbol}`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SymbolRegime {
+    pub as_of: DateTime<Utc>,
+    pub symbol: String,
+    pub trend: TrendRegime,
+    pub trend_inputs: TrendInputs,
+    pub volatility: VolatilityRegime,
+    pub volatility_inputs: VolatilityInputs,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn regimes_serialize_snake_case() {
+        assert_eq!(
+            serde_json::to_string(&RiskRegime::RiskOff).unwrap(),
+            "\"risk_off\""
+        );
+        assert_eq!(
+            serde_json::to_string(&VolatilityRegime::Elevated).unwrap(),
+            "\"elevated\""
+        );
+        assert!(VolatilityRegime::Extreme > VolatilityRegime::Normal);
+    }
+
+    #[test]
+    fn market_regime_roundtrip() {
+        let regime = MarketRegime {
+            as_of: Utc::now(),
+            benchmark: "SPY".to_string(),
+            trend: TrendRegime::Uptrend,
+            trend_inputs: TrendInputs {
+                close: 501.0,
+                fast_sma: 499.0,
+                slow_sma: 495.0,
+                efficiency_ratio: 0.6,
+            },
+            volatility: VolatilityRegime::Normal,
+            volatility_inputs: VolatilityInputs {
+                vix: Some(16.5),
+                atr_percent: None,
+            },
+            breadth: Some(BreadthRegime::Mixed),
+            breadth_inputs: Some(BreadthInputs {
+                above: vec!["XLK".to_string()],
+                below: vec!["XLE".to_string()],
+            }),
+            risk: RiskRegime::RiskOn,
+        };
+        let json = serde_json::to_value(&regime).unwrap();
+        assert_eq!(json["risk"], "risk_on");
+        assert!(json["volatility_inputs"].get("atr_percent").is_none());
+        let parsed: MarketRegime = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, regime);
+    }
+}
@@ -0,0 +1,69 @@
+use serde::{Deserialize, Serialize};
+
+/// GICS sectors and the SPDR Select Sector ETF that tracks each.
+pub const SECTOR_ETFS: &[(&str, &str)] = &[
+    ("Communication Services", "XLC"),
+    ("Consumer Discretionary", "XLY"),
+    ("Consumer Staples", "XLP"),
+    ("Energy", "XLE"),
+    ("Financials", "XLF"),
+    ("Health Care", "XLV"),
+    ("Industrials", "XLI"),
+    ("Information Technology", "XLK"),
+    ("Materials", "XLB"),
+    ("Real Estate", "XLRE"),
+    ("Utilities", "XLU"),
+];
+
+/// The sector ETF for a GICS sector name (case-insensitive).
+pub fn sector_etf(sector: &str) -> Option<&'static str> {
+    SECTOR_ETFS
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(sector.trim()))
+        .map(|(_, etf)| *etf)
+}
+
+/// Sector classification for one symbol, written to the cache as `meta:{symbol}`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SymbolMeta {
+    pub symbol: String,
+    /// GICS sector name (e.g., "Information Technology").
+    pub sector: String,
+    /// GICS industry, if known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub industry: Option<String>,
+    /// ETF tracking the sector. Defaults to the SPDR sector ETF for `sector`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sector_etf: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sector_etf_lookup_is_case_insensitive() {
+        assert_eq!(sector_etf("Information Technology"), Some("XLK"));
+        assert_eq!(sector_etf(" health care "), Some("XLV"));
+        assert_eq!(sector_etf("Crypto"), None);
+    }
+
+    #[test]
+    fn meta_serializes_without_empty_fields() {
+        let meta = SymbolMeta {
+            symbol: "AAPL".to_string(),
+            sector: "Information Technology".to_string(),
+            industry: None,
+            sector_etf: Some("XLK".to_string()),
+        };
+        let json = serde_json::to_value(&meta).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "symbol": "AAPL",
+                "sector": "Information Technology",
+                "sector_etf": "XLK"
+            })
+        );
+    }
+}
@@ -0,0 +1,62 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Lifecycle of an on-demand symbol load requested by the evaluator.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SymbolRequestStatus {
+    /// Waiting for the loader to pick it up.
+    Pending,
+    /// Candles and indicators have been written to the cache.
+    Loaded,
+    /// The loader could not obtain data for the symbol.
+    Failed,
+}
+
+impl SymbolRequestStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Loaded => "loaded",
+            Self::Failed => "failed",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "pending" => Some(Self::Pending),
+            "loaded" => Some(Self::Loaded),
+            "failed" => Some(Self::Failed),
+            _ => None,
+        }
+    }
+}
+
+/// A row of the `symbol_requests` table (see
+/// [`SYMBOL_REQUESTS_DDL`](crate::cache_schema::SYMBOL_REQUESTS_DDL)).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SymbolRequest {
+    pub symbol: String,
+    pub status: SymbolRequestStatus,
+    pub requested_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+    /// Loader error message when `status` is `Failed`.
+    pub error: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn status_str_roundtrip() {
+        for status in [
+            SymbolRequestStatus::Pending,
+            SymbolRequestStatus::Loaded,
+            SymbolRequestStatus::Failed,
+        ] {
+            assert_eq!(SymbolRequestStatus::parse(status.as_str()), Some(status));
+        }
+        assert_eq!(SymbolRequestStatus::parse("unknown"), None);
+    }
+}
@@ -3,7 +3,7 @@ use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-pub const OUTPUT_SCHEMA_VERSION: u32 = 1;
+pub const OUTPUT_SCHEMA_VERSION: u32 = 2;
 
 /// The complete decision output for a TradeProposal.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -23,6 +23,10 @@ pub struct TradeDecision {
     pub timeline: Vec<TimelinePoint>,
     pub agent_reports: Vec<AgentReport>,
     pub processing_time_ms: u64,
+    /// Which data, prompts and models this decision was based on.
+    /// Empty for decisions recorded before provenance was tracked.
+    #[serde(default)]
+    pub provenance: DecisionProvenance,
 }
 
 /// Assessment of a single trade leg.
@@ -114,6 +118,41 @@ pub struct AgentReport {
     pub elapsed_ms: u64,
 }
 
+/// Provenance of a decision: the data snapshot, prompt templates and models used.
+///
+/// Two decisions with the same `snapshot_hash` were made on identical cache data.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct DecisionProvenance {
+    /// Hex-encoded SHA-256 of the canonical domain snapshot JSON.
+    pub snapshot_hash: String,
+    /// Cache entries included in the snapshot.
+    pub data_sources: Vec<DataSourceRef>,
+    /// Prompt template version and model used by each agent, including the synthesizer.
+    pub agents: Vec<AgentProvenance>,
+}
+
+/// A cache entry that contributed to the domain snapshot.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DataSourceRef {
+    pub key: String,
+    pub category: String,
+    /// The pipeline that wrote the entry (e.g., "market-data").
+    pub source: String,
+    /// RFC3339 timestamp as stored in the cache.
+    pub updated_at: String,
+}
+
+/// Prompt and model identity for a single agent.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AgentProvenance {
+    pub agent_name: String,
+    pub domain: String,
+    /// Model ID, if the agent is backed by a model.
+    pub model: Option<String>,
+    /// Version of the system prompt template, if the domain has one.
+    pub prompt_version: Option<String>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -215,6 +254,30 @@ mod tests {
                 elapsed_ms: 2500,
             }],
             processing_time_ms: 5000,
+            provenance: DecisionProvenance {
+                snapshot_hash: "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08"
+                    .to_string(),
+                data_sources: vec![DataSourceRef {
+                    key: "indicator:rsi_14:AAPL".to_string(),
+                    category: "indicator".to_string(),
+                    source: "market-calculations".to_string(),
+                    updated_at: "2024-02-16T10:30:00+00:00".to_string(),
+                }],
+                agents: vec![
+                    AgentProvenance {
+                        agent_name: "technical".to_string(),
+                        domain: "technical".to_string(),
+                        model: Some("claude-3-5-haiku-latest".to_string()),
+                        prompt_version: Some("1".to_string()),
+                    },
+                    AgentProvenance {
+                        agent_name: "synthesizer".to_string(),
+                        domain: "synthesizer".to_string(),
+                        model: Some("claude-sonnet-4-5-20250929".to_string()),
+                        prompt_version: Some("1".to_string()),
+                    },
+                ],
+            },
         }
     }
 
@@ -249,6 +312,29 @@ mod tests {
         assert_eq!(valid, parsed);
     }
 
+    #[test]
+    fn provenance_identifies_snapshot_and_agents() {
+        let decision = sample_decision();
+        let json = serde_json::to_value(&decision).unwrap();
+        assert_eq!(
+            json["provenance"]["data_sources"][0]["key"],
+            "indicator:rsi_14:AAPL"
+        );
+        assert_eq!(json["provenance"]["agents"][1]["agent_name"], "synthesizer");
+        assert_eq!(
+            json["provenance"]["snapshot_hash"].as_str().unwrap().len(),
+            64
+        );
+    }
+
+    #[test]
+    fn decision_without_provenance_deserializes() {
+        let mut json = serde_json::to_value(sample_decision()).unwrap();
+        json.as_object_mut().unwrap().remove("provenance");
+        let decision: TradeDecision = serde_json::from_value(json).unwrap();
+        assert_eq!(decision.provenance, DecisionProvenance::default());
+    }
+
     #[test]
     fn decision_with_no_price_target_decay() {
         let mut decision = sample_decision();
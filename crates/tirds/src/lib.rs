@@ -20,16 +20,17 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use tirds_agents::{ClaudeSpecialist, Orchestrator, SpecialistAgent};
-use tirds_cache::{CacheReader, SqliteReader};
+use tirds_cache::{CacheReader, ReaderPool, SqliteReader, SymbolDemand};
 use tirds_models::config::TirdsConfig;
+use tirds_models::loader_status::LoaderStatus;
 use tirds_models::trade_decision::TradeDecision;
 use tirds_models::trade_input::TradeProposal;
 
 /// Build an Orchestrator from configuration.
 pub fn build_orchestrator(config: &TirdsConfig) -> Result<Orchestrator, anyhow::Error> {
-    let sqlite = SqliteReader::open(&config.cache.sqlite_path)?;
-    let cache = Arc::new(CacheReader::new(
-        sqlite,
+    let pool = ReaderPool::open(&config.cache.sqlite_path, config.cache.read_connections)?;
+    let cache = Arc::new(CacheReader::with_pool(
+        pool,
         config.cache.memory_max_capacity,
         Duration::from_secs(config.cache.memory_ttl_seconds),
     ));
@@ -54,7 +55,13 @@ pub fn build_orchestrator(config: &TirdsConfig) -> Result<Orchestrator, anyhow::
         })
         .collect();
 
-    Ok(Orchestrator::new(specialists, cache, config.agents.clone()))
+    let mut orchestrator = Orchestrator::new(specialists, cache, config.agents.clone());
+    if config.agents.on_demand.enabled {
+        let demand = SymbolDemand::open(&config.cache.sqlite_path)?;
+        orchestrator = orchestrator.with_symbol_demand(Arc::new(demand));
+    }
+
+    Ok(orchestrator)
 }
 
 /// Evaluate a trade proposal using the given orchestrator.
@@ -64,3 +71,10 @@ pub async fn evaluate(
 ) -> Result<TradeDecision, tirds_agents::AgentError> {
     orchestrator.evaluate(proposal).await
 }
+
+/// Read the loader's heartbeat record from the shared cache.
+/// Returns None if no loader has written one.
+pub fn loader_status(config: &TirdsConfig) -> Result<Option<LoaderStatus>, anyhow::Error> {
+    let sqlite = SqliteReader::open(&config.cache.sqlite_path)?;
+    Ok(sqlite.loader_status()?)
+}
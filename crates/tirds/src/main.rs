@@ -20,6 +20,10 @@ struct Cli {
     /// Pretty-print the output JSON
     #[arg(long)]
     pretty: bool,
+
+    /// Report the cache loader's heartbeat status and exit (non-zero if stale or missing)
+    #[arg(long)]
+    health: bool,
 }
 
 #[tokio::main]
@@ -38,6 +42,24 @@ async fn main() -> Result<()> {
     let config: TirdsConfig =
         toml::from_str(&config_str).with_context(|| "Failed to parse config")?;
 
+    if cli.health {
+        let status = tirds::loader_status(&config).context("Failed to read loader status")?;
+        let alive = status
+            .as_ref()
+            .is_some_and(|s| s.is_alive(chrono::Utc::now()));
+        let report = serde_json::json!({ "alive": alive, "status": status });
+        let output = if cli.pretty {
+            serde_json::to_string_pretty(&report)?
+        } else {
+            serde_json::to_string(&report)?
+        };
+        println!("{output}");
+        if !alive {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
     // Read proposal
     let proposal_json = if let Some(input_path) = &cli.input {
         std::fs::read_to_string(input_path)